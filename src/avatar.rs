@@ -0,0 +1,211 @@
+use base64::{prelude::BASE64_STANDARD, Engine as _};
+use std::{
+    env, fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// How long a cached avatar is used before it's downloaded again.
+const CACHE_LIFETIME: Duration = Duration::from_hours(7 * 24);
+
+/// The largest amount of base64-encoded data which can be sent in a single kitty graphics command.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// The first bytes of every PNG file.
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// The width in terminal cells at which avatars are displayed.
+pub const WIDTH: usize = 2;
+
+/// A terminal graphics protocol which can display inline images.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Protocol {
+    /// The kitty graphics protocol, which only accepts PNGs here.
+    Kitty,
+    /// iTerm2's inline images protocol, which accepts any common image format.
+    ITerm2,
+}
+
+impl Protocol {
+    /// Returns the graphics protocol supported by the terminal, if stdout is a terminal and it's
+    /// known to support one.
+    pub fn detect() -> Option<Self> {
+        if !io::stdout().is_terminal() {
+            return None;
+        }
+        Self::detect_from(|name| env::var(name).ok())
+    }
+
+    /// Returns the graphics protocol indicated by the environment variables returned by `var`.
+    fn detect_from<F: Fn(&str) -> Option<String>>(var: F) -> Option<Self> {
+        let term = var("TERM").unwrap_or_default();
+        let term_program = var("TERM_PROGRAM").unwrap_or_default();
+        if var("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || term.contains("ghostty")
+            || term_program == "ghostty"
+        {
+            Some(Protocol::Kitty)
+        } else if term_program == "iTerm.app"
+            || term_program == "WezTerm"
+            || var("LC_TERMINAL").as_deref() == Some("iTerm2")
+        {
+            Some(Protocol::ITerm2)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the escape sequences which display `image` `WIDTH` cells wide and one cell high,
+    /// leaving the cursor after it, or `None` if the protocol can't display `image`.
+    pub fn render(self, image: &[u8]) -> Option<String> {
+        match self {
+            Protocol::Kitty => {
+                if !image.starts_with(PNG_SIGNATURE) {
+                    return None;
+                }
+                let encoded = BASE64_STANDARD.encode(image);
+                let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+                let last = chunks.len() - 1;
+                let rendered = chunks
+                    .iter()
+                    .enumerate()
+                    .map(|(index, chunk)| {
+                        let more = u8::from(index != last);
+                        let chunk = String::from_utf8_lossy(chunk);
+                        if index == 0 {
+                            format!("\x1b_Ga=T,f=100,c={WIDTH},r=1,q=2,m={more};{chunk}\x1b\\")
+                        } else {
+                            format!("\x1b_Gm={more};{chunk}\x1b\\")
+                        }
+                    })
+                    .collect();
+                Some(rendered)
+            }
+            Protocol::ITerm2 => Some(format!(
+                "\x1b]1337;File=inline=1;size={};width={WIDTH};height=1;\
+                 preserveAspectRatio=1:{}\x07",
+                image.len(),
+                BASE64_STANDARD.encode(image)
+            )),
+        }
+    }
+}
+
+/// Returns the path at which the avatar of `owner` on `host` is cached, under `$XDG_CACHE_HOME`
+/// or else `~/.cache`.
+pub fn cache_path(host: &str, owner: &str) -> Option<PathBuf> {
+    cache_path_from(host, owner, |name| env::var_os(name).map(PathBuf::from))
+}
+
+fn cache_path_from<F: Fn(&str) -> Option<PathBuf>>(
+    host: &str,
+    owner: &str,
+    var: F,
+) -> Option<PathBuf> {
+    Some(
+        cache_dir_from(var)?
+            .join("avatars")
+            .join(host)
+            .join(owner.to_lowercase()),
+    )
+}
+
+/// Returns `add-remote`'s cache directory under `$XDG_CACHE_HOME` (if absolute) or else
+/// `~/.cache`, reading the environment variables via `var`.
+pub fn cache_dir_from<F: Fn(&str) -> Option<PathBuf>>(var: F) -> Option<PathBuf> {
+    let cache_dir = var("XDG_CACHE_HOME")
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| var("HOME").map(|home| home.join(".cache")))?;
+    Some(cache_dir.join("add-remote"))
+}
+
+/// Returns the avatar cached at `path`, unless it's missing or has expired.
+pub fn read_cached(path: &Path) -> Option<Vec<u8>> {
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+    let age = SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default();
+    if age > CACHE_LIFETIME {
+        return None;
+    }
+    fs::read(path).ok()
+}
+
+/// Caches `image` at `path`.  Failure isn't reported since the avatar can be downloaded again.
+pub fn write_cached(path: &Path, image: &[u8]) {
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir).and_then(|()| fs::write(path, image));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Protocol, KITTY_CHUNK_SIZE, PNG_SIGNATURE};
+    use std::{collections::HashMap, path::PathBuf};
+
+    #[test]
+    fn detect_protocol() {
+        let detect = |vars: &[(&str, &str)]| {
+            let vars: HashMap<_, _> = vars.iter().copied().collect();
+            Protocol::detect_from(|name| vars.get(name).map(ToString::to_string))
+        };
+        assert_eq!(detect(&[]), None);
+        assert_eq!(detect(&[("TERM", "xterm-256color")]), None);
+        assert_eq!(detect(&[("TERM", "xterm-kitty")]), Some(Protocol::Kitty));
+        assert_eq!(detect(&[("KITTY_WINDOW_ID", "1")]), Some(Protocol::Kitty));
+        assert_eq!(
+            detect(&[("TERM_PROGRAM", "iTerm.app")]),
+            Some(Protocol::ITerm2)
+        );
+        assert_eq!(detect(&[("LC_TERMINAL", "iTerm2")]), Some(Protocol::ITerm2));
+    }
+
+    #[test]
+    fn render() {
+        let jpeg = b"\xff\xd8\xff\xe0";
+        assert!(Protocol::Kitty.render(jpeg).is_none());
+        assert_eq!(
+            Protocol::ITerm2.render(jpeg).unwrap(),
+            "\x1b]1337;File=inline=1;size=4;width=2;height=1;preserveAspectRatio=1:/9j/4A==\x07"
+        );
+
+        let small_png = PNG_SIGNATURE.to_vec();
+        assert_eq!(
+            Protocol::Kitty.render(&small_png).unwrap(),
+            "\x1b_Ga=T,f=100,c=2,r=1,q=2,m=0;iVBORw0KGgo=\x1b\\"
+        );
+
+        // Large images are split into chunks, all but the last of which are marked as having more
+        // to follow.
+        let mut large_png = PNG_SIGNATURE.to_vec();
+        large_png.resize(KITTY_CHUNK_SIZE, 0);
+        let rendered = Protocol::Kitty.render(&large_png).unwrap();
+        let commands: Vec<_> = rendered.split("\x1b\\").filter(|c| !c.is_empty()).collect();
+        assert_eq!(commands.len(), 2);
+        assert!(commands[0].starts_with("\x1b_Ga=T,f=100,c=2,r=1,q=2,m=1;"));
+        assert!(commands[1].starts_with("\x1b_Gm=0;"));
+    }
+
+    #[test]
+    fn cache_path() {
+        let cache_path = |vars: &[(&str, &str)]| {
+            let vars: HashMap<_, _> = vars.iter().copied().collect();
+            super::cache_path_from("github", "Bob", |name| vars.get(name).map(PathBuf::from))
+        };
+        assert_eq!(
+            cache_path(&[("XDG_CACHE_HOME", "/cache"), ("HOME", "/home/a")]),
+            Some(PathBuf::from("/cache/add-remote/avatars/github/bob"))
+        );
+        assert_eq!(
+            cache_path(&[("XDG_CACHE_HOME", "relative"), ("HOME", "/home/a")]),
+            Some(PathBuf::from(
+                "/home/a/.cache/add-remote/avatars/github/bob"
+            ))
+        );
+        assert_eq!(cache_path(&[]), None);
+    }
+}
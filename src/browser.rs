@@ -0,0 +1,57 @@
+use std::{env, process::Command};
+
+/// Opens `url` in the browser named by the environment variable `BROWSER` if it's set, or else in
+/// the OS's default browser.
+pub fn open(url: &str) -> Result<(), String> {
+    let (program, args) = launcher(env::var("BROWSER").ok(), url);
+    let status = Command::new(&program)
+        .args(&args)
+        .status()
+        .map_err(|error| format!("couldn't run '{program}': {error}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("'{program}' exited with {status}"))
+    }
+}
+
+/// Returns the program and arguments which open `url`, using `browser` if it's set and not empty.
+fn launcher(browser: Option<String>, url: &str) -> (String, Vec<String>) {
+    if let Some(browser) = browser.filter(|browser| !browser.trim().is_empty()) {
+        return (browser, vec![url.to_string()]);
+    }
+    if cfg!(target_os = "macos") {
+        ("open".to_string(), vec![url.to_string()])
+    } else if cfg!(windows) {
+        // The empty argument is the title of the window `start` would open, without which a quoted
+        // URL would be taken as the title.
+        (
+            "cmd".to_string(),
+            vec![
+                "/C".to_string(),
+                "start".to_string(),
+                String::new(),
+                url.to_string(),
+            ],
+        )
+    } else {
+        ("xdg-open".to_string(), vec![url.to_string()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::launcher;
+
+    #[test]
+    fn browser_overrides_default() {
+        let url = "https://github.com/bob/repo";
+        assert_eq!(
+            launcher(Some("firefox".to_string()), url),
+            ("firefox".to_string(), vec![url.to_string()])
+        );
+        let (program, args) = launcher(Some(" ".to_string()), url);
+        assert_ne!(program, " ");
+        assert_eq!(args.last().map(String::as_str), Some(url));
+    }
+}
@@ -0,0 +1,1429 @@
+use super::{
+    pattern::Pattern,
+    remote_name::{self, RemoteNameTemplate},
+};
+use clap::{
+    error::ErrorKind, parser::ValueSource, Arg, ArgAction, ArgMatches, Args, CommandFactory, Error,
+    FromArgMatches, Parser,
+};
+use std::{iter, time::Duration};
+
+/// The action requested on the command line.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub enum Command {
+    /// Choose a fork and add it as a new remote.
+    #[default]
+    Add,
+    /// List the forks which could be added as remotes.
+    List,
+    /// Normalise the configuration of the remotes managed by `add-remote`.
+    Tidy,
+    /// List the branches of the given remote which have no counterpart in the upstream remote.
+    Compare(String),
+    /// Check out a local branch tracking a managed remote's branch which fuzzily matches the given
+    /// pattern.
+    Switch(String),
+    /// Rename the given remote to the given name, asking for whichever isn't given.
+    Rename(Option<String>, Option<String>),
+    /// Offer to rename each remote whose owner has a configured alias which differs from its name.
+    RenameAll,
+    /// Remove the given remote, or else one chosen from a list of the remotes.
+    Remove(Option<String>),
+    /// Offer to remove each remote whose repository has been deleted or archived.
+    Prune,
+    /// Change the URLs of the given remotes, or of all of them if `--all` was passed, to the given
+    /// form.
+    Convert(UrlForm, Vec<String>),
+    /// Report on whether each remote's repository and configuration are as expected.
+    Audit,
+    /// Print the JSON Schema of the report printed by `add-remote audit --format json`.
+    Schema,
+    /// Fetch every remote in parallel, then list the branches which are new.
+    Sync,
+    /// Store a token for the given forge host in the OS keyring.
+    AuthSet(String),
+    /// Remove the token stored for the given forge host from the OS keyring.
+    AuthDelete(String),
+    /// Log in to the given forge via OAuth and store the resulting token in the OS keyring.
+    Login(LoginForge),
+    /// Check that `add-remote` is set up correctly, printing a pass/fail report.
+    Doctor,
+}
+
+/// The Git config file in which custom aliases are stored.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum AliasScope {
+    /// The user's global config, so the alias applies to all repositories.
+    #[default]
+    Global,
+    /// The current repository's config, so the alias only applies to it.
+    Local,
+}
+
+impl AliasScope {
+    /// Parses `value`, which must be "global" or "local".
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "global" => Ok(AliasScope::Global),
+            "local" => Ok(AliasScope::Local),
+            _ => Err(format!(
+                "Invalid alias scope '{value}': expected 'global' or 'local'."
+            )),
+        }
+    }
+
+    /// The option passed to `git config` to select this scope.
+    pub fn git_config_option(self) -> &'static str {
+        match self {
+            AliasScope::Global => "--global",
+            AliasScope::Local => "--local",
+        }
+    }
+
+    /// A description of the config file for this scope.
+    pub fn description(self) -> &'static str {
+        match self {
+            AliasScope::Global => "your global git-config",
+            AliasScope::Local => "this repository's git-config",
+        }
+    }
+}
+
+/// The order in which the available forks are listed.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum ForkOrder {
+    /// Alphabetically by owner, with members of the main fork/source's organisation/group first.
+    #[default]
+    Name,
+    /// Most recently active first.
+    Activity,
+    /// Most starred first.
+    Stars,
+}
+
+impl ForkOrder {
+    /// Parses `value`, which must be "name", "activity" or "stars".
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "name" => Ok(ForkOrder::Name),
+            "activity" => Ok(ForkOrder::Activity),
+            "stars" => Ok(ForkOrder::Stars),
+            _ => Err(format!(
+                "Invalid sort order '{value}': expected 'name', 'activity' or 'stars'."
+            )),
+        }
+    }
+}
+
+/// The API via which the forks are listed.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum ForkApi {
+    /// The forge's REST API, paginated by links.
+    #[default]
+    Rest,
+    /// The forge's GraphQL API, where supported, which returns the details of up to 100 forks per
+    /// request.
+    GraphQl,
+}
+
+impl ForkApi {
+    /// Parses `value`, which must be "rest" or "graphql".
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "rest" => Ok(ForkApi::Rest),
+            "graphql" => Ok(ForkApi::GraphQl),
+            _ => Err(format!(
+                "Invalid API '{value}': expected 'rest' or 'graphql'."
+            )),
+        }
+    }
+}
+
+/// What to do if the input ends before a prompt is answered, e.g. when piped answers run out.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum OnEof {
+    /// Exit with an error.
+    #[default]
+    Fail,
+    /// Accept the prompt's default if it has one, or else exit with an error.
+    UseDefault,
+}
+
+impl OnEof {
+    /// Parses `value`, which must be "fail" or "default".
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "fail" => Ok(OnEof::Fail),
+            "default" => Ok(OnEof::UseDefault),
+            _ => Err(format!(
+                "Invalid end-of-input action '{value}': expected 'fail' or 'default'."
+            )),
+        }
+    }
+}
+
+/// A forge which `add-remote login` can log in to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LoginForge {
+    GitHub,
+    GitLab,
+}
+
+impl LoginForge {
+    /// Parses `value`, which must be "github" or "gitlab".
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "github" => Ok(LoginForge::GitHub),
+            "gitlab" => Ok(LoginForge::GitLab),
+            _ => Err(format!(
+                "Invalid forge '{value}': expected 'github' or 'gitlab'."
+            )),
+        }
+    }
+}
+
+/// Which form of URL the new remote is given.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum RemoteProtocol {
+    /// SSH, unless all of the existing remotes on supported forges use HTTPS.
+    #[default]
+    Auto,
+    /// Always SSH.
+    Ssh,
+    /// Always HTTPS.
+    Https,
+}
+
+impl RemoteProtocol {
+    /// Parses `value`, which must be "auto", "ssh" or "https".
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "auto" => Ok(RemoteProtocol::Auto),
+            "ssh" => Ok(RemoteProtocol::Ssh),
+            "https" => Ok(RemoteProtocol::Https),
+            _ => Err(format!(
+                "Invalid protocol '{value}': expected 'auto', 'ssh' or 'https'."
+            )),
+        }
+    }
+}
+
+/// The form of a remote's URL, which determines how it's accessed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UrlForm {
+    /// `git@<host>:<path>.git`, which needs SSH keys.
+    Ssh,
+    /// `https://<host>/<path>.git`, which needs credentials only for pushing or private
+    /// repositories.
+    Https,
+}
+
+impl UrlForm {
+    /// Parses `value`, which must be "ssh" or "https".
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "ssh" => Ok(UrlForm::Ssh),
+            "https" => Ok(UrlForm::Https),
+            _ => Err(format!(
+                "Invalid URL form '{value}': expected 'ssh' or 'https'."
+            )),
+        }
+    }
+}
+
+/// The format of a report.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+    /// Coloured lines of text.
+    #[default]
+    Human,
+    /// A JSON array, for consumption by other tools.
+    Json,
+}
+
+impl OutputFormat {
+    /// Parses `value`, which must be "human" or "json".
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "human" => Ok(OutputFormat::Human),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!(
+                "Invalid format '{value}': expected 'human' or 'json'."
+            )),
+        }
+    }
+}
+
+/// The options passed on the command line.
+#[derive(Default, Debug)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Options {
+    /// The action to perform.
+    pub command: Command,
+    /// Whether to print the help message and exit.
+    pub help: bool,
+    /// Whether to print the version and exit.
+    pub version: bool,
+    /// If set, only forks whose owner matches this are listed.
+    pub filter: Option<Pattern>,
+    /// Whether to produce plain, screen-reader-friendly output with no colours or columns.
+    pub plain: bool,
+    /// Whether to produce deterministic output with no colours and fixed column widths, suitable
+    /// for comparing against golden files.
+    pub stable: bool,
+    /// Whether to only list the forks from which merge/pull requests are open.
+    pub from_prs: bool,
+    /// Whether to show fork owners' avatars in terminals which can display images.
+    pub avatars: bool,
+    /// If set, only forks which have been active within this long are listed.
+    pub since: Option<Duration>,
+    /// If set, the order in which the forks are listed.
+    pub sort: Option<ForkOrder>,
+    /// Whether to list the forks from the cache rather than querying the forge.
+    pub offline: bool,
+    /// If set, the API via which the forks are listed.
+    pub api: Option<ForkApi>,
+    /// If set, forks of forks are listed too, down to this many levels below the main fork/source.
+    pub recursive: Option<usize>,
+    /// Whether to fail rather than prompt when a choice can't be made without asking.
+    pub no_input: bool,
+    /// What to do if the input ends before a prompt is answered.
+    pub on_eof: OnEof,
+    /// If set, how long to wait between repeatedly querying for new forks.
+    pub watch: Option<Duration>,
+    /// If set, the name of the new remote is expanded from this rather than asked for.
+    pub remote_name: Option<RemoteNameTemplate>,
+    /// If set, the owner of the fork to add, rather than asking for a choice.  Implies `no_input`.
+    pub fork: Option<String>,
+    /// If set, the name of the new remote, rather than asking for one.
+    pub alias: Option<String>,
+    /// If set, the maximum number of concurrent API requests and `git fetch` jobs.
+    pub jobs: Option<usize>,
+    /// If set, how long to wait for an API request to complete before giving up on it.
+    pub timeout: Option<Duration>,
+    /// If set, how many times to retry an API request which fails transiently.
+    pub retries: Option<usize>,
+    /// Whether to skip verifying the TLS certificates of the forges' APIs.
+    pub insecure: bool,
+    /// Whether to log what's done to stderr.
+    pub verbose: bool,
+    /// Whether to suppress output which isn't needed to answer the prompts.
+    pub quiet: bool,
+    /// If set, where custom aliases are stored.
+    pub alias_scope: Option<AliasScope>,
+    /// If set, which form of URL the new remote is given.
+    pub protocol: Option<RemoteProtocol>,
+    /// Whether to leave pushing to the new remote enabled.
+    pub allow_push: bool,
+    /// If set, the only branch of the new remote to fetch.
+    pub branch: Option<String>,
+    /// Whether to add the new remote without fetching from it.
+    pub no_fetch: bool,
+    /// If set, how many commits of history to fetch from the tip of each of the new remote's
+    /// branches.
+    pub depth: Option<usize>,
+    /// If set, the filter for a partial fetch of the new remote, e.g. `blob:none`.
+    pub filter_spec: Option<String>,
+    /// Whether to never fetch the new remote's tags.
+    pub no_tags: bool,
+    /// Whether to open the new remote's web page in the browser once it's added.
+    pub open: bool,
+    /// Whether to print the Git commands which would add the chosen fork rather than running them.
+    pub dry_run: bool,
+    /// Whether to choose the fork in a full-screen interface rather than at a prompt.
+    pub tui: bool,
+    /// The directories passed via `-C`, each relative to the previous one, to change to before
+    /// running, as Git does.
+    pub directories: Vec<String>,
+    /// The Git config keys and values passed via `-c`, which apply to every Git invocation.
+    pub git_config: Vec<(String, String)>,
+    /// If set, the path to the repository passed via `--git-dir`.
+    pub git_dir: Option<String>,
+    /// If set, the client ID of the OAuth application used by `add-remote login`.
+    pub client_id: Option<String>,
+    /// Whether `add-remote remove` deletes the removed remote's owner's alias without asking.
+    pub forget_alias: bool,
+    /// Whether `add-remote convert` changes the URLs of all the remotes.
+    pub all_remotes: bool,
+    /// The format of the report printed by `add-remote audit`.
+    pub format: OutputFormat,
+}
+
+impl Options {
+    /// Parses `args`, which should not include the name of the executable.  If help for a
+    /// subcommand is requested, it's printed and the process exits.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        // Support the Windows-style request for help too.
+        let args = args.into_iter().map(|arg| {
+            if arg == "/?" {
+                "--help".to_string()
+            } else {
+                arg
+            }
+        });
+        let mut command = command();
+        let matches = command
+            .try_get_matches_from_mut(iter::once("add-remote".to_string()).chain(args))
+            .and_then(|mut matches| {
+                check_add_args_unused(&mut command, &matches)?;
+                Cli::from_arg_matches_mut(&mut matches)
+            });
+        let cli = match matches {
+            Ok(cli) => cli,
+            Err(error) if error.kind() == ErrorKind::DisplayHelp => error.exit(),
+            Err(error) => return Err(error.render().to_string().trim_end().to_string()),
+        };
+
+        let mut options = Options {
+            help: cli.help,
+            version: cli.version,
+            plain: cli.global.plain,
+            stable: cli.global.stable,
+            no_input: cli.global.no_input,
+            on_eof: cli.global.on_eof,
+            jobs: cli.global.jobs,
+            timeout: cli.global.timeout,
+            retries: cli.global.retries,
+            insecure: cli.global.insecure,
+            verbose: cli.global.verbose,
+            quiet: cli.global.quiet,
+            directories: cli.global.directories,
+            git_config: cli.global.git_config,
+            git_dir: cli.global.git_dir,
+            ..Options::default()
+        };
+        let add_args = match cli.command {
+            None => Some(cli.add),
+            Some(subcommand) => options.set_command(subcommand),
+        };
+        if let Some(add_args) = add_args {
+            options.set_add_args(add_args);
+        }
+        Ok(options)
+    }
+
+    /// Records `subcommand` and its arguments, returning the arguments of the `add` command to be
+    /// recorded by `set_add_args` if that's the one given.
+    fn set_command(&mut self, subcommand: Subcommand) -> Option<AddArgs> {
+        match subcommand {
+            Subcommand::Add(add_args) => Some(add_args),
+            Subcommand::List(list_args) => {
+                self.command = Command::List;
+                self.set_list_args(list_args);
+                None
+            }
+            Subcommand::Tidy => {
+                self.command = Command::Tidy;
+                None
+            }
+            Subcommand::Compare { remote } => {
+                self.command = Command::Compare(remote);
+                None
+            }
+            Subcommand::Switch { pattern } => {
+                self.command = Command::Switch(pattern);
+                None
+            }
+            Subcommand::Rename { old, new } => {
+                self.command = Command::Rename(old, new);
+                None
+            }
+            Subcommand::RenameAll => {
+                self.command = Command::RenameAll;
+                None
+            }
+            Subcommand::Remove {
+                remote,
+                forget_alias,
+                dry_run,
+            } => {
+                self.command = Command::Remove(remote);
+                self.forget_alias = forget_alias;
+                self.dry_run = dry_run;
+                None
+            }
+            Subcommand::Prune { dry_run } => {
+                self.command = Command::Prune;
+                self.dry_run = dry_run;
+                None
+            }
+            Subcommand::Convert { to, remotes, all } => {
+                self.command = Command::Convert(to, remotes);
+                self.all_remotes = all;
+                None
+            }
+            Subcommand::Audit { format } => {
+                self.command = Command::Audit;
+                self.format = format.unwrap_or_default();
+                None
+            }
+            Subcommand::Schema => {
+                self.command = Command::Schema;
+                None
+            }
+            Subcommand::Sync => {
+                self.command = Command::Sync;
+                None
+            }
+            Subcommand::Auth(AuthSubcommand::Set { host }) => {
+                self.command = Command::AuthSet(host);
+                None
+            }
+            Subcommand::Auth(AuthSubcommand::Delete { host }) => {
+                self.command = Command::AuthDelete(host);
+                None
+            }
+            Subcommand::Doctor => {
+                self.command = Command::Doctor;
+                None
+            }
+            Subcommand::Login { forge, client_id } => {
+                self.command = Command::Login(forge);
+                self.client_id = client_id;
+                None
+            }
+        }
+    }
+
+    fn set_add_args(&mut self, add_args: AddArgs) {
+        self.set_list_args(add_args.list);
+        self.watch = add_args.watch;
+        self.remote_name = add_args.remote_name;
+        self.fork = add_args.fork;
+        self.alias = add_args.alias;
+        self.alias_scope = add_args.alias_scope;
+        self.protocol = add_args.protocol;
+        self.allow_push = add_args.allow_push;
+        self.branch = add_args.branch;
+        self.no_fetch = add_args.no_fetch;
+        self.depth = add_args.depth;
+        self.filter_spec = add_args.filter_spec;
+        self.no_tags = add_args.no_tags;
+        self.open = add_args.open;
+        self.dry_run = add_args.dry_run;
+        self.tui = add_args.tui;
+        if self.fork.is_some() {
+            self.no_input = true;
+        }
+    }
+
+    fn set_list_args(&mut self, list_args: ListArgs) {
+        self.filter = list_args.filter;
+        self.from_prs = list_args.from_prs;
+        self.avatars = list_args.avatars;
+        self.since = list_args.since;
+        self.sort = list_args.sort;
+        self.offline = list_args.offline;
+        self.api = list_args.api;
+        self.recursive = list_args.recursive;
+    }
+}
+
+/// Add a remote fork to a local Git repository.  When run from a Git repo, it queries the forge
+/// hosting it (GitLab, GitHub, Gitea, Forgejo or a generic forge configured via
+/// 'add-remote.<host>.forksUrl') for the full list of forks and offers simple choices for adding
+/// one under a local alias.
+/// The added fork will be configured with a pull-url only; the push-url will be disabled unless
+/// '--allow-push' is passed.  It will also be tagged with 'remote.<alias>.addRemoteManaged = true'
+/// and 'remote.<alias>.addRemoteOwner = <owner>' in the repository's Git config to mark it as
+/// managed by 'add-remote'.
+#[derive(Parser, Debug)]
+#[command(
+    name = "add-remote",
+    disable_help_flag = true,
+    disable_version_flag = true,
+    max_term_width = 100
+)]
+struct Cli {
+    /// Print this help message, including how to configure 'add-remote', and exit.
+    #[arg(short, long)]
+    help: bool,
+    /// Print the version and exit.
+    #[arg(short = 'V', long)]
+    version: bool,
+    #[command(flatten)]
+    global: GlobalArgs,
+    #[command(flatten)]
+    add: AddArgs,
+    #[command(subcommand)]
+    command: Option<Subcommand>,
+}
+
+/// The options which apply to every command.
+#[derive(Args, Debug)]
+#[allow(clippy::struct_excessive_bools)]
+struct GlobalArgs {
+    /// Produce plain, line-oriented output suitable for screen readers, with no colours, columns
+    /// or hyperlinks.
+    #[arg(long, global = true)]
+    plain: bool,
+    /// Produce deterministic output for testing and scripting, with no colours, fixed column
+    /// widths and a fixed ordering.
+    #[arg(long, global = true)]
+    stable: bool,
+    /// Never prompt.  Defaults are used where available, otherwise 'add-remote' exits with an
+    /// error naming the option or config value needed.
+    #[arg(long, global = true)]
+    no_input: bool,
+    /// What to do if the input ends before a prompt is answered, e.g. when answers are piped in
+    /// and run out: 'fail' (the default) to exit with an error, or 'default' to accept the
+    /// prompt's default where it has one.
+    #[arg(long, global = true, value_name = "ACTION", value_parser = OnEof::parse, default_value = "fail")]
+    on_eof: OnEof,
+    /// Make up to N API requests at once when retrieving long lists of forks, and run up to N
+    /// parallel 'git fetch' jobs.  Defaults to the Git config value of 'add-remote.jobs' if set,
+    /// or else 4.
+    #[arg(short, long, global = true, value_name = "N", value_parser = parse_jobs)]
+    jobs: Option<usize>,
+    /// Give up on an API request which hasn't completed within DURATION, e.g. '30s' or '2m'.
+    /// Defaults to the Git config value of 'add-remote.httpTimeout' if set, or else 30s.
+    #[arg(long, global = true, value_name = "DURATION", value_parser = parse_interval)]
+    timeout: Option<Duration>,
+    /// Retry an API request up to N times if it times out, fails to connect, or gets a server
+    /// error.  Defaults to the Git config value of 'add-remote.httpRetries' if set, or else 2.
+    #[arg(long, global = true, value_name = "N", value_parser = parse_retries)]
+    retries: Option<usize>,
+    /// Don't verify the TLS certificates of the forges' APIs, e.g. for a self-hosted forge whose
+    /// certificate is signed by an internal CA.  This allows requests to be intercepted, so prefer
+    /// adding the CA's certificate via 'add-remote.caBundle'.
+    #[arg(long, global = true)]
+    insecure: bool,
+    /// Log every Git command run, every API request and its response's status, and how the forks
+    /// were paginated and filtered, to stderr.  See '--help' for how to choose what's logged.
+    #[arg(short, long, global = true)]
+    verbose: bool,
+    /// Print only the prompts, the list of forks to choose from, warnings and errors: no progress
+    /// spinner, and no listing of the remotes and branches once a fork is added.  The exit code
+    /// tells scripts the outcome; see the README for the codes.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Run as if started in PATH.  May be repeated, each relative to the previous one.  An empty
+    /// PATH leaves the directory unchanged.
+    #[arg(
+        short = 'C',
+        global = true,
+        value_name = "PATH",
+        help_heading = "Git Options"
+    )]
+    directories: Vec<String>,
+    /// Set the Git config value NAME to VALUE for every Git command run.  May be repeated.
+    #[arg(short = 'c', global = true, value_name = "NAME>=<VALUE", value_parser = parse_git_config, help_heading = "Git Options")]
+    git_config: Vec<(String, String)>,
+    /// Use the repository at PATH.
+    #[arg(long, global = true, value_name = "PATH", help_heading = "Git Options")]
+    git_dir: Option<String>,
+}
+
+/// The options which affect which forks are listed.
+#[derive(Args, Debug)]
+struct ListArgs {
+    /// Only list forks whose owner's name contains PATTERN.  If PATTERN is a glob pattern or a
+    /// regular expression wrapped in forward slashes, the owner's name must match it instead.
+    /// Matching is case-insensitive.
+    #[arg(long, value_name = "PATTERN", value_parser = Pattern::new_filter)]
+    filter: Option<Pattern>,
+    /// Only list the forks from which pull requests (GitHub) or merge requests (GitLab) are open
+    /// against the main fork/source.
+    #[arg(long)]
+    from_prs: bool,
+    /// Show each fork owner's avatar in terminals supporting the kitty or iTerm2 graphics
+    /// protocols.  Avatars are downloaded when first shown, then cached.
+    #[arg(long)]
+    avatars: bool,
+    /// Only list forks which have been pushed to (GitHub) or active (GitLab, Gitea) within
+    /// DURATION, e.g. '90d'.  Forks whose activity isn't known are still listed.  Defaults to the
+    /// Git config value of 'add-remote.minActivity' if set.
+    #[arg(long, value_name = "DURATION", value_parser = parse_interval)]
+    since: Option<Duration>,
+    /// List the forks in ORDER: 'name' (the default) alphabetically by owner, with members of the
+    /// main fork/source's organisation/group first, 'activity' for the most recently active first,
+    /// or 'stars' for the most starred first.  Defaults to the Git config value of
+    /// 'add-remote.sort' if set.
+    #[arg(long, value_name = "ORDER", value_parser = ForkOrder::parse)]
+    sort: Option<ForkOrder>,
+    /// List the forks as cached by a previous run rather than querying the forge, e.g. when
+    /// there's no network connection.  The list may be out of date.  The cache is used anyway if
+    /// the forge can't be reached.
+    #[arg(long)]
+    offline: bool,
+    /// List the forks via API: 'rest' (the default) or 'graphql', which needs far fewer requests
+    /// for repositories with many forks.  GraphQL is only used for GitHub (where it needs a token)
+    /// and GitLab.  Defaults to the Git config value of 'add-remote.api' if set.
+    #[arg(long, value_name = "API", value_parser = ForkApi::parse)]
+    api: Option<ForkApi>,
+    /// Also list the forks of forks, and their forks in turn, down to DEPTH levels below the main
+    /// fork/source, where its direct forks are level 1.  DEPTH defaults to 3.  Each is listed
+    /// along with the fork it was forked from.
+    #[arg(
+        long,
+        value_name = "DEPTH",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "3",
+        value_parser = parse_recursion_depth,
+        conflicts_with = "from_prs"
+    )]
+    recursive: Option<usize>,
+}
+
+/// The options of the `add` command, which may also be passed without naming a command.
+#[derive(Args, Debug)]
+#[allow(clippy::struct_excessive_bools)]
+struct AddArgs {
+    #[command(flatten)]
+    list: ListArgs,
+    /// Keep running, querying for forks every INTERVAL (e.g. '90s', '10m' or '1h') and listing
+    /// any which have appeared since the previous query, with the option of adding one.
+    #[arg(long, value_name = "INTERVAL", value_parser = parse_interval, conflicts_with = "offline")]
+    watch: Option<Duration>,
+    /// Add the fork owned by OWNER without listing the forks or asking anything, as if
+    /// '--no-input' were passed.  Exits with an error if OWNER doesn't own any of the available
+    /// forks.
+    #[arg(long, value_name = "OWNER")]
+    fork: Option<String>,
+    /// Name the new remote NAME rather than asking for a name.
+    #[arg(long, value_name = "NAME", value_parser = parse_alias, conflicts_with = "remote_name")]
+    alias: Option<String>,
+    /// Name the new remote by expanding TEMPLATE rather than asking for a name.  '{owner}' and
+    /// '{repo}' are replaced by the fork's owner and repository names, e.g. '{owner}-{repo}'.
+    #[arg(long, value_name = "TEMPLATE", value_parser = RemoteNameTemplate::new)]
+    remote_name: Option<RemoteNameTemplate>,
+    /// Where to store a custom alias for the chosen fork: 'global' (the default) to use it in all
+    /// repositories, or 'local' to only use it in this one.  Defaults to the Git config value of
+    /// 'add-remote.aliasScope' if set.
+    #[arg(long, value_name = "SCOPE", value_parser = AliasScope::parse)]
+    alias_scope: Option<AliasScope>,
+    /// Which URL to give the new remote: 'ssh', 'https', or 'auto' (the default) to use SSH
+    /// unless all of the existing remotes on supported forges use HTTPS.  Defaults to the Git
+    /// config value of 'add-remote.protocol' if set.
+    #[arg(long, value_name = "PROTOCOL", value_parser = RemoteProtocol::parse)]
+    protocol: Option<RemoteProtocol>,
+    /// Leave pushing to the new remote enabled rather than disabling its push URL, e.g. to push
+    /// to a co-maintainer's fork.  Pushing is also left enabled if the Git config value of
+    /// 'add-remote.disablePush' is false.
+    #[arg(long)]
+    allow_push: bool,
+    /// Only fetch the branch NAME of the new remote, e.g. that of a pull request, by limiting its
+    /// fetch refspec to '+refs/heads/NAME:refs/remotes/<alias>/NAME'.
+    #[arg(long, value_name = "NAME")]
+    branch: Option<String>,
+    /// Add the new remote without fetching from it, e.g. for very large repositories or on metered
+    /// connections.  The command to fetch from it later is printed instead.
+    #[arg(long)]
+    no_fetch: bool,
+    /// Fetch only the last DEPTH commits of each of the new remote's branches, as per 'git fetch
+    /// --depth', which makes the repository shallow.  Defaults to the Git config value of
+    /// 'add-remote.fetchDepth' if set.
+    #[arg(long, value_name = "DEPTH", value_parser = parse_fetch_depth, conflicts_with = "no_fetch")]
+    depth: Option<usize>,
+    /// Fetch from the new remote as a partial clone, omitting the objects excluded by FILTER (as
+    /// per 'git fetch --filter'), e.g. 'blob:none' to fetch file contents only when needed.  The
+    /// remote is configured as a promisor remote with 'remote.<alias>.partialCloneFilter' set to
+    /// FILTER, as per 'git clone --filter'.
+    #[arg(long = "fetch-filter", value_name = "FILTER", value_parser = parse_filter_spec)]
+    filter_spec: Option<String>,
+    /// Never fetch the new remote's tags, by setting 'remote.<alias>.tagOpt' to '--no-tags'.  Tags
+    /// are also left unfetched if the Git config value of 'add-remote.noTags' is true.
+    #[arg(long)]
+    no_tags: bool,
+    /// Once the remote is added, open the fork's web page in the browser named by the environment
+    /// variable 'BROWSER', or else in the default browser.
+    #[arg(long)]
+    open: bool,
+    /// Choose a fork and a name as usual, but print the Git commands which would add, configure
+    /// and fetch the new remote rather than running them.
+    #[arg(long)]
+    dry_run: bool,
+    /// Choose the fork in a full-screen interface listing the forks' details, with a search box
+    /// and a preview of the highlighted fork's branches.  Ignored if the terminal can't display
+    /// it, or with '--plain' or '--stable'.
+    #[arg(long)]
+    tui: bool,
+}
+
+/// The commands.
+#[derive(clap::Subcommand, Debug)]
+enum Subcommand {
+    /// Choose a fork and add it as a new remote.  This is the default.
+    Add(AddArgs),
+    /// List the forks which could be added as remotes, then exit.
+    List(ListArgs),
+    /// Normalise the configuration of the remotes added by 'add-remote': canonicalise their URLs,
+    /// remove duplicate fetch refspecs, fix the case of their names to match the configured
+    /// aliases, and group their sections in alphabetical order at the end of the repository's Git
+    /// config.
+    Tidy,
+    /// List the branches of REMOTE which have no counterpart in the upstream remote, along with
+    /// how many commits each has which aren't in upstream.  The upstream remote is the one named as
+    /// per 'add-remote.mainForkOwnerAlias' (or 'upstream'), or else 'origin'.  Only the local
+    /// remote-tracking branches are compared, so fetch both remotes first.
+    Compare {
+        /// The name of the remote to compare against upstream.
+        remote: String,
+    },
+    /// Search the branches of all the remotes added by 'add-remote' for those matching PATTERN,
+    /// then check out a local branch tracking the chosen one.  The characters of PATTERN must
+    /// appear in '<remote>/<branch>' in order, but not necessarily consecutively, e.g. 'hfp'
+    /// matches 'Herb/fix-parser'.
+    Switch {
+        /// The pattern to search for.
+        pattern: String,
+    },
+    /// Rename the remote OLD to NEW via 'git remote rename', asking for whichever isn't given.  If
+    /// the remote's owner has an alias configured under 'add-remote.forkAlias', it's updated to
+    /// NEW.
+    Rename {
+        /// The current name of the remote.
+        old: Option<String>,
+        /// The new name of the remote.
+        new: Option<String>,
+    },
+    /// Offer to rename each remote whose owner has an alias configured under
+    /// 'add-remote.forkAlias' which differs from the remote's name.
+    RenameAll,
+    /// Remove REMOTE via 'git remote remove', or if it's not given, list the remotes and ask which
+    /// to remove.  If the removed remote's owner has an alias configured under
+    /// 'add-remote.forkAlias', you're then asked whether to delete that too.
+    Remove {
+        /// The name of the remote to remove.
+        remote: Option<String>,
+        /// Delete the removed remote's owner's alias under 'add-remote.forkAlias' without asking.
+        #[arg(long)]
+        forget_alias: bool,
+        /// Print the Git commands which would remove the remote and any alias rather than running
+        /// them.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Query the forges for the repository of each remote, and offer to remove each remote whose
+    /// repository has been deleted or archived, e.g. since its owner deleted their fork once their
+    /// pull request was merged.  Remotes which local branches track are kept.
+    Prune {
+        /// Ask about each dead remote as usual, but print the Git commands which would remove
+        /// them rather than running them.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Change the URLs of REMOTEs to FORM: 'ssh' or 'https', e.g. when moving to a machine without
+    /// SSH keys.  If no REMOTE is given, either pass '--all' to change every remote, or choose one
+    /// from a list.  Only the URLs fetched from are changed; any separate push URL is left alone.
+    Convert {
+        /// The form to change the URLs to.
+        #[arg(value_name = "FORM", value_parser = UrlForm::parse)]
+        to: UrlForm,
+        /// The names of the remotes to change.
+        #[arg(value_name = "REMOTE")]
+        remotes: Vec<String>,
+        /// Change every remote hosted on a supported forge.
+        #[arg(long, conflicts_with = "remotes")]
+        all: bool,
+    },
+    /// Report, for each remote, whether its repository still exists or has been archived, renamed
+    /// or transferred, whether pushing to it is disabled if it was added by 'add-remote', and
+    /// whether its name matches its owner's alias under 'add-remote.forkAlias'.  Exits with an
+    /// error if any repository no longer exists.
+    Audit {
+        /// Print the report as 'human' (the default) readable lines or as 'json', i.e. an object
+        /// holding the schema version and a 'remotes' array.
+        #[arg(long, value_name = "FORMAT", value_parser = OutputFormat::parse)]
+        format: Option<OutputFormat>,
+    },
+    /// Print the JSON Schema which the report printed by 'add-remote audit --format json'
+    /// conforms to, then exit.
+    Schema,
+    /// Fetch every remote, running up to N fetches at once as set by '--jobs', printing a status
+    /// line as each completes and then listing the branches which are new to each remote.  Exits
+    /// with an error if any fetch failed.
+    Sync,
+    /// Manage the tokens stored in the OS keyring for accessing forges' APIs.
+    #[command(subcommand)]
+    Auth(AuthSubcommand),
+    /// Log in to FORGE ('github' or 'gitlab') via OAuth's device flow rather than creating a
+    /// Personal Access Token: a code is shown to be entered in the browser, and once that's done,
+    /// the resulting token is stored in the OS keyring.
+    Login {
+        /// The forge to log in to.
+        #[arg(value_parser = LoginForge::parse)]
+        forge: LoginForge,
+        /// The client ID of the OAuth application to log in with.  Defaults to the value of
+        /// 'add-remote.gitHubClientId' or 'add-remote.gitLabClientId', or failing that, the
+        /// application registered for 'add-remote'.
+        #[arg(long, value_name = "ID")]
+        client_id: Option<String>,
+    },
+    /// Check that 'add-remote' is set up correctly, printing a pass/fail report: Git is found,
+    /// this is a Git repository, its remotes are hosted on supported forges whose APIs are
+    /// reachable, and any tokens configured for those forges authenticate with the scopes needed.
+    Doctor,
+}
+
+/// The commands for managing tokens stored in the OS keyring.
+#[derive(clap::Subcommand, Debug)]
+enum AuthSubcommand {
+    /// Read a Personal Access Token for HOST (e.g. 'github.com') without echoing it, and store it
+    /// in the OS keyring, replacing any already stored.  The token is used when none is set in Git
+    /// config.  If stdin isn't a terminal, the token is read from its first line.
+    Set {
+        /// The host of the forge, e.g. 'github.com', 'gitlab.com' or 'codeberg.org'.
+        host: String,
+    },
+    /// Remove the token stored for HOST from the OS keyring.
+    Delete {
+        /// The host of the forge.
+        host: String,
+    },
+}
+
+/// Returns the help for the options and commands, as shown before the configuration details by
+/// `--help`.
+pub fn usage_help() -> String {
+    command().render_long_help().to_string()
+}
+
+/// Returns the clap command.  The top-level help flag is handled by `main` so that it can include
+/// the configuration help, but the subcommands use clap's own.
+fn command() -> clap::Command {
+    Cli::command().mut_subcommands(|subcommand| {
+        subcommand.arg(
+            Arg::new("help")
+                .short('h')
+                .long("help")
+                .action(ArgAction::Help)
+                .help("Print help"),
+        )
+    })
+}
+
+/// Returns an error if any of the options of the default `add` command were passed before a
+/// different command, e.g. `add-remote --fork bob tidy`.
+fn check_add_args_unused(command: &mut clap::Command, matches: &ArgMatches) -> Result<(), Error> {
+    let Some(subcommand) = matches.subcommand_name().filter(|name| *name != "add") else {
+        return Ok(());
+    };
+    let add_args = AddArgs::augment_args(clap::Command::new("add"));
+    let used = add_args
+        .get_arguments()
+        .find(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine));
+    match used {
+        Some(arg) => Err(command.error(
+            ErrorKind::ArgumentConflict,
+            format!(
+                "the subcommand '{subcommand}' cannot be used with '--{}'",
+                arg.get_long().unwrap_or_default()
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Checks that `value` can be used as the name of a remote.
+fn parse_alias(value: &str) -> Result<String, String> {
+    remote_name::validate(value).map(|()| value.to_string())
+}
+
+/// Parses `value` as the number of concurrent jobs, which must be at least 1.
+pub fn parse_jobs(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(jobs) if jobs > 0 => Ok(jobs),
+        _ => Err(format!(
+            "Invalid number of jobs '{value}': expected a positive integer."
+        )),
+    }
+}
+
+/// Parses `value` as the number of levels of forks to list, which must be at least 1.
+fn parse_recursion_depth(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(depth) if depth > 0 => Ok(depth),
+        _ => Err(format!(
+            "Invalid depth '{value}': expected a positive integer."
+        )),
+    }
+}
+
+/// Parses `value` as the number of commits to fetch from the tip of each branch, which must be at
+/// least 1.
+pub fn parse_fetch_depth(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(depth) if depth > 0 => Ok(depth),
+        _ => Err(format!(
+            "Invalid fetch depth '{value}': expected a positive integer."
+        )),
+    }
+}
+
+/// Checks that `value` has the form of one of the object filters understood by `git fetch
+/// --filter`, e.g. `blob:none`, `blob:limit=1m` or `tree:0`.
+fn parse_filter_spec(value: &str) -> Result<String, String> {
+    const PREFIXES: [&str; 6] = [
+        "blob:none",
+        "blob:limit=",
+        "tree:",
+        "sparse:oid=",
+        "object:type=",
+        "combine:",
+    ];
+    let is_valid = PREFIXES.iter().any(|prefix| value.starts_with(prefix))
+        && !value.chars().any(char::is_whitespace);
+    if is_valid {
+        Ok(value.to_string())
+    } else {
+        Err(format!(
+            "Invalid filter '{value}': expected e.g. 'blob:none', 'blob:limit=<size>' or \
+             'tree:<depth>'."
+        ))
+    }
+}
+
+/// Parses `value` as the number of times to retry a failed API request, which may be zero.
+pub fn parse_retries(value: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| {
+        format!("Invalid number of retries '{value}': expected a non-negative integer.")
+    })
+}
+
+/// Parses `value` as a Git config override in the form `<key>=<value>`, as passed to `git -c`.  As
+/// with Git, a key without `=` is given the value `true`.
+pub fn parse_git_config(value: &str) -> Result<(String, String), String> {
+    let (key, config_value) = value.split_once('=').unwrap_or((value, "true"));
+    let is_valid = key
+        .split_once('.')
+        .is_some_and(|(section, name)| !section.is_empty() && !name.is_empty());
+    if !is_valid {
+        return Err(format!(
+            "Invalid Git config '{value}': expected '<section>.<name>=<value>'."
+        ));
+    }
+    Ok((key.to_string(), config_value.to_string()))
+}
+
+/// Parses `value` as a positive interval: a whole number followed by a unit of `s`, `m`, `h` or `d`
+/// (for seconds, minutes, hours or days), or by nothing for seconds.
+pub fn parse_interval(value: &str) -> Result<Duration, String> {
+    let error = || {
+        format!("Invalid interval '{value}': expected a positive number of seconds, or e.g. '10m'.")
+    };
+    let (number, unit_seconds) = match value.char_indices().last() {
+        Some((index, 's')) => (&value[..index], 1),
+        Some((index, 'm')) => (&value[..index], 60),
+        Some((index, 'h')) => (&value[..index], 60 * 60),
+        Some((index, 'd')) => (&value[..index], 24 * 60 * 60),
+        _ => (value, 1),
+    };
+    match number.parse::<u64>() {
+        Ok(number) if number > 0 => number
+            .checked_mul(unit_seconds)
+            .map(Duration::from_secs)
+            .ok_or_else(error),
+        _ => Err(error()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AliasScope, Command, ForkApi, ForkOrder, LoginForge, OnEof, Options, OutputFormat,
+        RemoteProtocol, UrlForm,
+    };
+    use std::time::Duration;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(ToString::to_string))
+    }
+
+    #[test]
+    fn parse_flags() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.command, Command::Add);
+        assert!(!options.help);
+        assert!(!options.version);
+        assert!(options.filter.is_none());
+        assert!(!options.plain);
+        assert!(!options.stable);
+        assert!(!options.no_input);
+        assert_eq!(options.on_eof, OnEof::Fail);
+        assert!(options.remote_name.is_none());
+        assert!(options.jobs.is_none());
+        assert!(options.alias_scope.is_none());
+        assert!(!options.dry_run);
+        assert!(!options.tui);
+        assert!(!options.offline);
+        assert!(!options.verbose);
+        assert!(!options.quiet);
+
+        assert!(parse(&["/?"]).unwrap().help);
+        assert!(parse(&["-V"]).unwrap().version);
+        assert!(parse(&["--plain"]).unwrap().plain);
+        assert!(parse(&["-v"]).unwrap().verbose);
+        assert!(parse(&["list", "--verbose"]).unwrap().verbose);
+        assert!(parse(&["-q"]).unwrap().quiet);
+        assert!(parse(&["sync", "--quiet"]).unwrap().quiet);
+        assert!(parse(&["--quiet", "--verbose"]).is_err());
+        assert!(parse(&["--stable"]).unwrap().stable);
+        assert!(parse(&["--no-input"]).unwrap().no_input);
+        assert!(parse(&["--avatars"]).unwrap().avatars);
+        assert!(parse(&["--from-prs"]).unwrap().from_prs);
+        assert!(parse(&["--offline"]).unwrap().offline);
+        assert!(parse(&["list", "--offline"]).unwrap().offline);
+        assert!(parse(&["--dry-run"]).unwrap().dry_run);
+        assert!(parse(&["add", "--dry-run"]).unwrap().dry_run);
+        assert!(parse(&["list", "--dry-run"]).is_err());
+        assert!(parse(&["--tui"]).unwrap().tui);
+        assert!(parse(&["list", "--tui"]).is_err());
+        assert!(parse(&["--bogus"]).is_err());
+    }
+
+    #[test]
+    fn parse_command() {
+        assert_eq!(parse(&["add"]).unwrap().command, Command::Add);
+        let options = parse(&["--plain", "tidy"]).unwrap();
+        assert_eq!(options.command, Command::Tidy);
+        assert!(options.plain);
+        assert!(parse(&["tidy", "tidy"]).is_err());
+        assert!(parse(&["bogus"]).is_err());
+
+        assert_eq!(
+            parse(&["compare", "Herb"]).unwrap().command,
+            Command::Compare("Herb".to_string())
+        );
+        assert!(parse(&["compare"]).is_err());
+        assert!(parse(&["compare", "Herb", "Anthony"]).is_err());
+        assert_eq!(
+            parse(&["switch", "fix"]).unwrap().command,
+            Command::Switch("fix".to_string())
+        );
+        assert!(parse(&["switch"]).is_err());
+        assert_eq!(
+            parse(&["rename"]).unwrap().command,
+            Command::Rename(None, None)
+        );
+        assert_eq!(
+            parse(&["rename", "Herb", "Herbert"]).unwrap().command,
+            Command::Rename(Some("Herb".to_string()), Some("Herbert".to_string()))
+        );
+        assert!(parse(&["rename", "Herb", "Herbert", "Bert"]).is_err());
+        assert_eq!(parse(&["rename-all"]).unwrap().command, Command::RenameAll);
+        assert!(parse(&["rename-all", "Herb"]).is_err());
+        assert_eq!(parse(&["remove"]).unwrap().command, Command::Remove(None));
+        let options = parse(&["remove", "Herb", "--forget-alias"]).unwrap();
+        assert_eq!(options.command, Command::Remove(Some("Herb".to_string())));
+        assert!(options.forget_alias);
+        assert!(!parse(&["remove", "Herb"]).unwrap().forget_alias);
+        assert!(parse(&["remove", "Herb", "--dry-run"]).unwrap().dry_run);
+        assert!(parse(&["remove", "Herb", "Anthony"]).is_err());
+        assert_eq!(parse(&["prune"]).unwrap().command, Command::Prune);
+        assert!(parse(&["prune", "Herb"]).is_err());
+        assert!(parse(&["prune", "--dry-run"]).unwrap().dry_run);
+        let options = parse(&["convert", "ssh", "Herb", "Anthony"]).unwrap();
+        assert_eq!(
+            options.command,
+            Command::Convert(
+                UrlForm::Ssh,
+                vec!["Herb".to_string(), "Anthony".to_string()]
+            )
+        );
+        assert!(!options.all_remotes);
+        let options = parse(&["convert", "HTTPS", "--all"]).unwrap();
+        assert_eq!(
+            options.command,
+            Command::Convert(UrlForm::Https, Vec::new())
+        );
+        assert!(options.all_remotes);
+        assert!(parse(&["convert"]).is_err());
+        assert!(parse(&["convert", "git"]).is_err());
+        assert!(parse(&["convert", "ssh", "Herb", "--all"]).is_err());
+
+        let options = parse(&["sync", "-j", "8"]).unwrap();
+        assert_eq!(options.command, Command::Sync);
+        assert_eq!(options.jobs, Some(8));
+        assert!(parse(&["sync", "origin"]).is_err());
+
+        let options = parse(&["audit"]).unwrap();
+        assert_eq!(options.command, Command::Audit);
+        assert_eq!(options.format, OutputFormat::Human);
+        let options = parse(&["audit", "--format", "JSON"]).unwrap();
+        assert_eq!(options.format, OutputFormat::Json);
+        assert!(parse(&["audit", "--format", "yaml"]).is_err());
+        assert!(parse(&["--format", "json"]).is_err());
+        assert_eq!(
+            parse(&["auth", "set", "github.com"]).unwrap().command,
+            Command::AuthSet("github.com".to_string())
+        );
+        assert_eq!(
+            parse(&["auth", "delete", "gitlab.com"]).unwrap().command,
+            Command::AuthDelete("gitlab.com".to_string())
+        );
+        assert!(parse(&["auth"]).is_err());
+        assert!(parse(&["auth", "set"]).is_err());
+        let options = parse(&["login", "GitHub"]).unwrap();
+        assert_eq!(options.command, Command::Login(LoginForge::GitHub));
+        assert!(options.client_id.is_none());
+        let options = parse(&["login", "gitlab", "--client-id", "abc"]).unwrap();
+        assert_eq!(options.command, Command::Login(LoginForge::GitLab));
+        assert_eq!(options.client_id.as_deref(), Some("abc"));
+        assert!(parse(&["login", "gitea"]).is_err());
+        assert!(parse(&["login"]).is_err());
+        assert_eq!(parse(&["doctor"]).unwrap().command, Command::Doctor);
+        assert_eq!(parse(&["schema"]).unwrap().command, Command::Schema);
+        assert!(parse(&["--client-id", "abc"]).is_err());
+
+        let options = parse(&["list", "--from-prs", "--filter", "ser9"]).unwrap();
+        assert_eq!(options.command, Command::List);
+        assert!(options.from_prs);
+        assert!(options.filter.is_some());
+        assert!(parse(&["list", "--fork", "Herb"]).is_err());
+        assert!(parse(&["--from-prs", "tidy"]).is_err());
+    }
+
+    #[test]
+    fn parse_filter() {
+        let filter = parse(&["--filter", "ser9"]).unwrap().filter.unwrap();
+        assert!(filter.matches("Fraser999"));
+        assert!(!filter.matches("casperlabs"));
+
+        let filter = parse(&["--filter=f*9"]).unwrap().filter.unwrap();
+        assert!(filter.matches("Fraser999"));
+        assert!(!filter.matches("aFraser999"));
+
+        assert!(parse(&["--filter"]).is_err());
+        assert!(parse(&["--filter", "/(/"]).is_err());
+    }
+
+    #[test]
+    fn parse_jobs() {
+        assert_eq!(parse(&["--jobs", "8"]).unwrap().jobs, Some(8));
+        assert_eq!(parse(&["-j", "1"]).unwrap().jobs, Some(1));
+        assert_eq!(parse(&["--jobs=2"]).unwrap().jobs, Some(2));
+        assert!(parse(&["--jobs", "0"]).is_err());
+        assert!(parse(&["--jobs", "-1"]).is_err());
+        assert!(parse(&["--jobs", "many"]).is_err());
+        assert!(parse(&["--jobs"]).is_err());
+    }
+
+    #[test]
+    fn parse_timeout_and_retries() {
+        let options = parse(&[]).unwrap();
+        assert!(options.timeout.is_none());
+        assert!(options.retries.is_none());
+        let options = parse(&["list", "--timeout", "2m", "--retries", "0"]).unwrap();
+        assert_eq!(options.timeout, Some(Duration::from_mins(2)));
+        assert_eq!(options.retries, Some(0));
+        assert!(!options.insecure);
+        assert!(parse(&["doctor", "--insecure"]).unwrap().insecure);
+        assert_eq!(
+            parse(&["--timeout", "45"]).unwrap().timeout,
+            Some(Duration::from_secs(45))
+        );
+        assert!(parse(&["--timeout", "0s"]).is_err());
+        assert!(parse(&["--retries", "-1"]).is_err());
+        assert!(parse(&["--retries", "some"]).is_err());
+    }
+
+    #[test]
+    fn parse_git_options() {
+        let options = parse(&[
+            "-C",
+            "/repos",
+            "-c",
+            "core.sshCommand=ssh -i key",
+            "-C",
+            "cargo",
+            "-c",
+            "http.sslVerify",
+            "--git-dir=.git",
+        ])
+        .unwrap();
+        assert_eq!(options.directories, ["/repos", "cargo"]);
+        assert_eq!(
+            options.git_config,
+            [
+                ("core.sshCommand".to_string(), "ssh -i key".to_string()),
+                ("http.sslVerify".to_string(), "true".to_string())
+            ]
+        );
+        assert_eq!(options.git_dir.as_deref(), Some(".git"));
+
+        assert!(parse(&["-c", "nodot=1"]).is_err());
+        assert!(parse(&["-c", ".name=1"]).is_err());
+        assert!(parse(&["-C"]).is_err());
+    }
+
+    #[test]
+    fn parse_fork_and_alias() {
+        let options = parse(&["--fork", "bob", "--alias", "Bob"]).unwrap();
+        assert_eq!(options.fork.as_deref(), Some("bob"));
+        assert_eq!(options.alias.as_deref(), Some("Bob"));
+        assert!(options.no_input);
+
+        let options = parse(&["--alias", "Bob"]).unwrap();
+        assert!(options.fork.is_none());
+        assert!(!options.no_input);
+
+        assert!(parse(&["--alias", "bad..name"]).is_err());
+        assert!(parse(&["--alias", "Bob", "--remote-name", "{owner}"]).is_err());
+        assert!(parse(&["--fork"]).is_err());
+    }
+
+    #[test]
+    fn parse_alias_scope() {
+        let options = parse(&["--alias-scope", "local"]).unwrap();
+        assert_eq!(options.alias_scope, Some(AliasScope::Local));
+        let options = parse(&["--alias-scope=Global"]).unwrap();
+        assert_eq!(options.alias_scope, Some(AliasScope::Global));
+        assert!(parse(&["--alias-scope", "system"]).is_err());
+    }
+
+    #[test]
+    fn parse_protocol() {
+        assert!(parse(&[]).unwrap().protocol.is_none());
+        let protocol = |value: &str| parse(&["--protocol", value]).map(|options| options.protocol);
+        assert_eq!(protocol("auto"), Ok(Some(RemoteProtocol::Auto)));
+        assert_eq!(protocol("SSH"), Ok(Some(RemoteProtocol::Ssh)));
+        assert_eq!(protocol("https"), Ok(Some(RemoteProtocol::Https)));
+        assert!(protocol("git").is_err());
+        assert!(parse(&["list", "--protocol", "ssh"]).is_err());
+    }
+
+    #[test]
+    fn parse_allow_push() {
+        assert!(!parse(&[]).unwrap().allow_push);
+        assert!(parse(&["--allow-push"]).unwrap().allow_push);
+        assert!(parse(&["add", "--allow-push"]).unwrap().allow_push);
+        assert!(parse(&["list", "--allow-push"]).is_err());
+    }
+
+    #[test]
+    fn parse_branch() {
+        assert!(parse(&[]).unwrap().branch.is_none());
+        let options = parse(&["--branch", "fix-parser"]).unwrap();
+        assert_eq!(options.branch.as_deref(), Some("fix-parser"));
+        assert!(parse(&["list", "--branch", "fix-parser"]).is_err());
+        assert!(parse(&["--branch"]).is_err());
+    }
+
+    #[test]
+    fn parse_no_fetch() {
+        assert!(!parse(&[]).unwrap().no_fetch);
+        assert!(parse(&["--no-fetch"]).unwrap().no_fetch);
+        assert!(parse(&["list", "--no-fetch"]).is_err());
+    }
+
+    #[test]
+    fn parse_depth() {
+        assert!(parse(&[]).unwrap().depth.is_none());
+        assert_eq!(parse(&["--depth", "50"]).unwrap().depth, Some(50));
+        assert!(parse(&["--depth", "0"]).is_err());
+        assert!(parse(&["--depth", "-1"]).is_err());
+        assert!(parse(&["--depth", "1", "--no-fetch"]).is_err());
+        assert!(parse(&["list", "--depth", "1"]).is_err());
+    }
+
+    #[test]
+    fn parse_partial_clone_filter() {
+        assert!(parse(&[]).unwrap().filter_spec.is_none());
+        let filter =
+            |value: &str| parse(&["--fetch-filter", value]).map(|options| options.filter_spec);
+        assert_eq!(filter("blob:none"), Ok(Some("blob:none".to_string())));
+        assert_eq!(
+            filter("blob:limit=1m"),
+            Ok(Some("blob:limit=1m".to_string()))
+        );
+        assert_eq!(filter("tree:0"), Ok(Some("tree:0".to_string())));
+        assert!(filter("none").is_err());
+        assert!(filter("blob:none tree:0").is_err());
+        assert!(parse(&["list", "--fetch-filter", "blob:none"]).is_err());
+    }
+
+    #[test]
+    fn parse_open() {
+        assert!(!parse(&[]).unwrap().open);
+        assert!(parse(&["--open"]).unwrap().open);
+        assert!(parse(&["add", "--fork", "bob", "--open"]).unwrap().open);
+        assert!(parse(&["sync", "--open"]).is_err());
+    }
+
+    #[test]
+    fn parse_no_tags() {
+        assert!(!parse(&[]).unwrap().no_tags);
+        assert!(parse(&["--no-tags"]).unwrap().no_tags);
+        assert!(parse(&["list", "--no-tags"]).is_err());
+    }
+
+    #[test]
+    fn parse_watch() {
+        assert!(parse(&[]).unwrap().watch.is_none());
+        let watch = |value: &str| parse(&["--watch", value]).map(|options| options.watch);
+        assert_eq!(watch("90"), Ok(Some(Duration::from_secs(90))));
+        assert_eq!(watch("30s"), Ok(Some(Duration::from_secs(30))));
+        assert_eq!(watch("10m"), Ok(Some(Duration::from_mins(10))));
+        assert_eq!(watch("1h"), Ok(Some(Duration::from_hours(1))));
+        assert_eq!(watch("2d"), Ok(Some(Duration::from_hours(48))));
+        assert!(watch("0h").is_err());
+        assert!(watch("h").is_err());
+        assert!(watch("1w").is_err());
+        assert!(watch("-1m").is_err());
+        assert!(parse(&["--watch"]).is_err());
+        assert!(parse(&["--watch", "1h", "--offline"]).is_err());
+    }
+
+    #[test]
+    fn parse_sort() {
+        assert!(parse(&[]).unwrap().sort.is_none());
+        let sort = |value: &str| parse(&["list", "--sort", value]).map(|options| options.sort);
+        assert_eq!(sort("name"), Ok(Some(ForkOrder::Name)));
+        assert_eq!(sort("Activity"), Ok(Some(ForkOrder::Activity)));
+        assert_eq!(sort("stars"), Ok(Some(ForkOrder::Stars)));
+        assert!(sort("age").is_err());
+        assert!(parse(&["--sort"]).is_err());
+    }
+
+    #[test]
+    fn parse_api() {
+        assert!(parse(&[]).unwrap().api.is_none());
+        let api = |value: &str| parse(&["list", "--api", value]).map(|options| options.api);
+        assert_eq!(api("rest"), Ok(Some(ForkApi::Rest)));
+        assert_eq!(api("GraphQL"), Ok(Some(ForkApi::GraphQl)));
+        assert!(api("soap").is_err());
+        assert!(parse(&["--api"]).is_err());
+    }
+
+    #[test]
+    fn parse_recursive() {
+        assert!(parse(&[]).unwrap().recursive.is_none());
+        let recursive = |args: &[&str]| parse(args).map(|options| options.recursive);
+        assert_eq!(recursive(&["list", "--recursive"]), Ok(Some(3)));
+        assert_eq!(recursive(&["--recursive=5"]), Ok(Some(5)));
+        assert!(recursive(&["--recursive=0"]).is_err());
+        assert!(recursive(&["list", "--recursive", "2"]).is_err());
+        assert!(recursive(&["--recursive", "--from-prs"]).is_err());
+    }
+
+    #[test]
+    fn parse_since() {
+        assert!(parse(&[]).unwrap().since.is_none());
+        let options = parse(&["list", "--since", "30d"]).unwrap();
+        assert_eq!(options.since, Some(Duration::from_hours(720)));
+        assert!(parse(&["--since", "0d"]).is_err());
+        assert!(parse(&["tidy", "--since", "30d"]).is_err());
+    }
+
+    #[test]
+    fn parse_on_eof() {
+        let options = parse(&["--on-eof", "default"]).unwrap();
+        assert_eq!(options.on_eof, OnEof::UseDefault);
+        let options = parse(&["--on-eof=FAIL"]).unwrap();
+        assert_eq!(options.on_eof, OnEof::Fail);
+        assert!(parse(&["--on-eof", "ignore"]).is_err());
+    }
+
+    #[test]
+    fn parse_remote_name() {
+        let template = parse(&["--remote-name", "{owner}-{repo}"])
+            .unwrap()
+            .remote_name
+            .unwrap();
+        assert_eq!(template.expand("Fraser999", "cargo"), "Fraser999-cargo");
+        assert!(parse(&["--remote-name={user}"]).is_err());
+        assert!(parse(&["--remote-name"]).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use crate::git_url;
+use std::fmt::Display;
+use thiserror::Error;
+
+/// The errors which cause `add-remote` to exit, each with its own exit code.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// `add-remote` wasn't run from within a Git repository.
+    #[error(
+        "Failed to execute 'git remote show'.  Execute this program from inside a Git repository."
+    )]
+    NotARepository,
+    /// None of the repository's remotes are hosted on a known forge.
+    #[error(
+        "This repository doesn't appear to be hosted on GitLab, GitHub or a Gitea instance.  \
+         'add-remote' can only be used with GitLab, GitHub or Gitea projects.  Self-hosted Gitea \
+         and Forgejo instances must be listed under 'add-remote.giteaHost'."
+    )]
+    UnsupportedHost,
+    /// The repository is hosted on GitLab, but no GitLab token is configured.
+    #[error(
+        "This repository is hosted on GitLab.  To use 'add-remote' with a GitLab project, you must \
+         provide a GitLab Personal Access Token with \"read_api\" scope, either in your git config \
+         under the key 'add-remote.gitLabToken', in the OS keyring by running 'add-remote auth set \
+         gitlab.com', or in the environment variable 'ADD_REMOTE_GITLAB_TOKEN' or \
+         'GITLAB_TOKEN'.  For full details, see \
+         https://github.com/Fraser999/Add-Remote#personal-access-tokens."
+    )]
+    MissingGitLabToken,
+    /// A Git command failed.
+    #[error("{0}")]
+    Git(String),
+    /// An invalid argument was passed on the command line.
+    #[error("{0}")]
+    InvalidArgument(String),
+    /// The name for the new remote is invalid.
+    #[error("{0}")]
+    InvalidRemoteName(String),
+    /// The remote or branch to compare or switch to couldn't be found.
+    #[error("{0}")]
+    NotFound(String),
+    /// A choice was needed, but the input had ended or `--no-input` was passed.
+    #[error("{0}")]
+    InputNeeded(String),
+    /// The fork requested via `--fork` isn't available.
+    #[error("{0}")]
+    ForkUnavailable(String),
+    /// A request to the forge's API failed.
+    #[error("{0}")]
+    Request(String),
+    /// The forge's API responded with something other than what was expected.
+    #[error("Unexpected response from {request}: {reason}")]
+    UnexpectedResponse { request: String, reason: String },
+    /// A token couldn't be stored in or removed from the OS keyring.
+    #[error("The OS keyring couldn't be updated: {0}.")]
+    Keyring(String),
+    /// Some of the checks made by `add-remote doctor` failed.
+    #[error("{0} check(s) failed.")]
+    ChecksFailed(usize),
+    /// There are no forks which could be added as remotes.
+    #[error("{0}")]
+    NoForksAvailable(String),
+    /// The forge's API rejected a request as unauthorised, so a valid token is needed.
+    #[error("{0}")]
+    AuthRequired(String),
+    /// The forge couldn't be reached, and no list of forks was cached to fall back on.
+    #[error("{0}")]
+    Unreachable(String),
+}
+
+impl Error {
+    /// Returns the code with which the process exits due to this error.  These are documented in
+    /// the README and must not change.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::NotARepository => -1,
+            Error::UnsupportedHost => -2,
+            Error::MissingGitLabToken => -3,
+            Error::Git(_) => -4,
+            Error::InvalidArgument(_) => -5,
+            Error::InvalidRemoteName(_) => -6,
+            Error::NotFound(_) => -7,
+            Error::InputNeeded(_) => -8,
+            Error::ForkUnavailable(_) => -9,
+            Error::Request(_) => -10,
+            Error::UnexpectedResponse { .. } => -11,
+            Error::Keyring(_) => -12,
+            Error::ChecksFailed(_) => -13,
+            Error::NoForksAvailable(_) => -14,
+            Error::AuthRequired(_) => -15,
+            Error::Unreachable(_) => -16,
+        }
+    }
+
+    /// Returns an error describing why the response to `request` couldn't be used, with any token
+    /// in `request` redacted.
+    pub fn unexpected_response<T: Display>(request: &str, reason: T) -> Self {
+        Error::UnexpectedResponse {
+            request: git_url::redact(request),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use std::collections::HashSet;
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        let errors = [
+            Error::NotARepository,
+            Error::UnsupportedHost,
+            Error::MissingGitLabToken,
+            Error::Git(String::new()),
+            Error::InvalidArgument(String::new()),
+            Error::InvalidRemoteName(String::new()),
+            Error::NotFound(String::new()),
+            Error::InputNeeded(String::new()),
+            Error::ForkUnavailable(String::new()),
+            Error::Request(String::new()),
+            Error::UnexpectedResponse {
+                request: String::new(),
+                reason: String::new(),
+            },
+            Error::Keyring(String::new()),
+            Error::ChecksFailed(0),
+            Error::NoForksAvailable(String::new()),
+            Error::AuthRequired(String::new()),
+            Error::Unreachable(String::new()),
+        ];
+        let codes: HashSet<i32> = errors.iter().map(Error::exit_code).collect();
+        assert_eq!(codes.len(), errors.len());
+    }
+}
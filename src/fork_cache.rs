@@ -0,0 +1,227 @@
+use crate::{avatar, git_url};
+use regex::Regex;
+use serde_json::{json, Map, Value};
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+};
+
+/// A page of a fork list as cached, along with the `ETag` which the forge sent with it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachedPage {
+    pub etag: String,
+    pub content: String,
+    pub next_link: Option<String>,
+    pub last_link: Option<String>,
+}
+
+/// The cached pages of the fork list of one repository, keyed by their requests.  Any tokens in
+/// the requests or in the pages' links are redacted before being written to disk.
+#[derive(Debug, Default)]
+pub struct ForkCache {
+    path: Option<PathBuf>,
+    pages: Mutex<HashMap<String, CachedPage>>,
+    changed: Mutex<bool>,
+}
+
+impl ForkCache {
+    /// Opens the cache of the fork list of `owner/name` as served by the API at `host`.  A missing
+    /// or unreadable cache is treated as empty.
+    pub fn open(host: &str, owner: &str, name: &str) -> Self {
+        let path = cache_path_from(host, owner, name, |var| env::var_os(var).map(PathBuf::from));
+        let pages = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|contents| parse(&contents))
+            .unwrap_or_default();
+        ForkCache {
+            path,
+            pages: Mutex::new(pages),
+            changed: Mutex::new(false),
+        }
+    }
+
+    /// Returns the page cached as the response to `request`, with any tokens redacted from its
+    /// links restored from `request`.
+    pub fn get(&self, request: &str) -> Option<CachedPage> {
+        let page = self
+            .pages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&git_url::redact(request))
+            .cloned()?;
+        let restore = |link: Option<String>| link.map(|link| restore_tokens(&link, request));
+        Some(CachedPage {
+            next_link: restore(page.next_link),
+            last_link: restore(page.last_link),
+            ..page
+        })
+    }
+
+    /// Caches `page` as the response to `request`.
+    pub fn insert(&self, request: &str, page: CachedPage) {
+        let redact = |link: Option<String>| link.map(|link| git_url::redact(&link));
+        let page = CachedPage {
+            next_link: redact(page.next_link),
+            last_link: redact(page.last_link),
+            ..page
+        };
+        let previous = self
+            .pages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(git_url::redact(request), page.clone());
+        if previous.as_ref() != Some(&page) {
+            *self.changed.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        }
+    }
+
+    /// Writes the cache to disk if it has changed.  Failure isn't reported since the fork list can
+    /// be downloaded again.
+    pub fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if !*self.changed.lock().unwrap_or_else(PoisonError::into_inner) {
+            return;
+        }
+        let pages: Map<String, Value> = self
+            .pages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(request, page)| {
+                let value = json!({
+                    "etag": page.etag,
+                    "content": page.content,
+                    "next_link": page.next_link,
+                    "last_link": page.last_link,
+                });
+                (request.clone(), value)
+            })
+            .collect();
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir)
+                .and_then(|()| fs::write(path, Value::Object(pages).to_string()));
+        }
+    }
+}
+
+/// Returns the path at which the fork list of `owner/name` as served by the API at `host` is
+/// cached, reading the environment variables via `var`.
+fn cache_path_from<F: Fn(&str) -> Option<PathBuf>>(
+    host: &str,
+    owner: &str,
+    name: &str,
+    var: F,
+) -> Option<PathBuf> {
+    Some(
+        avatar::cache_dir_from(var)?
+            .join("forks")
+            .join(host)
+            .join(owner.to_lowercase())
+            .join(format!("{}.json", name.to_lowercase())),
+    )
+}
+
+/// Parses the cached pages from the `contents` of a cache file, skipping any which are malformed.
+fn parse(contents: &str) -> HashMap<String, CachedPage> {
+    let Ok(Value::Object(pages)) = serde_json::from_str(contents) else {
+        return HashMap::new();
+    };
+    pages
+        .into_iter()
+        .filter_map(|(request, value)| {
+            let field = |key: &str| value[key].as_str().map(ToString::to_string);
+            let page = CachedPage {
+                etag: field("etag")?,
+                content: field("content")?,
+                next_link: field("next_link"),
+                last_link: field("last_link"),
+            };
+            Some((request, page))
+        })
+        .collect()
+}
+
+/// Returns `link` with any token parameters which were redacted replaced by those in `request`.
+fn restore_tokens(link: &str, request: &str) -> String {
+    let token_parameter = Regex::new(r"[?&]((?:private|access)_token=)([^&\s]+)").unwrap();
+    token_parameter
+        .captures_iter(request)
+        .fold(link.to_string(), |link, captures| {
+            link.replace(
+                &format!("{}***", &captures[1]),
+                &format!("{}{}", &captures[1], &captures[2]),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, CachedPage, ForkCache};
+    use std::{collections::HashMap, path::PathBuf};
+
+    #[test]
+    fn cache_path() {
+        let cache_path = |vars: &[(&str, &str)]| {
+            let vars: HashMap<_, _> = vars.iter().copied().collect();
+            super::cache_path_from("api.github.com", "Alice", "Repo", |name| {
+                vars.get(name).map(PathBuf::from)
+            })
+        };
+        assert_eq!(
+            cache_path(&[("XDG_CACHE_HOME", "/cache")]),
+            Some(PathBuf::from(
+                "/cache/add-remote/forks/api.github.com/alice/repo.json"
+            ))
+        );
+        assert_eq!(cache_path(&[]), None);
+    }
+
+    #[test]
+    fn tokens_are_redacted_and_restored() {
+        let cache = ForkCache::default();
+        let request = "https://gitlab.com/api/v4/projects/a%2Fb/forks?private_token=secret";
+        let next = "https://gitlab.com/api/v4/projects/a%2Fb/forks?page=2&private_token=secret";
+        let page = CachedPage {
+            etag: "W/\"1\"".to_string(),
+            content: "[]".to_string(),
+            next_link: Some(next.to_string()),
+            last_link: None,
+        };
+        cache.insert(request, page.clone());
+        assert!(*cache.changed.lock().unwrap());
+
+        let stored = cache.pages.lock().unwrap();
+        let (key, stored_page) = stored.iter().next().unwrap();
+        assert!(!key.contains("secret"));
+        assert!(!stored_page.next_link.as_ref().unwrap().contains("secret"));
+        drop(stored);
+
+        assert_eq!(cache.get(request), Some(page));
+        let other_token = request.replace("secret", "other");
+        assert_eq!(
+            cache.get(&other_token).unwrap().next_link.unwrap(),
+            next.replace("secret", "other")
+        );
+    }
+
+    #[test]
+    fn parse_skips_malformed_pages() {
+        let contents = r#"{
+            "https://a/1": {"etag": "\"x\"", "content": "[1]", "next_link": "https://a/2"},
+            "https://a/2": {"content": "[2]"}
+        }"#;
+        let pages = parse(contents);
+        assert_eq!(pages.len(), 1);
+        assert_eq!(
+            pages["https://a/1"].next_link.as_deref(),
+            Some("https://a/2")
+        );
+        assert_eq!(pages["https://a/1"].last_link, None);
+        assert!(parse("not json").is_empty());
+    }
+}
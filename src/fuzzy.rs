@@ -0,0 +1,53 @@
+/// The score for each character of the pattern which is matched.
+const MATCH_SCORE: i64 = 1;
+/// The bonus for a match immediately following the previous one.
+const CONSECUTIVE_BONUS: i64 = 5;
+/// The bonus for a match at the start of the value or of a word within it.
+const WORD_START_BONUS: i64 = 3;
+
+/// Returns a score for how well `value` matches `pattern`, or `None` if it doesn't match.  The
+/// characters of `pattern` must all appear in `value` in the same order, though not necessarily
+/// consecutively.  Matching is case-insensitive, and higher scores are given for runs of
+/// consecutive characters and for matches at the start of words (i.e. following `/`, `-`, `_` or
+/// `.`).
+pub fn score(pattern: &str, value: &str) -> Option<i64> {
+    let value: Vec<char> = value.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut value_index = 0;
+    let mut previous_match: Option<usize> = None;
+    for pattern_char in pattern.to_lowercase().chars() {
+        let offset = value[value_index..]
+            .iter()
+            .position(|value_char| *value_char == pattern_char)?;
+        let index = value_index + offset;
+        score += MATCH_SCORE;
+        if previous_match.is_some_and(|previous| previous + 1 == index) {
+            score += CONSECUTIVE_BONUS;
+        }
+        if index == 0 || ['/', '-', '_', '.'].contains(&value[index - 1]) {
+            score += WORD_START_BONUS;
+        }
+        previous_match = Some(index);
+        value_index = index + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::score;
+
+    #[test]
+    fn fuzzy_score() {
+        assert_eq!(score("", "anything"), Some(0));
+        assert!(score("fix", "Herb/fix-parser").is_some());
+        assert!(score("fxp", "Herb/fix-parser").is_some());
+        assert!(score("FXP", "herb/fix-parser").is_some());
+        assert!(score("pxf", "Herb/fix-parser").is_none());
+        assert!(score("fixes", "Herb/fix-parser").is_none());
+
+        // Consecutive matches and matches at the start of words score higher.
+        assert!(score("fix", "Herb/fix-parser") > score("fix", "Herb/f-i-x"));
+        assert!(score("par", "Herb/fix-parser") > score("par", "Herb/fixpar"));
+    }
+}
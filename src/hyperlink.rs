@@ -0,0 +1,87 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+};
+
+/// Environment variables set only by terminals which support OSC 8 hyperlinks.
+const SUPPORTING_TERM_VARS: [&str; 4] = [
+    "DOMTERM",
+    "WT_SESSION",
+    "KITTY_WINDOW_ID",
+    "KONSOLE_VERSION",
+];
+
+/// Values of `TERM_PROGRAM` set by terminals which support OSC 8 hyperlinks.
+const SUPPORTING_TERM_PROGRAMS: [&str; 5] = ["iTerm.app", "WezTerm", "vscode", "Hyper", "ghostty"];
+
+/// Substrings of `TERM` values set by terminals which support OSC 8 hyperlinks.
+const SUPPORTING_TERMS: [&str; 5] = ["kitty", "alacritty", "foot", "ghostty", "wezterm"];
+
+/// Returns `text` wrapped in an OSC 8 escape sequence, so that supporting terminals display it as
+/// a link to `target`.
+pub fn link(text: &str, target: &str) -> String {
+    format!("\x1b]8;;{target}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// Whether stdout is a terminal which is known to support OSC 8 hyperlinks.  Setting the
+/// environment variable `FORCE_HYPERLINK` to `1` or `0` overrides the detection.
+pub fn are_supported() -> bool {
+    are_supported_by(io::stdout().is_terminal(), |name| env::var(name).ok())
+}
+
+/// Whether hyperlinks are supported, given whether stdout `is_terminal` and the environment
+/// variables returned by `var`.
+fn are_supported_by<F: Fn(&str) -> Option<String>>(is_terminal: bool, var: F) -> bool {
+    if let Some(force) = var("FORCE_HYPERLINK") {
+        return force.trim() != "0";
+    }
+    let term = var("TERM").unwrap_or_default();
+    if !is_terminal || term == "dumb" {
+        return false;
+    }
+    SUPPORTING_TERM_VARS.iter().any(|name| var(name).is_some())
+        || var("VTE_VERSION")
+            .and_then(|version| version.parse::<u32>().ok())
+            .is_some_and(|version| version >= 5000)
+        || var("TERM_PROGRAM")
+            .is_some_and(|program| SUPPORTING_TERM_PROGRAMS.contains(&program.as_str()))
+        || SUPPORTING_TERMS.iter().any(|name| term.contains(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    #[test]
+    fn link() {
+        assert_eq!(
+            super::link("bob", "https://github.com/bob"),
+            "\x1b]8;;https://github.com/bob\x1b\\bob\x1b]8;;\x1b\\"
+        );
+    }
+
+    #[test]
+    fn are_supported_by() {
+        let supported = |is_terminal: bool, vars: &[(&str, &str)]| {
+            let vars: HashMap<_, _> = vars.iter().copied().collect();
+            super::are_supported_by(is_terminal, |name| vars.get(name).map(ToString::to_string))
+        };
+
+        assert!(!supported(true, &[]));
+        assert!(!supported(true, &[("TERM", "xterm-256color")]));
+        assert!(!supported(false, &[("TERM_PROGRAM", "iTerm.app")]));
+        assert!(!supported(true, &[("TERM", "dumb"), ("WT_SESSION", "1")]));
+        assert!(!supported(true, &[("VTE_VERSION", "4600")]));
+
+        assert!(supported(true, &[("TERM_PROGRAM", "iTerm.app")]));
+        assert!(supported(true, &[("TERM", "xterm-kitty")]));
+        assert!(supported(true, &[("WT_SESSION", "abc")]));
+        assert!(supported(true, &[("VTE_VERSION", "6003")]));
+
+        assert!(supported(false, &[("FORCE_HYPERLINK", "1")]));
+        assert!(!supported(
+            true,
+            &[("FORCE_HYPERLINK", "0"), ("TERM", "xterm-kitty")]
+        ));
+    }
+}
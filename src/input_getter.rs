@@ -1,30 +1,38 @@
-use std::{
-    io::{self, BufRead, Write},
-    process,
-};
+use std::io::{self, BufRead, Write};
 
-/// Reads a line from `reader` and strips trailing whitespace.
-pub fn get_string<T: BufRead>(reader: &mut T) -> Result<String, String> {
+/// The answer which quits at a prompt for a name.  Unlike `q`, it can't be a remote's name.
+pub const QUIT_ESCAPE: &str = ":q";
+/// The answer which goes back at a prompt for a name.  Unlike `b`, it can't be a remote's name.
+pub const BACK_ESCAPE: &str = ":b";
+
+/// Reads a line from `reader` and strips trailing whitespace, or returns `None` if the end of the
+/// stream has been reached.
+pub fn get_string<T: BufRead>(reader: &mut T) -> Result<Option<String>, String> {
     io::stdout().flush().unwrap();
     let mut input = String::new();
     match reader.read_line(&mut input) {
-        Ok(0) => {
-            // A signal was sent - just exit the process as it was likely Ctrl-C.
-            process::exit(0);
-        }
-        Ok(_) => Ok(input.trim_end().to_string()),
+        Ok(0) => Ok(None),
+        Ok(_) => Ok(Some(input.trim_end().to_string())),
         Err(error) => Err(format!("Failed to read from std::cin: {error}.")),
     }
 }
 
-/// Reads a line from `reader`, and strips the trailing whitespace.  It returns true if the line is
-/// `Y` or `y`; false if the line is `N` or `n`; the unwrapped `default` value if the line is empty,
-/// or else an error.
-pub fn get_bool<T: BufRead>(reader: &mut T, default: Option<bool>) -> Result<bool, String> {
-    io::stdout().flush().unwrap();
-    let input = get_string(reader)?;
+/// Whether `input` is a request to quit at a prompt which isn't for a name, i.e. is `q`, `Q` or
+/// `:q`.
+pub fn is_quit_request(input: &str) -> bool {
+    input.eq_ignore_ascii_case("q") || input == QUIT_ESCAPE
+}
+
+/// Whether `input` is a request for help with the current prompt, i.e. is `?`.
+pub fn is_help_request(input: &str) -> bool {
+    input == "?"
+}
+
+/// Returns true if `input` is `Y` or `y`; false if it's `N` or `n`; the unwrapped `default` value
+/// if it's empty, or else an error.
+pub fn parse_bool(input: &str, default: Option<bool>) -> Result<bool, String> {
     let error = "Enter 'y' or 'n' only.".to_string();
-    match &*input {
+    match input {
         "Y" | "y" => Ok(true),
         "N" | "n" => Ok(false),
         "" => default.ok_or(error),
@@ -32,11 +40,8 @@ pub fn get_bool<T: BufRead>(reader: &mut T, default: Option<bool>) -> Result<boo
     }
 }
 
-/// Reads a line from `reader` and strips trailing whitespace.  It returns the value entered if it
-/// can be parsed as a `u64`.
-pub fn get_uint<T: BufRead>(reader: &mut T, default: Option<u64>) -> Result<u64, String> {
-    io::stdout().flush().unwrap();
-    let input = get_string(reader)?;
+/// Returns `input` parsed as a `u64`, or the unwrapped `default` value if `input` is empty.
+pub fn parse_uint(input: &str, default: Option<u64>) -> Result<u64, String> {
     let error = "Enter positive integer or zero.".to_string();
     if input.is_empty() {
         return default.ok_or(error);
@@ -55,51 +60,63 @@ mod tests {
 
     #[test]
     fn get_string() {
-        let mut cursor = make_cursor("AbCd");
-        assert_eq!(super::get_string(&mut cursor).unwrap(), "AbCd");
+        let mut cursor = make_cursor("AbCd\nEf\n");
+        assert_eq!(super::get_string(&mut cursor).unwrap().unwrap(), "AbCd");
+        assert_eq!(super::get_string(&mut cursor).unwrap().unwrap(), "Ef");
+        assert_eq!(super::get_string(&mut cursor).unwrap(), None);
+
+        let mut cursor = make_cursor("q\n");
+        assert_eq!(super::get_string(&mut cursor).unwrap().unwrap(), "q");
     }
 
     #[test]
-    fn get_bool() {
-        // Cases where `get_bool()` returns Ok(true)
-        let mut cursor = make_cursor("Y");
-        assert!(super::get_bool(&mut cursor, None).unwrap());
-        cursor = make_cursor("Y");
-        assert!(super::get_bool(&mut cursor, Some(false)).unwrap());
-        cursor = make_cursor("y");
-        assert!(super::get_bool(&mut cursor, None).unwrap());
-        cursor = make_cursor("y");
-        assert!(super::get_bool(&mut cursor, Some(false)).unwrap());
-
-        // Cases where `get_bool()` returns Ok(false)
-        cursor = make_cursor("N");
-        assert!(!super::get_bool(&mut cursor, None).unwrap());
-        cursor = make_cursor("N");
-        assert!(!super::get_bool(&mut cursor, Some(true)).unwrap());
-        cursor = make_cursor("n");
-        assert!(!super::get_bool(&mut cursor, None).unwrap());
-        cursor = make_cursor("n");
-        assert!(!super::get_bool(&mut cursor, Some(true)).unwrap());
-
-        // Cases where `get_bool()` returns Err
-        cursor = make_cursor("yy");
-        assert!(super::get_bool(&mut cursor, None).is_err());
-        cursor = make_cursor("nn");
-        assert!(super::get_bool(&mut cursor, Some(true)).is_err());
+    fn is_quit_request() {
+        assert!(super::is_quit_request("q"));
+        assert!(super::is_quit_request("Q"));
+        assert!(super::is_quit_request(":q"));
+        assert!(!super::is_quit_request("quit"));
+        assert!(!super::is_quit_request(":Q"));
+        assert!(!super::is_quit_request(""));
     }
 
     #[test]
-    fn get_uint() {
-        let mut cursor = make_cursor("0");
-        assert_eq!(super::get_uint(&mut cursor, None).unwrap(), 0);
-        cursor = make_cursor("999999");
-        assert_eq!(super::get_uint(&mut cursor, None).unwrap(), 999_999);
-        cursor = make_cursor("999999");
-        assert_eq!(super::get_uint(&mut cursor, Some(1234)).unwrap(), 999_999);
-
-        cursor = make_cursor("-1");
-        assert!(super::get_uint(&mut cursor, None).is_err());
-        cursor = make_cursor("gibberish");
-        assert!(super::get_uint(&mut cursor, None).is_err());
+    fn parse_bool() {
+        // Cases where `parse_bool()` returns Ok(true)
+        assert!(super::parse_bool("Y", None).unwrap());
+        assert!(super::parse_bool("Y", Some(false)).unwrap());
+        assert!(super::parse_bool("y", None).unwrap());
+        assert!(super::parse_bool("y", Some(false)).unwrap());
+        assert!(super::parse_bool("", Some(true)).unwrap());
+
+        // Cases where `parse_bool()` returns Ok(false)
+        assert!(!super::parse_bool("N", None).unwrap());
+        assert!(!super::parse_bool("N", Some(true)).unwrap());
+        assert!(!super::parse_bool("n", None).unwrap());
+        assert!(!super::parse_bool("n", Some(true)).unwrap());
+
+        // Cases where `parse_bool()` returns Err
+        assert!(super::parse_bool("yy", None).is_err());
+        assert!(super::parse_bool("nn", Some(true)).is_err());
+        assert!(super::parse_bool("", None).is_err());
+        assert!(super::parse_bool("?", Some(true)).is_err());
+    }
+
+    #[test]
+    fn is_help_request() {
+        assert!(super::is_help_request("?"));
+        assert!(!super::is_help_request("??"));
+        assert!(!super::is_help_request(""));
+    }
+
+    #[test]
+    fn parse_uint() {
+        assert_eq!(super::parse_uint("0", None).unwrap(), 0);
+        assert_eq!(super::parse_uint("999999", None).unwrap(), 999_999);
+        assert_eq!(super::parse_uint("999999", Some(1234)).unwrap(), 999_999);
+        assert_eq!(super::parse_uint("", Some(1234)).unwrap(), 1234);
+
+        assert!(super::parse_uint("", None).is_err());
+        assert!(super::parse_uint("-1", None).is_err());
+        assert!(super::parse_uint("gibberish", None).is_err());
     }
 }
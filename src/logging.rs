@@ -0,0 +1,35 @@
+use std::{
+    env,
+    io::{self, IsTerminal},
+};
+use tracing_subscriber::{filter::EnvFilter, fmt};
+
+/// The environment variable holding the filter which selects what's logged, in the syntax of
+/// `tracing_subscriber::EnvFilter`, e.g. `debug` or `add_remote::repo=trace`.
+const LOG_VAR: &str = "ADD_REMOTE_LOG";
+
+/// The filter used for `--verbose` if `ADD_REMOTE_LOG` isn't set.
+const VERBOSE_FILTER: &str = "add_remote=debug";
+
+/// Starts logging to stderr if `verbose` is set or `ADD_REMOTE_LOG` is non-empty, filtered by the
+/// latter if it's set.  Otherwise nothing is logged.  Returns an error describing an invalid
+/// filter.
+pub fn init(verbose: bool, plain: bool) -> Result<(), String> {
+    let filter = match env::var(LOG_VAR) {
+        Ok(directives) if !directives.trim().is_empty() => directives,
+        _ if verbose => VERBOSE_FILTER.to_string(),
+        _ => return Ok(()),
+    };
+    let filter = EnvFilter::builder()
+        .parse(&filter)
+        .map_err(|error| format!("Invalid {LOG_VAR} filter '{filter}': {error}"))?;
+    let ansi = !plain && env::var_os("NO_COLOR").is_none() && io::stderr().is_terminal();
+    fmt()
+        .with_env_filter(filter)
+        .with_writer(io::stderr)
+        .with_ansi(ansi)
+        .with_target(true)
+        .compact()
+        .try_init()
+        .map_err(|error| error.to_string())
+}
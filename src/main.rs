@@ -15,79 +15,393 @@
     clippy::pedantic
 )]
 
+#[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+compile_error!("Either the 'rustls' or the 'native-tls' feature must be enabled.");
+
+/// Owner avatars displayed via the kitty or iTerm2 terminal graphics protocols.
+mod avatar;
+/// Opens web pages in the user's browser.
+mod browser;
+/// Parses the command line arguments.
+mod cli;
+/// The errors which cause `add-remote` to exit, and their exit codes.
+mod error;
+/// Fork lists cached on disk along with their `ETag`s, so that unchanged pages needn't be
+/// downloaded again.
+mod fork_cache;
+/// Fuzzy matching for searching branch names.
+mod fuzzy;
+/// Parses the URLs of Git remotes.
+mod git_url;
+/// Clickable hyperlinks for terminals which support them.
+mod hyperlink;
 /// Reads and validates input from a stream.
 mod input_getter;
+/// Logs what's being done to stderr, if `--verbose` or `ADD_REMOTE_LOG` asks for it.
+mod logging;
+/// Glob and regex patterns for matching fork owners' names.
+mod pattern;
+/// A spinner showing what's being fetched while the forks are queried.
+mod progress;
+/// Templates for, and validation of, the names of new remotes.
+mod remote_name;
 /// Main struct that holds the details for the current Git repository.
 mod repo;
+/// Suggestions for the defaults offered to the user.
+mod suggest;
+/// Date arithmetic for the timestamps returned by the GitLab and GitHub APIs.
+mod timestamp;
+/// Tokens for the forges' APIs stored in the OS keyring.
+mod token_store;
 
-use colour::{dark_cyan, dark_cyan_ln, dark_green_ln, yellow_ln};
+use cli::{Command, Options};
+use colour::{dark_cyan, dark_cyan_ln, dark_green_ln, red_ln, yellow_ln};
+use error::Error;
 use repo::Repo;
-use std::{env, process};
+use std::{
+    env,
+    io::{self, BufRead, IsTerminal},
+    process, thread,
+    time::Duration,
+};
 
 /// Main function.
 fn main() {
     ctrlc::set_handler(move || process::exit(0)).expect("Error setting Ctrl-C handler");
-    let args: Vec<_> = env::args().collect();
+    if let Err(error) = run() {
+        red_ln!("{}", error);
+        process::exit(error.exit_code());
+    }
+}
 
-    if args
-        .iter()
-        .any(|arg| arg == "-h" || arg == "/?" || arg == "--help")
-    {
-        return print_help();
+/// Parses the command line and runs the chosen command.
+fn run() -> Result<(), Error> {
+    let options = Options::parse(env::args().skip(1)).map_err(Error::InvalidArgument)?;
+
+    if options.plain || options.stable {
+        let _ = colour::force_no_colour();
     }
 
-    if args
-        .iter()
-        .any(|arg| arg == "-v" || arg == "-V" || arg == "--version")
-    {
+    logging::init(options.verbose, options.plain || options.stable)
+        .map_err(Error::InvalidArgument)?;
+
+    if options.help {
+        print_help();
+        return Ok(());
+    }
+
+    if options.version {
         println!("{}", env!("CARGO_PKG_VERSION"));
-        return;
+        return Ok(());
+    }
+
+    forward_git_options(&options).map_err(Error::InvalidArgument)?;
+
+    match options.command.clone() {
+        Command::Add => match options.watch {
+            Some(interval) => watch_forks(options, interval),
+            None => add_remote(options),
+        },
+        Command::List => list_forks(options),
+        Command::Tidy => Repo::new_local(options)?.tidy(),
+        Command::Compare(alias) => Repo::new_local(options)?.compare(&alias),
+        Command::Switch(pattern) => Repo::new_local(options)?.switch(&pattern),
+        Command::Rename(old, new) => {
+            Repo::new_local(options)?.rename(old.as_deref(), new.as_deref())
+        }
+        Command::RenameAll => Repo::new_local(options)?.rename_all(),
+        Command::Remove(alias) => Repo::new_local(options)?.remove(alias.as_deref()),
+        Command::Prune => Repo::new_local(options)?.prune(),
+        Command::Convert(form, remotes) => Repo::new_local(options)?.convert(form, &remotes),
+        Command::Audit => Repo::new_local(options)?.audit(),
+        Command::Schema => {
+            Repo::print_audit_schema();
+            Ok(())
+        }
+        Command::Sync => Repo::new_local(options)?.sync(),
+        Command::AuthSet(host) => store_token(&host),
+        Command::AuthDelete(host) => delete_token(&host),
+        Command::Login(forge) => Repo::login(options, forge),
+        Command::Doctor => Repo::doctor(options),
+    }
+}
+
+/// Returns `host` lowercased, or an error if it isn't a bare host such as "github.com".
+fn parse_token_host(host: &str) -> Result<String, Error> {
+    if host.is_empty() || host.contains(['/', ':', '@']) {
+        return Err(Error::InvalidArgument(format!(
+            "Invalid host '{host}': expected a host such as 'github.com'."
+        )));
+    }
+    Ok(host.to_lowercase())
+}
+
+/// Reads a token for `host` and stores it in the OS keyring.  The token is read without echoing
+/// it if stdin is a terminal, or else from the first line of stdin.
+fn store_token(host: &str) -> Result<(), Error> {
+    let host = parse_token_host(host)?;
+    let token = if io::stdin().is_terminal() {
+        rpassword::prompt_password(format!("Token for {host}: "))
+    } else {
+        let mut line = String::new();
+        io::stdin().lock().read_line(&mut line).map(|_| line)
+    }
+    .map_err(|error| Error::InputNeeded(format!("Failed to read the token: {error}")))?;
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(Error::InputNeeded("No token was entered.".to_string()));
+    }
+    token_store::set(&host, token).map_err(Error::Keyring)?;
+    dark_green_ln!("Stored the token for {} in the OS keyring.", host);
+    Ok(())
+}
+
+/// Removes the token stored for `host` from the OS keyring.
+fn delete_token(host: &str) -> Result<(), Error> {
+    let host = parse_token_host(host)?;
+    if token_store::delete(&host).map_err(Error::Keyring)? {
+        dark_green_ln!("Removed the token for {} from the OS keyring.", host);
+    } else {
+        yellow_ln!("No token is stored for {} in the OS keyring.", host);
     }
+    Ok(())
+}
 
-    let mut repo = Repo::default();
+/// Lists the available forks, asks the user to choose one and a name for it, then adds it.
+fn add_remote(options: Options) -> Result<(), Error> {
+    let is_filtered = options.filter.is_some();
+    let is_quiet = options.quiet;
+    let mut repo = Repo::new(options)?;
     if repo.has_no_available_forks() {
-        yellow_ln!("There are no forks available which aren't already a remote:");
-        println!("{}", repo.git_remote_verbose_output());
-        return;
+        if !is_quiet {
+            println!(
+                "The existing remotes are:\n{}",
+                repo.git_remote_verbose_output()?
+            );
+        }
+        let message = if is_filtered {
+            "There are no forks matching the filter which aren't already a remote."
+        } else {
+            "There are no forks available which aren't already a remote."
+        };
+        return Err(Error::NoForksAvailable(message.to_string()));
+    }
+    choose_and_add_fork(&mut repo)
+}
+
+/// Lists the forks which could be added as remotes.
+fn list_forks(options: Options) -> Result<(), Error> {
+    let repo = Repo::new(options)?;
+    if repo.has_no_available_forks() {
+        return Err(Error::NoForksAvailable(
+            "There are no forks available to add.".to_string(),
+        ));
     }
     repo.show_available_forks();
-    repo.choose_fork();
-    if repo.choose_local_remote_alias() {
-        repo.offer_to_set_alias();
+    Ok(())
+}
+
+/// Repeatedly queries GitLab/GitHub for forks, waiting `interval` between queries, and lists any
+/// which weren't available at the previous query, offering to add one of them.
+fn watch_forks(options: Options, interval: Duration) -> Result<(), Error> {
+    let is_quiet = options.quiet;
+    let mut repo = Repo::new(options)?;
+    let mut known_owners = repo.available_fork_owners();
+    if !is_quiet {
+        println!("Watching for new forks every {interval:?}.  Press Ctrl-C to stop.");
+    }
+    loop {
+        thread::sleep(interval);
+        repo.refresh()?;
+        let current_owners = repo.available_fork_owners();
+        repo.retain_available_forks(|owner| !known_owners.contains(owner));
+        known_owners = current_owners;
+        if repo.has_no_available_forks() {
+            continue;
+        }
+        println!();
+        repo.show_available_forks();
+        if repo.confirm_add_new_fork()? {
+            choose_and_add_fork(&mut repo)?;
+        }
     }
-    repo.set_remote();
+}
+
+/// Applies the Git global options passed on the command line so that every Git invocation honours
+/// them, the same way Git passes them on to its own subcommands: `-C` changes the current
+/// directory, `--git-dir` sets `GIT_DIR`, and `-c` appends to the config set via `GIT_CONFIG_COUNT`.
+fn forward_git_options(options: &Options) -> Result<(), String> {
+    // As with Git, an empty path leaves the current directory unchanged.
+    for directory in options.directories.iter().filter(|dir| !dir.is_empty()) {
+        env::set_current_dir(directory)
+            .map_err(|error| format!("Cannot change to '{directory}': {error}"))?;
+    }
+    if let Some(git_dir) = &options.git_dir {
+        env::set_var("GIT_DIR", git_dir);
+    }
+    let existing_count: usize = env::var("GIT_CONFIG_COUNT")
+        .ok()
+        .and_then(|count| count.parse().ok())
+        .unwrap_or_default();
+    for (index, (key, value)) in options.git_config.iter().enumerate() {
+        env::set_var(format!("GIT_CONFIG_KEY_{}", existing_count + index), key);
+        env::set_var(
+            format!("GIT_CONFIG_VALUE_{}", existing_count + index),
+            value,
+        );
+    }
+    if !options.git_config.is_empty() {
+        env::set_var(
+            "GIT_CONFIG_COUNT",
+            (existing_count + options.git_config.len()).to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Asks the user to choose one or more of the available forks and a name for each, then adds them.
+fn choose_and_add_fork(repo: &mut Repo) -> Result<(), Error> {
+    loop {
+        if !repo.uses_tui() {
+            repo.show_available_forks();
+        }
+        repo.choose_fork()?;
+        if repo.add_chosen_forks()? {
+            break;
+        }
+        println!();
+    }
+    repo.offer_maintenance()
 }
 
 /// Prints the help message.
+#[allow(clippy::needless_raw_string_hashes)]
 fn print_help() {
+    print!("{}", cli::usage_help());
     print!(
         r#"
-Add a remote fork to a local Git repository.  When run from a Git repo, it queries GitLab or GitHub
-for the full list of forks and offers simple choices for adding one under a local alias.  The added
-fork will be configured with a pull-url only; the push-url will be disabled.
-
 Configuration
 =============
 'add-remote' will display all forks which aren't currently copied locally, then ask you to choose
 one and to provide an alias for it.
 
-It will offer a default selection (i.e. just hit <return> to select it) if it can.  The default will
-be chosen as follows:
+Enter 'q' at any prompt to quit, except at a prompt for a remote's name, where 'q' could be the
+name: there, enter ':q' to quit, or ':b' to go back and choose a different fork.  Enter '?' at any
+prompt for an explanation of what's being asked, and of where its default comes from.
+"#
+    );
+    print_fork_choice_help();
+    print_alias_help();
+    print_token_help();
+    print_network_help();
+    print_new_remote_help();
+    print_example_gitconfig();
+}
+
+/// Prints the help for the listing and choice of forks.
+#[allow(clippy::needless_raw_string_hashes)]
+fn print_fork_choice_help() {
+    print!(
+        r#"
+If the main fork/source is owned by a GitHub organisation or GitLab group, forks owned by its members
+are listed first and flagged as such.
+
+It will offer a default selection (i.e. just hit <return> to select it) if it can.  If there's only
+one fork available, it will be selected.  Otherwise each fork is given a score by adding up the
+weights of the following signals which apply to it, and the highest-scoring fork (the first listed in
+the case of a tie) is selected, as long as it scores above zero:
 
-* if there's only one fork available, it will be selected, or else
-* the main fork/source owner if not already added locally, or else
-* the fork indicated by the Git config value of "#
+* fork is the main fork/source (weight 100)
+* owner matches "#
     );
     dark_cyan!("add-remote.preferredFork");
     print!(
-        r#" if set, and if that fork
-  is not already added locally
+        r" (weight 60)
+* owner has been chosen before, i.e. has an "
+    );
+    dark_cyan!("add-remote.forkAlias");
+    print!(
+        r" entry (weight 40)
+* fork was recently active (weight 30 today, decaying to zero over a year)
+* owner is a member of the main fork/source's organisation/group (weight 20)
 
+The weights can be changed under the subkey "
+    );
+    dark_cyan!("add-remote.weight");
+    println!(
+        r" using the names sourceOwner,
+preferredFork, previouslyChosen, recentActivity and orgMember, e.g:
+"
+    );
+    yellow_ln!("    git config --global --add add-remote.weight.recentActivity 80");
+    print!(
+        r"
+To use the original strict order of precedence instead (the main fork/source owner, or else the first
+fork matching the preferred fork), set "
+    );
+    dark_cyan!("add-remote.suggestionStrategy");
+    println!(
+        " to 'precedence':
+"
+    );
+    yellow_ln!("    git config --global --add add-remote.suggestionStrategy precedence");
+    print!(
+        r#"
 You can set "#
     );
     dark_cyan!("add-remote.preferredFork");
     println!(" (e.g. to 'CasperLabs') by running:\n");
     yellow_ln!("    git config --global --add add-remote.preferredFork CasperLabs");
+    print!(
+        r"
+The value can also be a glob pattern, where '*' matches any sequence of characters and '?' matches
+any single character, or a regular expression wrapped in forward slashes.  Matching is
+case-insensitive, e.g:
+
+"
+    );
+    yellow_ln!("    git config --global --add add-remote.preferredFork \"casper*\"");
+    yellow_ln!(
+        "    git config --global --add add-remote.preferredFork \"/^casper(labs|-network)$/\""
+    );
+    print!(
+        r"
+Forks which you never want to see listed can be hidden across all repositories by adding their
+owners' names to the multi-valued Git config key "
+    );
+    dark_cyan!("add-remote.ignoreFork");
+    println!(
+        r".  As with
+the preferred fork, each value can be a glob pattern or a regular expression wrapped in forward
+slashes, e.g:
+"
+    );
+    yellow_ln!("    git config --global --add add-remote.ignoreFork dependabot");
+    yellow_ln!("    git config --global --add add-remote.ignoreFork \"*-bot\"");
+    println!(
+        r"
+To find a fork in a long list, enter part of its owner's name at the fork prompt, e.g. 'fra' for
+'Fraser999'.  If only one fork matches, it's chosen; otherwise just the matching forks are listed,
+closest match first.
+
+To hide a fork for the current repository only, enter 'h' followed by its index (e.g. 'h3') at the
+fork prompt.  This adds the fork's owner to the repository's local Git config, so it won't be listed
+in future runs.  The main fork/source is always listed, even if its owner matches an ignored fork.
+
+To hide forks which haven't been active recently, set "
+    );
+    dark_cyan!("add-remote.minActivity");
+    println!(
+        r" to the longest time since their last activity,
+e.g. '365d', or pass '--since' to override it:
+"
+    );
+    yellow_ln!("    git config --global add-remote.minActivity 365d");
+}
+
+/// Prints the help for the choice of the new remote's alias.
+#[allow(clippy::needless_raw_string_hashes)]
+fn print_alias_help() {
     print!(
         r#"
 Having chosen the fork to add, you will then be asked to provide an alias for it.  Again, a default
@@ -103,6 +417,11 @@ value will be presented, chosen as follows:
     dark_cyan!("add-remote.forkAlias");
     print!(
         r#" if set
+* uses the Git config value of "#
+    );
+    dark_cyan!("add-remote.remoteName");
+    print!(
+        r#" if set, expanded as per '--remote-name'
 * uses the fork-owner's name
 
 You can set "#
@@ -119,10 +438,34 @@ Default aliases can be added to your .gitconfig file under the subkey
     println!(" by running e.g:\n");
     yellow_ln!("    git config --global --add add-remote.forkAlias.anthonywilliams Anthony");
     yellow_ln!("    git config --global --add add-remote.forkAlias.hsutter Herb");
+    print!(
+        r"
+If you provide a custom alias, you'll be asked whether to add it under "
+    );
+    dark_cyan!("add-remote.forkAlias");
+    print!(
+        r" in
+your global git-config.  To always do so without asking, or to never be asked, set
+"
+    );
+    dark_cyan!("add-remote.storeAlias");
+    println!(" to 'always' or 'never', e.g:\n");
+    yellow_ln!("    git config --global --add add-remote.storeAlias never");
+    print!(
+        r"
+To store aliases in the repository's own git-config instead, so they only apply to that project,
+pass '--alias-scope local' or set "
+    );
+    dark_cyan!("add-remote.aliasScope");
+    println!(" to 'local'.");
+}
+
+/// Prints the help for providing Personal Access Tokens.
+fn print_token_help() {
     println!(
         r#"
 To use `add-remote` with any GitLab repository or with a private GitHub one, you need to provide a
-Personal Access Token via git config.
+Personal Access Token via git config or an environment variable.
 
 For GitLab, create a token (https://gitlab.com/profile/personal_access_tokens) ensuring it has
 "read_api" scope, then add it to your .gitconfig:
@@ -131,11 +474,201 @@ For GitLab, create a token (https://gitlab.com/profile/personal_access_tokens) e
     yellow_ln!("    git config --global --add add-remote.gitLabToken <GitLab Token's Value>");
     println!(
         r#"
-For GitHub, create a token (https://github.com/settings/tokens) ensuring it has full "repo" scope,
-then add it **along with your GitHub username** separated with a colon ':' to your .gitconfig:
+For GitHub, create a fine-grained token (https://github.com/settings/personal-access-tokens/new)
+with read access to the repositories you use 'add-remote' with (or a classic token with full "repo"
+scope), then add it to your .gitconfig:
 "#
     );
-    yellow_ln!("    git config --global --add add-remote.gitHubToken <GitHub Username:GitHub Token's Value>");
+    yellow_ln!("    git config --global --add add-remote.gitHubToken <GitHub Token's Value>");
+    println!(
+        r"
+The token is sent as a bearer token.  The older format of your GitHub username and the token
+separated with a colon ':' is still accepted, and is sent using basic authentication."
+    );
+    println!(
+        r"
+For Gitea and Forgejo instances such as Codeberg, a token is only needed for private repositories.
+Create one with read access to repositories, then add it to your .gitconfig:
+"
+    );
+    yellow_ln!("    git config --global --add add-remote.giteaToken <Gitea Token's Value>");
+    print!(
+        r"
+To keep a token out of your .gitconfig, write it to a file instead and set
+"
+    );
+    dark_cyan!("add-remote.gitLabTokenFile");
+    print!(", ");
+    dark_cyan!("add-remote.gitHubTokenFile");
+    print!(" or ");
+    dark_cyan!("add-remote.giteaTokenFile");
+    print!(
+        r" to the file's path.
+The file is read each time 'add-remote' runs.  A token set directly takes precedence over a file.
+Files ending in '.gpg' or '.asc' are decrypted with 'gpg', and those ending in '.age' with 'age'
+using the identity file set as "
+    );
+    dark_cyan!("add-remote.ageIdentity");
+    println!(".\n");
+    yellow_ln!("    git config --global --add add-remote.gitHubTokenFile ~/.secrets/gh_token");
+    println!(
+        r"
+To keep a token out of plaintext files altogether, store it in the OS keyring instead by running
+e.g. 'add-remote auth set github.com'.  It's used if neither a token nor a token file is set.
+Alternatively, run 'add-remote login github' or 'add-remote login gitlab' to log in via OAuth and
+store the resulting token there, using the OAuth application whose client ID is set as
+'add-remote.gitHubClientId' or 'add-remote.gitLabClientId'.
+
+Failing all of those, the token is read from the first of these environment variables which is set:
+
+    GitHub:         ADD_REMOTE_GITHUB_TOKEN, GITHUB_TOKEN, GH_TOKEN
+    GitLab:         ADD_REMOTE_GITLAB_TOKEN, GITLAB_TOKEN
+    Gitea/Forgejo:  ADD_REMOTE_GITEA_TOKEN, GITEA_TOKEN"
+    );
+}
+
+/// Prints the help for the network settings which are honoured.
+fn print_network_help() {
+    print!(
+        r"
+Requests to the GitLab or GitHub API honour Git's own "
+    );
+    dark_cyan!("http.proxy");
+    print!(", ");
+    dark_cyan!("http.sslVerify");
+    print!(" and ");
+    dark_cyan!("http.sslCAInfo");
+    println!(
+        r" settings, including
+any per-URL overrides such as 'http.https://api.github.com.proxy', along with the GIT_SSL_NO_VERIFY
+and GIT_SSL_CAINFO environment variables.  If 'http.proxy' isn't set, 'https.proxy' is used for
+HTTPS requests, and failing that, the HTTPS_PROXY, HTTP_PROXY and ALL_PROXY environment variables.
+Hosts listed in NO_PROXY are always reached directly."
+    );
+    print!(
+        r"
+To trust an internal CA's certificates in addition to the usual ones, e.g. for a self-hosted forge,
+set "
+    );
+    dark_cyan!("add-remote.caBundle");
+    println!(
+        r" to the path of a PEM bundle of them, e.g:
+"
+    );
+    yellow_ln!("    git config --global add-remote.caBundle ~/certs/internal-ca.pem");
+    println!(
+        r"
+As a last resort, '--insecure' skips verifying certificates altogether."
+    );
+    print!(
+        r"
+An API request which hasn't completed within 30 seconds is abandoned.  To change this, set
+"
+    );
+    dark_cyan!("add-remote.httpTimeout");
+    println!(
+        r" or pass '--timeout', e.g:
+"
+    );
+    yellow_ln!("    git config --global add-remote.httpTimeout 1m");
+    print!(
+        r"
+A request which times out, fails to connect, or gets a server error is retried up to twice, waiting
+a little longer each time.  To change this, set "
+    );
+    dark_cyan!("add-remote.httpRetries");
+    println!(" or pass '--retries'.");
+    println!(
+        r"
+To see every API request and Git command run, pass '--verbose'.  For finer control, set the
+ADD_REMOTE_LOG environment variable to a filter such as 'debug' or 'add_remote::repo=trace'."
+    );
+    print!(
+        r"
+Repositories on Codeberg and gitea.com are recognised automatically.  To use a self-hosted Gitea or
+Forgejo instance, add its host to "
+    );
+    dark_cyan!("add-remote.giteaHost");
+    println!(", e.g:\n");
+    yellow_ln!("    git config --global --add add-remote.giteaHost git.example.com");
+    print!(
+        r"
+Other forges can be described by URL templates and JSON pointers under 'add-remote.<host>', of
+which only "
+    );
+    dark_cyan!("add-remote.<host>.forksUrl");
+    println!(
+        r" is required.  See the README for the full list of
+settings, e.g:
+"
+    );
+    yellow_ln!(
+        "    git config --global add-remote.git.example.com.forksUrl \
+         \"https://git.example.com/api/v1/repos/{{owner}}/{{repo}}/forks\""
+    );
+    print!(
+        r"
+If a remote points at a mirror rather than at GitLab or GitHub, set
+"
+    );
+    dark_cyan!("add-remote.<mirror URL>.mirrorOf");
+    println!(
+        r" to the URL of the repository it mirrors so that its
+forks can still be found, e.g:
+"
+    );
+    yellow_ln!(
+        "    git config --global add-remote.https://git.internal/mirrors/cargo.git.mirrorOf \
+         https://github.com/rust-lang/cargo.git"
+    );
+}
+
+/// Prints the help for configuring the new remote.
+fn print_new_remote_help() {
+    print!(
+        r"
+The new remote is given the fork's SSH URL unless all of the existing remotes use HTTPS.  To
+always use one or the other, pass '--protocol ssh' or '--protocol https', or set "
+    );
+    dark_cyan!("add-remote.protocol");
+    println!(" to 'ssh' or\n'https'.");
+    print!(
+        r"
+Pushing to the new remote is disabled by setting its push URL to 'disable_push'.  To leave it
+enabled, pass '--allow-push', or set "
+    );
+    dark_cyan!("add-remote.disablePush");
+    println!(" to false.  To disable it with a different push URL, set");
+    dark_cyan!("add-remote.pushUrlSentinel");
+    println!(", e.g:\n");
+    yellow_ln!("    git config --global --add add-remote.pushUrlSentinel no_push");
+    print!(
+        r"
+Fetching from the new remote prunes remote-tracking branches whose branches have been deleted from
+the fork, by setting 'remote.<alias>.prune' to true.  To leave them in place, set "
+    );
+    dark_cyan!("add-remote.pruneOnFetch");
+    println!(" to\nfalse.");
+    print!(
+        r"
+After fetching the new remote, 'add-remote' offers to check out a local branch tracking the branch
+passed via '--branch', or else the fork's default branch.  To stop being asked, set "
+    );
+    dark_cyan!("add-remote.offerCheckout");
+    println!(" to\nfalse.");
+    print!(
+        r"
+After adding a remote, 'add-remote' offers to register the repository with 'git maintenance' so that
+all of its remotes are fetched in the background, unless it's already registered.  To stop being
+asked, set "
+    );
+    dark_cyan!("add-remote.offerMaintenance");
+    println!(" to false.");
+}
+
+/// Prints an example .gitconfig containing all of the settings described in the help.
+#[allow(clippy::needless_raw_string_hashes)]
+fn print_example_gitconfig() {
     println!(
         r#"
 Having run these Git config commands, your .gitconfig should contain the following:
@@ -144,9 +677,12 @@ Having run these Git config commands, your .gitconfig should contain the followi
     dark_green_ln!(
         r#"[add-remote]
     preferredFork = CasperLabs
+    ignoreFork = dependabot
+    ignoreFork = *-bot
+    minActivity = 365d
     mainForkOwnerAlias = owner
     gitLabToken = <GitLab Token's Value>
-    gitHubToken = <GitHub Username:GitHub Token's Value>
+    gitHubToken = <GitHub Token's Value>
 [add-remote "forkAlias"]
     anthonywilliams = Anthony
     hsutter = Herb
@@ -0,0 +1,114 @@
+use regex::{Regex, RegexBuilder};
+
+/// A case-insensitive pattern for matching against fork owners' names.  Values wrapped in forward
+/// slashes (e.g. `/^casper(labs)?$/`) are treated as regular expressions, while anything else is
+/// treated as a glob where `*` matches any sequence of characters and `?` matches any single
+/// character.
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    Glob(Vec<char>),
+    Regex(Regex),
+}
+
+impl Pattern {
+    /// Parses `pattern`, returning an error if it's an invalid regular expression.
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        if pattern.len() > 1 && pattern.starts_with('/') && pattern.ends_with('/') {
+            return RegexBuilder::new(&pattern[1..pattern.len() - 1])
+                .case_insensitive(true)
+                .build()
+                .map(Pattern::Regex)
+                .map_err(|error| format!("Invalid regular expression {pattern}: {error}"));
+        }
+        Ok(Pattern::Glob(pattern.to_lowercase().chars().collect()))
+    }
+
+    /// Like `new`, except that a plain value (i.e. a glob without any wildcards) matches anywhere
+    /// within the value being tested rather than having to match it entirely.
+    pub fn new_filter(pattern: &str) -> Result<Self, String> {
+        match Self::new(pattern)? {
+            Pattern::Glob(glob) if !glob.iter().any(|c| *c == '*' || *c == '?') => {
+                Self::new(&format!("*{pattern}*"))
+            }
+            pattern => Ok(pattern),
+        }
+    }
+
+    /// Whether `value` matches this pattern.  Globs must match the whole of `value`, whereas
+    /// regular expressions are unanchored unless they include `^` and/or `$`.
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            Pattern::Glob(glob) => {
+                let value: Vec<char> = value.to_lowercase().chars().collect();
+                Self::glob_matches(glob, &value)
+            }
+            Pattern::Regex(regex) => regex.is_match(value),
+        }
+    }
+
+    /// Iterative glob matching, backtracking to the most recent `*` on a mismatch.
+    fn glob_matches(glob: &[char], value: &[char]) -> bool {
+        let (mut glob_index, mut value_index) = (0, 0);
+        let mut backtrack: Option<(usize, usize)> = None;
+        while value_index < value.len() {
+            match glob.get(glob_index) {
+                Some('*') => {
+                    backtrack = Some((glob_index, value_index));
+                    glob_index += 1;
+                }
+                Some(c) if *c == '?' || *c == value[value_index] => {
+                    glob_index += 1;
+                    value_index += 1;
+                }
+                _ => match backtrack {
+                    Some((star_index, star_value_index)) => {
+                        glob_index = star_index + 1;
+                        value_index = star_value_index + 1;
+                        backtrack = Some((star_index, value_index));
+                    }
+                    None => return false,
+                },
+            }
+        }
+        glob[glob_index..].iter().all(|c| *c == '*')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pattern;
+
+    #[test]
+    fn glob() {
+        let pattern = Pattern::new("casper*").unwrap();
+        assert!(pattern.matches("CasperLabs"));
+        assert!(pattern.matches("casper"));
+        assert!(pattern.matches("casper-network"));
+        assert!(!pattern.matches("TheCasper"));
+
+        let pattern = Pattern::new("*labs").unwrap();
+        assert!(pattern.matches("CasperLabs"));
+        assert!(!pattern.matches("CasperLabsX"));
+
+        let pattern = Pattern::new("a?c*z").unwrap();
+        assert!(pattern.matches("abcz"));
+        assert!(pattern.matches("aXc-lots-of-z-and-z"));
+        assert!(!pattern.matches("acz"));
+
+        let pattern = Pattern::new("Fraser999").unwrap();
+        assert!(pattern.matches("fraser999"));
+        assert!(!pattern.matches("fraser9999"));
+    }
+
+    #[test]
+    fn regex() {
+        let pattern = Pattern::new("/^casper(labs)?$/").unwrap();
+        assert!(pattern.matches("Casper"));
+        assert!(pattern.matches("CASPERLABS"));
+        assert!(!pattern.matches("casper-network"));
+
+        assert!(Pattern::new("/(/").is_err());
+        // A lone slash is a glob.
+        assert!(Pattern::new("/").unwrap().matches("/"));
+    }
+}
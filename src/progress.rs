@@ -0,0 +1,88 @@
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::{
+    io::{self, IsTerminal},
+    time::Duration,
+};
+use tracing::level_filters::LevelFilter;
+
+/// How often the spinner advances.
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A spinner on stderr describing what's being fetched, so that a long series of API requests
+/// doesn't look like a hang.  It's only drawn if stderr is a terminal and `--plain` wasn't passed,
+/// as a constantly redrawn line is unhelpful to screen readers; otherwise every method is a no-op.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    bar: ProgressBar,
+}
+
+impl Progress {
+    /// Returns a spinner which is never drawn.
+    pub fn hidden() -> Self {
+        Progress {
+            bar: ProgressBar::hidden(),
+        }
+    }
+
+    /// Starts a spinner showing `message`, unless `plain` is set, stderr isn't a terminal, or
+    /// anything is being logged to stderr.
+    pub fn start(message: String, plain: bool) -> Self {
+        if plain || !io::stderr().is_terminal() || LevelFilter::current() != LevelFilter::OFF {
+            return Self::hidden();
+        }
+        let bar = ProgressBar::new_spinner().with_message(message);
+        if let Ok(style) = ProgressStyle::with_template("{spinner:.cyan} {msg}") {
+            bar.set_style(style);
+        }
+        bar.enable_steady_tick(TICK_INTERVAL);
+        Progress { bar }
+    }
+
+    /// Shows that page `page` of `total` (if known) of `what` is being fetched.
+    pub fn page(&self, what: &str, page: usize, total: Option<usize>) {
+        let message = match total {
+            Some(total) => format!("Fetching {what}, page {page}/{total}…"),
+            None => format!("Fetching {what}, page {page}…"),
+        };
+        self.bar.set_message(message);
+    }
+
+    /// Hides the spinner while `f` runs, so that whatever `f` prints isn't garbled by it.
+    pub fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        self.bar.suspend(f)
+    }
+
+    /// Hides the spinner until `resume` is called, e.g. while a countdown is shown instead.
+    pub fn pause(&self) {
+        if !self.bar.is_hidden() {
+            self.bar.set_draw_target(ProgressDrawTarget::hidden());
+        }
+    }
+
+    /// Shows the spinner again after `pause`.
+    pub fn resume(&self) {
+        if !self.bar.is_finished() {
+            self.bar.set_draw_target(ProgressDrawTarget::stderr());
+        }
+    }
+
+    /// Removes the spinner.
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Progress;
+
+    #[test]
+    fn hidden_progress_is_inert() {
+        let progress = Progress::start("Querying".to_string(), true);
+        progress.page("the forks of a/b", 2, Some(3));
+        progress.pause();
+        progress.resume();
+        progress.finish();
+        assert!(progress.bar.is_hidden());
+    }
+}
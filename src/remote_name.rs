@@ -0,0 +1,161 @@
+/// A placeholder which can appear within a `RemoteNameTemplate`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Placeholder {
+    /// `{owner}`: the fork owner's name.
+    Owner,
+    /// `{repo}`: the fork's repository name.
+    Repo,
+}
+
+/// A part of a parsed `RemoteNameTemplate`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Segment {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+/// A template for the name of a new remote, e.g. `{owner}-{repo}`.  Literal braces can be included
+/// by doubling them, i.e. `{{` and `}}`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RemoteNameTemplate(Vec<Segment>);
+
+impl RemoteNameTemplate {
+    /// Parses `template`, returning an error if it contains an unknown or unterminated placeholder.
+    pub fn new(template: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    literal.push('{');
+                    let _ = chars.next();
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    literal.push('}');
+                    let _ = chars.next();
+                }
+                '{' => {
+                    let (name, rest) = chars.as_str().split_once('}').ok_or_else(|| {
+                        format!("Unterminated placeholder in remote name template '{template}'.")
+                    })?;
+                    let placeholder = match name {
+                        "owner" => Placeholder::Owner,
+                        "repo" => Placeholder::Repo,
+                        _ => {
+                            return Err(format!(
+                                "Unknown placeholder '{{{name}}}' in remote name template \
+                                 '{template}'.  Expected '{{owner}}' or '{{repo}}'."
+                            ))
+                        }
+                    };
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Placeholder(placeholder));
+                    chars = rest.chars();
+                }
+                '}' => {
+                    return Err(format!(
+                        "Unmatched '}}' in remote name template '{template}'."
+                    ))
+                }
+                _ => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        if segments.is_empty() {
+            return Err("The remote name template must not be empty.".to_string());
+        }
+        Ok(RemoteNameTemplate(segments))
+    }
+
+    /// Returns the remote name for the fork of `repo` owned by `owner`.
+    pub fn expand(&self, owner: &str, repo: &str) -> String {
+        self.0
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(literal) => literal.as_str(),
+                Segment::Placeholder(Placeholder::Owner) => owner,
+                Segment::Placeholder(Placeholder::Repo) => repo,
+            })
+            .collect()
+    }
+}
+
+/// Checks that `name` can be used as the name of a Git remote, i.e. that `refs/remotes/<name>/`
+/// would be a valid prefix for the remote-tracking branches, using the rules of
+/// `git check-ref-format`.
+pub fn validate(name: &str) -> Result<(), String> {
+    let invalid = |reason: &str| Err(format!("Invalid remote name '{name}': {reason}."));
+    if name.is_empty() {
+        return invalid("it must not be empty");
+    }
+    if name.starts_with('-') {
+        return invalid("it must not start with '-'");
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| c.is_whitespace() || c.is_control() || "~^:?*[\\".contains(*c))
+    {
+        return invalid(&format!("it must not contain {c:?}"));
+    }
+    if name.contains("..") || name.contains("@{") {
+        return invalid("it must not contain '..' or '@{'");
+    }
+    if name.ends_with('.') {
+        return invalid("it must not end with '.'");
+    }
+    for component in name.split('/') {
+        if component.is_empty() {
+            return invalid("it must not start or end with '/', or contain '//'");
+        }
+        if component.starts_with('.') || component.strip_suffix(".lock").is_some() {
+            return invalid("no part of it may start with '.' or end with '.lock'");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate, RemoteNameTemplate};
+
+    #[test]
+    fn expand_template() {
+        let template = RemoteNameTemplate::new("{owner}-{repo}").unwrap();
+        assert_eq!(template.expand("Fraser999", "cargo"), "Fraser999-cargo");
+        let template = RemoteNameTemplate::new("fork/{owner}").unwrap();
+        assert_eq!(template.expand("Fraser999", "cargo"), "fork/Fraser999");
+        let template = RemoteNameTemplate::new("{{{repo}}}").unwrap();
+        assert_eq!(template.expand("Fraser999", "cargo"), "{cargo}");
+        let template = RemoteNameTemplate::new("upstream").unwrap();
+        assert_eq!(template.expand("Fraser999", "cargo"), "upstream");
+
+        assert!(RemoteNameTemplate::new("").is_err());
+        assert!(RemoteNameTemplate::new("{user}").is_err());
+        assert!(RemoteNameTemplate::new("{owner").is_err());
+        assert!(RemoteNameTemplate::new("owner}").is_err());
+    }
+
+    #[test]
+    fn validate_remote_name() {
+        for valid in &[
+            "upstream",
+            "Fraser999-cargo",
+            "fork/Fraser999",
+            "a.b",
+            "{cargo}",
+        ] {
+            assert!(validate(valid).is_ok(), "{}", valid);
+        }
+        for invalid in &[
+            "", "-x", "a b", "a:b", "a..b", "a@{b", "a.", "/a", "a/", "a//b", ".a", "a/.b",
+            "a.lock", "a?", "a*", "a[b", "a\\b", "a~b", "a^b",
+        ] {
+            assert!(validate(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
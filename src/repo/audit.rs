@@ -0,0 +1,373 @@
+use super::{
+    doctor::{Outcome, Report},
+    forge::{self, RepoState, RepoStatus},
+    Name, Owner, Repo, DISABLED_PUSH_URL,
+};
+use crate::{cli::OutputFormat, error::Error};
+use serde_json::{json, Value};
+
+/// The version of the JSON printed by `add-remote audit --format json`, incremented whenever a
+/// field is removed or its meaning changes.
+const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// The JSON Schema of the JSON printed by `add-remote audit --format json`.
+const JSON_SCHEMA: &str = include_str!("../../schemas/audit.schema.json");
+
+/// What `add-remote audit` found out about one remote.
+struct RemoteAudit {
+    alias: String,
+    /// The owner and name of the repository the remote fetches from, if it's on a known forge.
+    fork: Option<(Owner, Name)>,
+    /// Whether the remote was added by `add-remote`.
+    managed: bool,
+    /// Whether pushing to the remote should be disabled, i.e. it's managed and
+    /// `add-remote.disablePush` isn't false.
+    expect_push_disabled: bool,
+    /// The state of the repository as reported by its forge, or a description of why this couldn't
+    /// be found.  `None` if the remote isn't on a known forge.
+    state: Option<Result<RepoState, String>>,
+    push_disabled: bool,
+    /// The push URL which disables pushing, as set by `add-remote.pushUrlSentinel`.
+    push_url_sentinel: String,
+    /// The owner of the fork and the alias configured for them under `add-remote.forkAlias`, if
+    /// any.
+    configured_alias: Option<(Owner, String)>,
+}
+
+impl RemoteAudit {
+    /// Returns the `<owner>/<name>` of the fork.
+    fn fork_name(&self) -> Option<String> {
+        self.fork
+            .as_ref()
+            .map(|(owner, name)| format!("{}/{}", owner.0, name.0))
+    }
+
+    /// Returns the status of the repository, if the forge reported it.
+    fn status(&self) -> Option<RepoStatus> {
+        Some(self.state.as_ref()?.as_ref().ok()?.status)
+    }
+
+    /// Returns the current `<owner>/<name>` of the repository if it has been renamed or
+    /// transferred.
+    fn moved_to(&self) -> Option<&str> {
+        let fork_name = self.fork_name()?;
+        let full_name = self.state.as_ref()?.as_ref().ok()?.full_name.as_deref()?;
+        (!full_name.eq_ignore_ascii_case(&fork_name)).then_some(full_name)
+    }
+
+    /// Returns the outcomes of the checks, in the order they're reported.
+    fn outcomes(&self) -> Vec<Outcome> {
+        let mut outcomes = Vec::new();
+        let fork = self.fork_name().unwrap_or_default();
+        outcomes.push(match &self.state {
+            None => Outcome::Warn(
+                "not hosted on a supported forge, so its repository wasn't checked".to_string(),
+            ),
+            Some(Err(error)) => Outcome::Warn(format!("couldn't check {fork}: {error}")),
+            Some(Ok(state)) => match state.status {
+                RepoStatus::Active => Outcome::Pass(format!("{fork} exists")),
+                RepoStatus::Archived => Outcome::Warn(format!("{fork} has been archived")),
+                RepoStatus::Missing => Outcome::Fail(format!(
+                    "{fork} no longer exists, or is private; 'add-remote prune' offers to remove \
+                     the remote"
+                )),
+            },
+        });
+        if let Some(current) = self.moved_to() {
+            outcomes.push(Outcome::Warn(format!(
+                "{fork} has been renamed or transferred to {current}, so the remote's URL is out \
+                 of date"
+            )));
+        }
+        if self.expect_push_disabled {
+            outcomes.push(if self.push_disabled {
+                Outcome::Pass("pushing is disabled".to_string())
+            } else {
+                Outcome::Warn(format!(
+                    "pushing is enabled; 'git remote set-url --push {} {}' disables it, or set \
+                     add-remote.disablePush to false if it's meant to be enabled",
+                    self.alias, self.push_url_sentinel
+                ))
+            });
+        }
+        if let Some((owner, configured)) = &self.configured_alias {
+            outcomes.push(if *configured == self.alias {
+                Outcome::Pass(format!("named as per add-remote.forkAlias.{}", owner.0))
+            } else {
+                Outcome::Warn(format!(
+                    "add-remote.forkAlias.{} is '{configured}'; 'add-remote rename-all' offers to \
+                     rename the remote",
+                    owner.0
+                ))
+            });
+        }
+        outcomes
+    }
+
+    /// Returns the findings as a JSON object.
+    fn to_json(&self) -> Value {
+        let (status, error) = match &self.state {
+            None => (None, None),
+            Some(Ok(state)) => (Some(state.status), None),
+            Some(Err(error)) => (None, Some(error.as_str())),
+        };
+        json!({
+            "remote": self.alias,
+            "fork": self.fork_name(),
+            "managed": self.managed,
+            "exists": status.map(|status| status != RepoStatus::Missing),
+            "archived": status.map(|status| status == RepoStatus::Archived),
+            "check_error": error,
+            "moved_to": self.moved_to(),
+            "push_disabled": self.push_disabled,
+            "configured_alias": self.configured_alias.as_ref().map(|(_, alias)| alias),
+            "alias_matches": self
+                .configured_alias
+                .as_ref()
+                .map(|(_, alias)| *alias == self.alias),
+        })
+    }
+}
+
+impl Repo {
+    /// Reports, for each remote, whether its repository still exists, whether it has been
+    /// renamed or transferred, whether pushing to it is disabled if it's managed by `add-remote`,
+    /// and whether its name matches the alias configured for its owner.  The report is printed
+    /// in the format chosen by `--format`.  Returns an error if any repository no longer exists.
+    pub fn audit(&mut self) -> Result<(), Error> {
+        let managed = self.managed_remotes();
+        let mut audits = Vec::new();
+        let mut forges = Vec::new();
+        for alias in self.get_local_remote_aliases()? {
+            let local = Self::get_local_remote(&*self.git, &self.mirrors, &alias);
+            let (fork, state) = match local {
+                Some((owner, (name, _, url))) => match forge::new_provider(self, &url) {
+                    Ok(forge) => {
+                        forges.push((audits.len(), forge));
+                        (Some((owner, name)), None)
+                    }
+                    Err(error) => (Some((owner, name)), Some(Err(error.to_string()))),
+                },
+                None => (None, None),
+            };
+            let configured_alias = self.remote_owner(&alias).and_then(|owner| {
+                let configured = self.config.fork_aliases.get(&owner.0.to_lowercase())?;
+                Some((owner, configured.clone()))
+            });
+            let is_managed = managed.iter().any(|(managed, _)| managed.0 == alias);
+            audits.push(RemoteAudit {
+                managed: is_managed,
+                expect_push_disabled: is_managed && self.disable_push,
+                push_disabled: self.is_push_disabled(&alias),
+                push_url_sentinel: self.push_url_sentinel.clone(),
+                alias,
+                fork,
+                state,
+                configured_alias,
+            });
+        }
+
+        if let Some((_, forge)) = forges.first() {
+            let api_url = forge.api_url();
+            self.configure_client(&api_url)?;
+        }
+        let repos: Vec<_> = forges
+            .iter()
+            .filter_map(|(index, forge)| {
+                let (owner, name) = audits[*index].fork.as_ref()?;
+                Some((owner, name, forge.as_ref()))
+            })
+            .collect();
+        let states = self.query_repo_states(&repos);
+        for ((index, _), state) in forges.iter().zip(states) {
+            audits[*index].state = Some(state);
+        }
+
+        match self.options.format {
+            OutputFormat::Human => {
+                let mut report = Report::default();
+                for audit in &audits {
+                    let subject = format!("Remote '{}'", audit.alias);
+                    for outcome in audit.outcomes() {
+                        report.record(&subject, outcome);
+                    }
+                }
+                report.finish()
+            }
+            OutputFormat::Json => {
+                println!("{}", audits_to_json(&audits));
+                let missing = audits
+                    .iter()
+                    .filter(|audit| audit.status() == Some(RepoStatus::Missing))
+                    .count();
+                if missing == 0 {
+                    Ok(())
+                } else {
+                    Err(Error::ChecksFailed(missing))
+                }
+            }
+        }
+    }
+
+    /// Prints the JSON Schema of the report printed by `add-remote audit --format json`.
+    pub fn print_audit_schema() {
+        print!("{JSON_SCHEMA}");
+    }
+
+    /// Returns whether the remote `alias` pushes to the sentinel set by
+    /// `add-remote.pushUrlSentinel`, or to the default one which it may have been given before the
+    /// sentinel was set.
+    fn is_push_disabled(&self, alias: &str) -> bool {
+        self.get_remote_push_url(alias).is_some_and(|push_url| {
+            [self.push_url_sentinel.as_str(), DISABLED_PUSH_URL].contains(&push_url.trim())
+        })
+    }
+}
+
+/// Returns the report printed by `add-remote audit --format json`.
+fn audits_to_json(audits: &[RemoteAudit]) -> Value {
+    json!({
+        "schema_version": JSON_SCHEMA_VERSION,
+        "remotes": audits.iter().map(RemoteAudit::to_json).collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        audits_to_json, RemoteAudit, RepoState, RepoStatus, JSON_SCHEMA, JSON_SCHEMA_VERSION,
+    };
+    use crate::repo::{Name, Owner};
+    use serde_json::{json, Value};
+
+    fn audit(status: RepoStatus, full_name: &str) -> RemoteAudit {
+        RemoteAudit {
+            alias: "bob".to_string(),
+            fork: Some((Owner("Bob".to_string()), Name("repo".to_string()))),
+            managed: true,
+            expect_push_disabled: true,
+            state: Some(Ok(RepoState {
+                status,
+                full_name: Some(full_name.to_string()),
+            })),
+            push_disabled: true,
+            push_url_sentinel: "disable_push".to_string(),
+            configured_alias: Some((Owner("Bob".to_string()), "bob".to_string())),
+        }
+    }
+
+    #[test]
+    fn moved_to() {
+        assert_eq!(audit(RepoStatus::Active, "bob/Repo").moved_to(), None);
+        assert_eq!(
+            audit(RepoStatus::Active, "Robert/repo").moved_to(),
+            Some("Robert/repo")
+        );
+    }
+
+    #[test]
+    fn to_json() {
+        let mut remote = audit(RepoStatus::Archived, "Robert/repo");
+        remote.push_disabled = false;
+        remote.configured_alias = Some((Owner("Bob".to_string()), "Herb".to_string()));
+        let json = remote.to_json();
+        assert_eq!(json["remote"], "bob");
+        assert_eq!(json["fork"], "Bob/repo");
+        assert_eq!(json["exists"], true);
+        assert_eq!(json["archived"], true);
+        assert!(json["check_error"].is_null());
+        assert_eq!(json["moved_to"], "Robert/repo");
+        assert_eq!(json["push_disabled"], false);
+        assert_eq!(json["configured_alias"], "Herb");
+        assert_eq!(json["alias_matches"], false);
+
+        remote.state = Some(Err("the forge responded with HTTP 500".to_string()));
+        let json = remote.to_json();
+        assert!(json["exists"].is_null());
+        assert_eq!(json["check_error"], "the forge responded with HTTP 500");
+        assert!(json["moved_to"].is_null());
+    }
+
+    #[test]
+    fn json_shape() {
+        let mut unchecked = audit(RepoStatus::Active, "Bob/repo");
+        unchecked.alias = "local".to_string();
+        unchecked.fork = None;
+        unchecked.managed = false;
+        unchecked.state = None;
+        unchecked.push_disabled = false;
+        unchecked.configured_alias = None;
+        assert_eq!(
+            audits_to_json(&[audit(RepoStatus::Active, "Bob/repo"), unchecked]),
+            json!({
+                "schema_version": 1,
+                "remotes": [
+                    {
+                        "remote": "bob",
+                        "fork": "Bob/repo",
+                        "managed": true,
+                        "exists": true,
+                        "archived": false,
+                        "check_error": null,
+                        "moved_to": null,
+                        "push_disabled": true,
+                        "configured_alias": "bob",
+                        "alias_matches": true,
+                    },
+                    {
+                        "remote": "local",
+                        "fork": null,
+                        "managed": false,
+                        "exists": null,
+                        "archived": null,
+                        "check_error": null,
+                        "moved_to": null,
+                        "push_disabled": false,
+                        "configured_alias": null,
+                        "alias_matches": null,
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn json_schema() {
+        let schema: Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        assert_eq!(
+            schema["properties"]["schema_version"]["const"],
+            JSON_SCHEMA_VERSION
+        );
+        let validator = jsonschema::validator_for(&schema).unwrap();
+
+        let mut failed = audit(RepoStatus::Missing, "Bob/repo");
+        failed.state = Some(Err("the forge responded with HTTP 500".to_string()));
+        let mut unchecked = audit(RepoStatus::Active, "Bob/repo");
+        unchecked.fork = None;
+        unchecked.state = None;
+        unchecked.configured_alias = None;
+        let report = audits_to_json(&[
+            audit(RepoStatus::Active, "Bob/repo"),
+            audit(RepoStatus::Archived, "Robert/repo"),
+            audit(RepoStatus::Missing, "Bob/repo"),
+            failed,
+            unchecked,
+        ]);
+        let errors: Vec<_> = validator
+            .iter_errors(&report)
+            .map(|error| error.to_string())
+            .collect();
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(validator.is_valid(&audits_to_json(&[])));
+
+        // The schema must be strict enough to catch the report's shape changing.
+        let mut renamed = report.clone();
+        let remote = renamed["remotes"][0].as_object_mut().unwrap();
+        let exists = remote.remove("exists").unwrap();
+        let _ = remote.insert("present".to_string(), exists);
+        assert!(!validator.is_valid(&renamed));
+        let mut retyped = report;
+        retyped["remotes"][0]["managed"] = json!("yes");
+        assert!(!validator.is_valid(&retyped));
+    }
+}
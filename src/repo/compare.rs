@@ -0,0 +1,188 @@
+use super::{forge::ForgeKind, Fork, Name, Repo, Url};
+use crate::{error::Error, suggest::DEFAULT_MAIN_FORK_OWNER_ALIAS};
+use colour::{dark_cyan, green_ln, yellow_ln};
+
+/// The minimum width of the column of branch names when `--stable` is passed.
+const STABLE_BRANCH_WIDTH: usize = 40;
+
+impl Repo {
+    /// Lists the branches of the remote `alias` which have no counterpart of the same name in the
+    /// upstream remote, most recently committed to first, along with how many commits each has
+    /// which aren't on any of the upstream remote's branches.  Only the remote-tracking branches
+    /// are compared, so both remotes should have been fetched recently.
+    pub fn compare(&self, alias: &str) -> Result<(), Error> {
+        let aliases = self.get_local_remote_aliases()?;
+        if !aliases.iter().any(|other| other == alias) {
+            return Err(Error::NotFound(format!(
+                "There is no remote named '{alias}'."
+            )));
+        }
+        let Some(upstream) = self.upstream_alias(&aliases) else {
+            return Err(Error::NotFound(format!(
+                "Unable to find the upstream remote.  Expected a remote named '{}' or 'origin'.",
+                self.main_fork_owner_alias()
+            )));
+        };
+        if upstream == alias {
+            return Err(Error::NotFound(format!(
+                "'{alias}' is the upstream remote."
+            )));
+        }
+
+        let branches = self.remote_branches(alias)?;
+        if branches.is_empty() {
+            yellow_ln!(
+                "'{}' has no remote-tracking branches.  Run 'git fetch {}' first.",
+                alias,
+                alias
+            );
+            return Ok(());
+        }
+        let upstream_branches = self.remote_branches(upstream)?;
+        let upstream_arg = format!("--remotes={upstream}");
+        let unique_branches: Vec<(String, u64)> = branches
+            .into_iter()
+            .filter(|branch| !upstream_branches.contains(branch))
+            .map(|branch| {
+                let ref_name = format!("refs/remotes/{alias}/{branch}");
+                let ahead = self
+                    .git_output(&["rev-list", "--count", &ref_name, "--not", &upstream_arg])?
+                    .trim()
+                    .parse()
+                    .unwrap_or_default();
+                Ok((branch, ahead))
+            })
+            .collect::<Result<_, Error>>()?;
+        if unique_branches.is_empty() {
+            green_ln!("Every branch of '{}' is also in '{}'.", alias, upstream);
+            return Ok(());
+        }
+
+        println!("Branches of '{alias}' which aren't in '{upstream}':");
+        let first_column_width = unique_branches
+            .iter()
+            .map(|(branch, _)| branch.len())
+            .max()
+            .unwrap_or_default()
+            + 2;
+        for (branch, ahead) in unique_branches {
+            let commits = if ahead == 1 { "commit" } else { "commits" };
+            if self.options.plain {
+                println!("{branch}: {ahead} {commits} ahead of {upstream}");
+            } else if self.options.stable {
+                let width = STABLE_BRANCH_WIDTH.max(branch.len() + 2);
+                println!("{branch:<width$}{ahead} {commits} ahead");
+            } else {
+                print!("{branch:<first_column_width$}");
+                dark_cyan!("{} {} ahead", ahead, commits);
+                println!();
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints the URL of the forge's page comparing the most recently committed-to branch of the
+    /// new remote `alias` with the main fork/source's default branch, if the forge supports it.
+    pub(super) fn print_compare_url(&self, alias: &str) -> Result<(), Error> {
+        let Some(branch) = self.remote_branches(alias)?.into_iter().next() else {
+            return Ok(());
+        };
+        let upstream_base =
+            (self.main_fork_listing.as_ref()).and_then(|listing| listing.default_branch.as_deref());
+        let fork = &self.available_forks[self.chosen_fork_index];
+        let upstream = (&self.main_fork_url, &self.main_fork_name, upstream_base);
+        if let Some(url) = compare_url(upstream, fork, &branch) {
+            println!("To review '{alias}/{branch}', see {url}\n");
+        }
+        Ok(())
+    }
+
+    /// Returns the alias of the remote for the main fork/source, i.e. the one named as per
+    /// `add-remote.mainForkOwnerAlias` (or "upstream") if it exists, or else "origin".
+    fn upstream_alias<'a>(&self, aliases: &'a [String]) -> Option<&'a str> {
+        [self.main_fork_owner_alias(), "origin"]
+            .iter()
+            .find_map(|candidate| aliases.iter().find(|alias| alias == candidate))
+            .map(String::as_str)
+    }
+
+    /// The alias given to the main fork/source's remote by default.
+    fn main_fork_owner_alias(&self) -> &str {
+        self.config
+            .main_fork_owner_alias
+            .as_deref()
+            .unwrap_or(DEFAULT_MAIN_FORK_OWNER_ALIAS)
+    }
+}
+
+/// Returns the URL of the forge's page comparing `fork`'s `branch` with `upstream`, given as its
+/// URL, name and default branch if known.  GitHub and Gitea compare against the main fork/source,
+/// while GitLab compares against the fork's own default branch since comparing across projects
+/// needs the fork's project ID.  Returns `None` if the page's URL can't be formed.
+fn compare_url(upstream: (&Url, &Name, Option<&str>), fork: &Fork, branch: &str) -> Option<String> {
+    let (upstream_url, upstream_name, upstream_base) = upstream;
+    // GitHub and Gitea need the fork's name as well as its owner if it's been renamed.
+    let head = if fork.name.0.eq_ignore_ascii_case(&upstream_name.0) {
+        format!("{}:{branch}", fork.owner.0)
+    } else {
+        format!("{}:{}:{branch}", fork.owner.0, fork.name.0)
+    };
+    match fork.url.forge {
+        // GitHub compares against the default branch if no base is given.
+        ForgeKind::GitHub => Some(match upstream_base {
+            Some(base) => format!("{}/compare/{base}...{head}", upstream_url.web_url()),
+            None => format!("{}/compare/{head}", upstream_url.web_url()),
+        }),
+        ForgeKind::Gitea => {
+            upstream_base.map(|base| format!("{}/compare/{base}...{head}", upstream_url.web_url()))
+        }
+        ForgeKind::GitLab => (fork.default_branch.as_deref())
+            .or(upstream_base)
+            .map(|base| format!("{}/-/compare/{base}...{branch}", fork.url.web_url())),
+        ForgeKind::Generic => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare_url, Fork, Name, Url};
+
+    #[test]
+    fn compare_urls() {
+        let (upstream, _, name) = Url::new("https://github.com/Fraser999/Add-Remote.git").unwrap();
+        let (url, owner, fork_name) = Url::new("git@github.com:Herb/Add-Remote.git").unwrap();
+        let mut fork = Fork::new(owner, fork_name, url, None);
+        assert_eq!(
+            compare_url((&upstream, &name, Some("main")), &fork, "fix-parser").as_deref(),
+            Some("https://github.com/Fraser999/Add-Remote/compare/main...Herb:fix-parser")
+        );
+        assert_eq!(
+            compare_url((&upstream, &name, None), &fork, "fix-parser").as_deref(),
+            Some("https://github.com/Fraser999/Add-Remote/compare/Herb:fix-parser")
+        );
+        fork.name = Name("add-remote-fork".to_string());
+        assert_eq!(
+            compare_url((&upstream, &name, Some("main")), &fork, "fix").as_deref(),
+            Some("https://github.com/Fraser999/Add-Remote/compare/main...Herb:add-remote-fork:fix")
+        );
+
+        let (upstream, _, name) = Url::new("https://gitlab.com/group/project.git").unwrap();
+        let (url, owner, fork_name) = Url::new("git@gitlab.com:Herb/project.git").unwrap();
+        let mut fork = Fork::new(owner, fork_name, url, None);
+        assert_eq!(compare_url((&upstream, &name, None), &fork, "fix"), None);
+        fork.default_branch = Some("develop".to_string());
+        assert_eq!(
+            compare_url((&upstream, &name, Some("main")), &fork, "fix").as_deref(),
+            Some("https://gitlab.com/Herb/project/-/compare/develop...fix")
+        );
+
+        let (upstream, _, name) = Url::new("https://codeberg.org/forgejo/forgejo.git").unwrap();
+        let (url, owner, fork_name) = Url::new("https://codeberg.org/Herb/forgejo.git").unwrap();
+        let fork = Fork::new(owner, fork_name, url, None);
+        assert_eq!(compare_url((&upstream, &name, None), &fork, "fix"), None);
+        assert_eq!(
+            compare_url((&upstream, &name, Some("forgejo")), &fork, "fix").as_deref(),
+            Some("https://codeberg.org/forgejo/forgejo/compare/forgejo...Herb:fix")
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use super::{forge, Repo, Url};
+use crate::{cli::Options, error::Error, git_url};
+use colour::{green_ln, red_ln, yellow_ln};
+use reqwest::{
+    header::{HeaderMap, AUTHORIZATION},
+    StatusCode,
+};
+use serde_json::Value;
+
+/// A response to a request sent by `add-remote doctor`.
+pub(super) struct Probe {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+impl Probe {
+    /// Returns the body parsed as JSON, or `Value::Null` if it isn't JSON.
+    pub fn json(&self) -> Value {
+        serde_json::from_str(&self.body).unwrap_or_default()
+    }
+}
+
+/// The outcome of one of the checks made by `add-remote doctor` or `add-remote audit`.
+pub(super) enum Outcome {
+    /// The check passed, with a description of what was found.
+    Pass(String),
+    /// The check found something which may be a problem, but doesn't stop `add-remote` working.
+    Warn(String),
+    /// The check failed, with a description of the problem.
+    Fail(String),
+}
+
+/// The report printed by `add-remote doctor` or `add-remote audit`, tallying the failed checks.
+#[derive(Default)]
+pub(super) struct Report {
+    failures: usize,
+}
+
+impl Report {
+    /// Prints the `outcome` of the check of `subject`.
+    pub fn record(&mut self, subject: &str, outcome: Outcome) {
+        match outcome {
+            Outcome::Pass(details) => green_ln!("[pass] {}: {}", subject, details),
+            Outcome::Warn(details) => yellow_ln!("[warn] {}: {}", subject, details),
+            Outcome::Fail(details) => {
+                self.failures += 1;
+                red_ln!("[FAIL] {}: {}", subject, details);
+            }
+        }
+    }
+
+    /// Returns an error if any check failed.
+    pub fn finish(self) -> Result<(), Error> {
+        if self.failures == 0 {
+            println!("All checks passed.");
+            Ok(())
+        } else {
+            Err(Error::ChecksFailed(self.failures))
+        }
+    }
+}
+
+impl Repo {
+    /// Checks that `add-remote` can work in the current directory, printing a pass/fail report:
+    /// Git is found, this is a Git repository, its remotes are hosted on supported forges whose
+    /// APIs are reachable, and any tokens configured for those forges authenticate with the scopes
+    /// needed.  Returns an error if any check failed.
+    pub fn doctor(options: Options) -> Result<(), Error> {
+        let mut report = Report::default();
+        let Some(git) = find_git::git_path() else {
+            report.record("Git", Outcome::Fail("not found".to_string()));
+            return report.finish();
+        };
+        report.record("Git", Outcome::Pass(format!("found at {}", git.display())));
+        let mut repo = Self::new_uninitialised();
+        repo.options = options;
+        repo.read_config();
+
+        let output = repo
+            .git
+            .output(repo.git.command().args(["rev-parse", "--show-toplevel"]));
+        match output {
+            Ok(output) if output.status.success() => report.record(
+                "Repository",
+                Outcome::Pass(String::from_utf8_lossy(&output.stdout).trim().to_string()),
+            ),
+            _ => {
+                report.record(
+                    "Repository",
+                    Outcome::Fail("not inside a Git repository".to_string()),
+                );
+                return report.finish();
+            }
+        }
+
+        let urls = repo.check_remotes(&mut report)?;
+        let mut checked_hosts = Vec::new();
+        for url in urls {
+            let host = url.host();
+            if !checked_hosts.contains(&host) {
+                repo.check_forge(&url, &mut report);
+                checked_hosts.push(host);
+            }
+        }
+        report.finish()
+    }
+
+    /// Checks that each remote is hosted on a supported forge, returning the URLs of those which
+    /// are.
+    fn check_remotes(&self, report: &mut Report) -> Result<Vec<Url>, Error> {
+        let aliases = self.get_local_remote_aliases()?;
+        if aliases.is_empty() {
+            report.record("Remotes", Outcome::Fail("there are none".to_string()));
+            return Ok(Vec::new());
+        }
+        let mut urls = Vec::new();
+        for alias in aliases {
+            let subject = format!("Remote '{alias}'");
+            match Self::get_local_remote(&*self.git, &self.mirrors, &alias) {
+                Some((owner, (name, _, url))) => {
+                    report.record(
+                        &subject,
+                        Outcome::Pass(format!("{}/{} on {}", owner.0, name.0, url.host())),
+                    );
+                    urls.push(url);
+                }
+                None => report.record(
+                    &subject,
+                    Outcome::Warn("not hosted on a supported forge, so it's ignored".to_string()),
+                ),
+            }
+        }
+        if urls.is_empty() {
+            report.record("Remotes", Outcome::Fail(Error::UnsupportedHost.to_string()));
+        }
+        Ok(urls)
+    }
+
+    /// Checks that the API of the forge hosting `url` is reachable, and that the token configured
+    /// for it, if any, authenticates with the scopes needed.
+    fn check_forge(&mut self, url: &Url, report: &mut Report) {
+        let provider = match forge::new_provider(self, url) {
+            Ok(provider) => provider,
+            Err(error) => {
+                report.record(
+                    &format!("Token for {}", url.host()),
+                    Outcome::Fail(error.to_string()),
+                );
+                return;
+            }
+        };
+        let api_url = provider.api_url();
+        let subject = format!("{} API", provider.name());
+        if let Err(error) = self.configure_client(&api_url) {
+            report.record(&subject, Outcome::Fail(error.to_string()));
+            return;
+        }
+        match self.probe(&api_url, None) {
+            Ok(probe) => report.record(
+                &subject,
+                Outcome::Pass(format!("{api_url} is reachable (HTTP {})", probe.status)),
+            ),
+            Err(error) => {
+                report.record(&subject, Outcome::Fail(error));
+                return;
+            }
+        }
+        let subject = format!("{} token", provider.name());
+        match provider.check_token(self) {
+            Some(Ok(details)) => report.record(&subject, Outcome::Pass(details)),
+            Some(Err(problem)) => report.record(&subject, Outcome::Fail(problem)),
+            None => report.record(
+                &subject,
+                Outcome::Warn(
+                    "none is configured, so only public repositories can be queried, and at a \
+                     lower rate limit"
+                        .to_string(),
+                ),
+            ),
+        }
+    }
+
+    /// Sends a GET for `url`, returning the response whatever its status, or a description of the
+    /// failure if none was received.
+    pub(super) fn probe(&self, url: &str, authorisation: Option<&String>) -> Result<Probe, String> {
+        self.runtime.block_on(async {
+            let mut request_builder = self.client.get(url);
+            if let Some(auth) = authorisation {
+                request_builder = request_builder.header(AUTHORIZATION, auth);
+            }
+            let failed =
+                |error: reqwest::Error| git_url::redact(&format!("failed to reach {url}: {error}"));
+            let response = request_builder.send().await.map_err(failed)?;
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = response.text().await.map_err(failed)?;
+            Ok(Probe {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+}
@@ -0,0 +1,305 @@
+/// Lists forks on forges described by URL templates in Git config.
+mod generic;
+/// Lists forks on Gitea and Forgejo instances.
+mod gitea;
+/// Lists forks on GitHub.
+mod github;
+/// Lists forks on GitLab.
+mod gitlab;
+/// Lists forks via the forges' GraphQL APIs.
+mod graphql;
+
+use super::{doctor::Probe, Fork, Name, Owner, Repo, Url};
+use crate::error::Error;
+use colour::yellow_ln;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::{collections::HashMap, fmt::Debug, sync::OnceLock};
+
+/// The hosts of well-known public Gitea/Forgejo instances, which are recognised without being
+/// listed under `add-remote.giteaHost`.
+const GITEA_HOSTS: [&str; 2] = ["codeberg.org", "gitea.com"];
+
+/// The hosts of further forges set in Git config, i.e. Gitea/Forgejo instances set by
+/// `add-remote.giteaHost` and generic forges set by `add-remote.<host>.forksUrl`.  These are read
+/// along with the rest of the config, but are needed whenever a URL is parsed.
+static CONFIGURED_HOSTS: OnceLock<HashMap<String, ForgeKind>> = OnceLock::new();
+
+/// The kind of forge hosting a repository, which determines how its forks are listed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(super) enum ForgeKind {
+    GitLab,
+    GitHub,
+    Gitea,
+    Generic,
+}
+
+impl ForgeKind {
+    /// Returns the kind of forge at `host`, or `None` if it's not a known forge.  `is_https` is
+    /// needed since GitHub also serves SSH from `ssh.github.com`.
+    pub(super) fn of_host(host: &str, is_https: bool) -> Option<Self> {
+        match (host, is_https) {
+            ("gitlab.com", _) => Some(ForgeKind::GitLab),
+            ("github.com", _) | ("ssh.github.com", false) => Some(ForgeKind::GitHub),
+            (host, _) if GITEA_HOSTS.contains(&host) => Some(ForgeKind::Gitea),
+            (host, _) => CONFIGURED_HOSTS.get()?.get(host).copied(),
+        }
+    }
+}
+
+/// Records the forges set in Git config so that URLs hosted on them are recognised.  Only the
+/// first call has any effect.
+pub(super) fn set_configured_hosts(gitea_hosts: &[String], generic_hosts: &[String]) {
+    let hosts = gitea_hosts
+        .iter()
+        .map(|host| (host.to_lowercase(), ForgeKind::Gitea))
+        .chain(
+            generic_hosts
+                .iter()
+                .map(|host| (host.to_lowercase(), ForgeKind::Generic)),
+        )
+        .collect();
+    let _ = CONFIGURED_HOSTS.set(hosts);
+}
+
+/// The main fork/source of a repository.
+pub(super) struct Source {
+    pub owner: Owner,
+    pub name: Name,
+    pub url: Url,
+    /// The source as listed among the forks, including its stars and activity, if the forge
+    /// described it.
+    pub listing: Option<Fork>,
+    /// The API request for listing the members of the organisation/group which owns the source, if
+    /// it is owned by one.
+    pub org_members_request: Option<String>,
+}
+
+/// Whether a repository which a remote fetches from is still usable.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(super) enum RepoStatus {
+    /// The repository exists and isn't archived.
+    Active,
+    /// The repository exists but has been archived, so is read-only.
+    Archived,
+    /// The forge doesn't know of the repository: it has been deleted, or is private.
+    Missing,
+}
+
+/// What the forge reports about a repository which a remote fetches from.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(super) struct RepoState {
+    pub status: RepoStatus,
+    /// The repository's current `<owner>/<name>`, if the forge reports it.  This differs from the
+    /// one requested if the repository has been renamed or transferred, since the forges redirect
+    /// requests made under the old name.
+    pub full_name: Option<String>,
+}
+
+/// The API of a forge hosting repositories and their forks.  The requests are sent using `repo`,
+/// which holds the HTTP client and the details of the main fork/source once retrieved.
+pub(super) trait ForgeProvider: Debug + Send + Sync {
+    /// The name of the forge, for use in messages.
+    fn name(&self) -> &str;
+
+    /// The kind of forge.
+    fn kind(&self) -> ForgeKind;
+
+    /// The base URL of the forge's API, used to find any of Git's `http.<url>.*` settings which
+    /// apply to it.
+    fn api_url(&self) -> String;
+
+    /// The value of the `Authorization` header to send with each request, if any.
+    fn auth_header(&self) -> Option<&String>;
+
+    /// Retrieves the main fork/source of the repository `owner/name`, i.e. the repository itself
+    /// if it's not a fork, or else the repository it was forked from.
+    fn get_source(&self, repo: &Repo, owner: &Owner, name: &Name) -> Result<Source, Error>;
+
+    /// Retrieves the forks of the repository `owner/name`.
+    fn list_forks(&self, repo: &Repo, owner: &Owner, name: &Name) -> Result<Vec<Fork>, Error>;
+
+    /// Retrieves the forks from which pull/merge requests are open against the repository
+    /// `owner/name`.
+    fn list_pull_request_forks(
+        &self,
+        _repo: &Repo,
+        _owner: &Owner,
+        _name: &Name,
+    ) -> Result<Vec<Fork>, Error> {
+        yellow_ln!(
+            "Listing the forks with open pull requests isn't supported for {}.",
+            self.name()
+        );
+        Ok(Vec::new())
+    }
+
+    /// Returns the username of `member`, an element of a response to `org_members_request`.
+    fn member_login<'a>(&self, member: &'a Value) -> Option<&'a str> {
+        member["login"].as_str()
+    }
+
+    /// Queries the forge for the repository `owner/name` and returns its current owner, which
+    /// differs from `owner` if the owner has been renamed.  Returns `None` if this can't be found.
+    fn current_owner(&self, _repo: &Repo, _owner: &Owner, _name: &Name) -> Option<Owner> {
+        None
+    }
+
+    /// Queries the forge for whether the repository `owner/name` still exists, whether it's
+    /// archived and what it's currently called, returning a description of the problem if this
+    /// can't be found.
+    fn repo_state(&self, _repo: &Repo, _owner: &Owner, _name: &Name) -> Result<RepoState, String> {
+        Err(format!(
+            "checking repositories isn't supported for {}",
+            self.name()
+        ))
+    }
+
+    /// Checks that the configured token authenticates and has the scopes needed, returning a
+    /// description of what was found, or of the problem.  Returns `None` if no token is configured.
+    fn check_token(&self, _repo: &Repo) -> Option<Result<String, String>> {
+        self.auth_header().map(|_| {
+            Ok(format!(
+                "configured, but checking tokens isn't supported for {}",
+                self.name()
+            ))
+        })
+    }
+}
+
+/// Returns a description of the rejection of a token by `forge`, which responded to the request
+/// checking it with `probe`.
+fn token_rejected(forge: &str, probe: &Probe) -> String {
+    format!(
+        "{forge} rejected it (HTTP {}): {}",
+        probe.status,
+        probe.json()["message"]
+            .as_str()
+            .unwrap_or_else(|| probe.body.trim())
+    )
+}
+
+/// Returns the state of a repository given the `probe` of the API request for its details, in
+/// which `archived_pointer` points to whether it's archived and `full_name_pointer` to its
+/// `<owner>/<name>`, if the forge reports these.
+fn repo_state_from(
+    probe: &Probe,
+    archived_pointer: Option<&str>,
+    full_name_pointer: Option<&str>,
+) -> Result<RepoState, String> {
+    match probe.status {
+        StatusCode::NOT_FOUND | StatusCode::GONE => Ok(RepoState {
+            status: RepoStatus::Missing,
+            full_name: None,
+        }),
+        status if status.is_success() => {
+            let json = probe.json();
+            let is_archived = archived_pointer
+                .and_then(|pointer| json.pointer(pointer)?.as_bool())
+                .unwrap_or_default();
+            Ok(RepoState {
+                status: if is_archived {
+                    RepoStatus::Archived
+                } else {
+                    RepoStatus::Active
+                },
+                full_name: full_name_pointer
+                    .and_then(|pointer| json.pointer(pointer)?.as_str())
+                    .map(ToString::to_string),
+            })
+        }
+        status => Err(format!("the forge responded with HTTP {status}")),
+    }
+}
+
+/// Returns the provider for the forge hosting `url`.
+pub(super) fn new_provider(repo: &Repo, url: &Url) -> Result<Box<dyn ForgeProvider>, Error> {
+    Ok(match url.forge {
+        ForgeKind::GitLab => Box::new(gitlab::GitLab::new(repo)?),
+        ForgeKind::GitHub => Box::new(github::GitHub::new(repo)),
+        ForgeKind::Gitea => Box::new(gitea::Gitea::new(repo, url)),
+        ForgeKind::Generic => Box::new(generic::Generic::new(repo, url)),
+    })
+}
+
+/// Returns the string at `pointer` within `value`, or a description of why it's missing.
+fn string_at<'a>(value: &'a Value, pointer: &str) -> Result<&'a str, String> {
+    value
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("expected a string at '{pointer}' in {value}"))
+}
+
+/// Parses a repository as returned by the GitHub or Gitea API, both of which describe the owner,
+/// SSH URL and default branch in the same way, but which report activity and stars in different
+/// fields.  Returns a description of the problem if any of the required fields are missing.
+fn parse_repo(
+    value: &Value,
+    kind: ForgeKind,
+    activity_field: &str,
+    stars_field: &str,
+) -> Result<Fork, String> {
+    let owner = Owner(string_at(value, "/owner/login")?.to_string());
+    let name = Name(string_at(value, "/name")?.to_string());
+    let url = Url::of_forge(kind, string_at(value, "/ssh_url")?);
+    let last_activity = value[activity_field].as_str();
+    Ok(Fork {
+        avatar_url: value["owner"]["avatar_url"]
+            .as_str()
+            .map(ToString::to_string),
+        stars: value[stars_field].as_u64(),
+        default_branch: value["default_branch"].as_str().map(ToString::to_string),
+        subfork_count: value["forks_count"].as_u64().unwrap_or_default(),
+        ..Fork::new(owner, name, url, last_activity)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{repo_state_from, Probe, Repo, RepoStatus};
+    use reqwest::{header::HeaderMap, StatusCode};
+    use wiremock::{Mock, MockServer};
+
+    /// Starts a mock of a forge's API on `repo`'s runtime, so that it serves `repo`'s requests.
+    pub(super) fn mock_api(repo: &Repo) -> MockServer {
+        repo.runtime.block_on(MockServer::start())
+    }
+
+    /// Mounts `mock` on `server`.
+    pub(super) fn mount(repo: &Repo, server: &MockServer, mock: Mock) {
+        repo.runtime.block_on(mock.mount(server));
+    }
+
+    #[test]
+    fn repo_statuses() {
+        let probe = |status: StatusCode, body: &str| Probe {
+            status,
+            headers: HeaderMap::new(),
+            body: body.to_string(),
+        };
+        let status = |probe: &Probe, archived_pointer| {
+            repo_state_from(probe, archived_pointer, None).map(|state| state.status)
+        };
+        let active = probe(
+            StatusCode::OK,
+            r#"{"archived": false, "full_name": "Bob/repo"}"#,
+        );
+        assert_eq!(status(&active, Some("/archived")), Ok(RepoStatus::Active));
+        let archived = probe(StatusCode::OK, r#"{"archived": true}"#);
+        assert_eq!(
+            status(&archived, Some("/archived")),
+            Ok(RepoStatus::Archived)
+        );
+        assert_eq!(status(&archived, None), Ok(RepoStatus::Active));
+        let full_name = |probe: &Probe| {
+            repo_state_from(probe, Some("/archived"), Some("/full_name"))
+                .map(|state| state.full_name)
+        };
+        assert_eq!(full_name(&active), Ok(Some("Bob/repo".to_string())));
+        assert_eq!(full_name(&archived), Ok(None));
+        let missing = probe(StatusCode::NOT_FOUND, r#"{"message": "Not Found"}"#);
+        assert_eq!(status(&missing, Some("/archived")), Ok(RepoStatus::Missing));
+        let forbidden = probe(StatusCode::FORBIDDEN, "");
+        assert!(status(&forbidden, Some("/archived")).is_err());
+    }
+}
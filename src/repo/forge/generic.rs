@@ -0,0 +1,257 @@
+use super::{repo_state_from, ForgeKind, ForgeProvider, RepoState, Source};
+use crate::{
+    error::Error,
+    repo::{Fork, Name, Owner, Repo, Url},
+};
+use colour::yellow_ln;
+use serde_json::Value;
+
+/// A forge described by URL templates and JSON pointers set under `add-remote.<host>` in Git
+/// config, e.g. a Gogs instance.  Only `forksUrl` is required.  In the templates, `{owner}` and
+/// `{repo}` are replaced by the owner and name of the repository.
+#[derive(Debug)]
+pub(super) struct Generic {
+    /// The host of the forge, used in messages.
+    host: String,
+    /// The template of the API request listing a repository's forks as a JSON array.
+    forks_url: String,
+    /// The template of the API request for a repository's details, used to find its parent.  If
+    /// unset, every repository is treated as the main fork/source.
+    repo_url: Option<String>,
+    /// The template of the API request listing a repository's open pull requests as a JSON array.
+    pulls_url: Option<String>,
+    /// The JSON pointer to a repository's parent within its details.
+    parent_pointer: String,
+    /// The JSON pointer to the repository from which a pull request was opened.
+    head_repo_pointer: String,
+    /// The JSON pointer to a repository's owner.
+    owner_pointer: String,
+    /// The JSON pointer to a repository's name.
+    name_pointer: String,
+    /// The JSON pointer to a repository's clone URL.
+    url_pointer: String,
+    /// The JSON pointer to when a repository was last active, if the forge reports it.
+    activity_pointer: Option<String>,
+    /// The JSON pointer to a repository's number of stars, if the forge reports it.
+    stars_pointer: Option<String>,
+    /// The JSON pointer to a repository's default branch, if the forge reports it.
+    default_branch_pointer: Option<String>,
+    /// The value of the `Authorization` header derived from the token set as
+    /// `add-remote.<host>.token`, using the scheme set as `add-remote.<host>.authScheme`.
+    authorisation: Option<String>,
+}
+
+impl Generic {
+    pub(super) fn new(repo: &Repo, url: &Url) -> Self {
+        let host = url.host();
+        let config = |name: &str| repo.get_from_gitconfig(&format!("add-remote.{host}.{name}"));
+        let scheme = config("authScheme").unwrap_or_else(|| "Bearer".to_string());
+        Generic {
+            forks_url: config("forksUrl").unwrap_or_default(),
+            repo_url: config("repoUrl"),
+            pulls_url: config("pullsUrl"),
+            parent_pointer: config("parentPointer").unwrap_or_else(|| "/parent".to_string()),
+            head_repo_pointer: config("headRepoPointer")
+                .unwrap_or_else(|| "/head/repo".to_string()),
+            owner_pointer: config("ownerPointer").unwrap_or_else(|| "/owner/login".to_string()),
+            name_pointer: config("namePointer").unwrap_or_else(|| "/name".to_string()),
+            url_pointer: config("urlPointer").unwrap_or_else(|| "/ssh_url".to_string()),
+            activity_pointer: config("activityPointer"),
+            stars_pointer: config("starsPointer"),
+            default_branch_pointer: config("defaultBranchPointer"),
+            authorisation: repo
+                .read_token(&format!("{host}.token"), &host, &[])
+                .map(|token| format!("{scheme} {token}")),
+            host,
+        }
+    }
+
+    /// Returns `template` with the placeholders replaced by `owner` and `name`.
+    fn expand(template: &str, owner: &Owner, name: &Name) -> String {
+        template
+            .replace("{owner}", &owner.0)
+            .replace("{repo}", &name.0)
+    }
+
+    /// Parses a repository using the configured JSON pointers, returning `None` if any of the
+    /// required fields are missing.
+    fn parse_repo(&self, value: &Value) -> Option<Fork> {
+        let string = |pointer: &str| value.pointer(pointer).and_then(Value::as_str);
+        let owner = Owner(string(&self.owner_pointer)?.to_string());
+        let name = Name(string(&self.name_pointer)?.to_string());
+        let url = Url::of_forge(ForgeKind::Generic, string(&self.url_pointer)?);
+        let last_activity = self.activity_pointer.as_deref().and_then(string);
+        Some(Fork {
+            stars: self
+                .stars_pointer
+                .as_deref()
+                .and_then(|pointer| value.pointer(pointer))
+                .and_then(Value::as_u64),
+            default_branch: self
+                .default_branch_pointer
+                .as_deref()
+                .and_then(string)
+                .map(ToString::to_string),
+            ..Fork::new(owner, name, url, last_activity)
+        })
+    }
+}
+
+impl ForgeProvider for Generic {
+    fn name(&self) -> &str {
+        &self.host
+    }
+
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::Generic
+    }
+
+    fn api_url(&self) -> String {
+        self.forks_url.clone()
+    }
+
+    fn auth_header(&self) -> Option<&String> {
+        self.authorisation.as_ref()
+    }
+
+    /// Follows the chain of parents if `repoUrl` is set.
+    fn get_source(&self, repo: &Repo, owner: &Owner, name: &Name) -> Result<Source, Error> {
+        let (mut owner, mut name) = (owner.clone(), name.clone());
+        let mut listing = None;
+        if let Some(repo_url) = &self.repo_url {
+            loop {
+                let request = Self::expand(repo_url, &owner, &name);
+                let response_as_json = repo.get_json(&request, self.auth_header())?;
+                let parent = response_as_json
+                    .pointer(&self.parent_pointer)
+                    .filter(|parent| parent.is_object())
+                    .and_then(|parent| self.parse_repo(parent));
+                let Some(parent) = parent else {
+                    listing = self.parse_repo(&response_as_json);
+                    break;
+                };
+                (owner, name) = (parent.owner, parent.name);
+            }
+        }
+        let url = listing.as_ref().map_or_else(
+            || {
+                Url::of_forge(
+                    ForgeKind::Generic,
+                    &format!("git@{}:{}/{}.git", self.host, owner.0, name.0),
+                )
+            },
+            |fork| fork.url.clone(),
+        );
+        Ok(Source {
+            owner,
+            name,
+            url,
+            listing,
+            org_members_request: None,
+        })
+    }
+
+    fn list_forks(&self, repo: &Repo, owner: &Owner, name: &Name) -> Result<Vec<Fork>, Error> {
+        let request = Self::expand(&self.forks_url, owner, name);
+        Ok(repo
+            .get_fork_list_values(&request, self.auth_header(), owner, name)?
+            .iter()
+            .filter_map(|value| self.parse_repo(value))
+            .collect())
+    }
+
+    fn list_pull_request_forks(
+        &self,
+        repo: &Repo,
+        owner: &Owner,
+        name: &Name,
+    ) -> Result<Vec<Fork>, Error> {
+        let Some(pulls_url) = &self.pulls_url else {
+            repo.progress.suspend(|| {
+                yellow_ln!(
+                    "Listing the forks with open pull requests requires add-remote.{}.pullsUrl to \
+                     be set.",
+                    self.host
+                );
+            });
+            return Ok(Vec::new());
+        };
+        let request = Self::expand(pulls_url, owner, name);
+        Ok(repo
+            .get_all_array_values(&request, self.auth_header())?
+            .iter()
+            .filter_map(|value| value.pointer(&self.head_repo_pointer))
+            .filter_map(|head_repo| self.parse_repo(head_repo))
+            .collect())
+    }
+
+    /// Whether a repository is archived isn't configurable, so only whether it exists is checked,
+    /// and only if `repoUrl` is configured.
+    fn repo_state(&self, repo: &Repo, owner: &Owner, name: &Name) -> Result<RepoState, String> {
+        let Some(repo_url) = &self.repo_url else {
+            return Err(format!(
+                "checking repositories needs add-remote.{}.repoUrl to be set",
+                self.host
+            ));
+        };
+        let request = Self::expand(repo_url, owner, name);
+        repo_state_from(&repo.probe(&request, self.auth_header())?, None, None)
+    }
+
+    fn current_owner(&self, repo: &Repo, owner: &Owner, name: &Name) -> Option<Owner> {
+        let request = Self::expand(self.repo_url.as_ref()?, owner, name);
+        let page = repo.try_send_get(&request, self.auth_header()).ok()?;
+        let value: Value = serde_json::from_str(&page.content).ok()?;
+        Some(self.parse_repo(&value)?.owner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Generic, Name, Owner};
+    use serde_json::json;
+
+    #[test]
+    fn parse_repo() {
+        let generic = Generic {
+            host: "git.example.com".to_string(),
+            forks_url: "https://git.example.com/api/{owner}/{repo}/forks".to_string(),
+            repo_url: None,
+            pulls_url: None,
+            parent_pointer: "/parent".to_string(),
+            head_repo_pointer: "/head/repo".to_string(),
+            owner_pointer: "/namespace/path".to_string(),
+            name_pointer: "/name".to_string(),
+            url_pointer: "/clone_url".to_string(),
+            activity_pointer: Some("/updated".to_string()),
+            stars_pointer: Some("/stars".to_string()),
+            default_branch_pointer: None,
+            authorisation: None,
+        };
+        let fork = generic
+            .parse_repo(&json!({
+                "name": "cargo",
+                "namespace": {"path": "Fraser999"},
+                "clone_url": "https://git.example.com/Fraser999/cargo.git",
+                "updated": "2024-05-01T00:00:00Z",
+                "stars": 3
+            }))
+            .unwrap();
+        assert_eq!(fork.owner.0, "Fraser999");
+        assert_eq!(fork.name.0, "cargo");
+        assert!(fork.url.is_https());
+        assert_eq!(fork.last_activity.as_deref(), Some("2024-05-01T00:00:00Z"));
+        assert_eq!(fork.stars, Some(3));
+        assert!(fork.default_branch.is_none());
+        assert!(generic.parse_repo(&json!({"name": "cargo"})).is_none());
+
+        assert_eq!(
+            Generic::expand(
+                &generic.forks_url,
+                &Owner("Fraser999".to_string()),
+                &Name("cargo".to_string())
+            ),
+            "https://git.example.com/api/Fraser999/cargo/forks"
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use super::{
+    parse_repo, repo_state_from, token_rejected, ForgeKind, ForgeProvider, RepoState, Source,
+};
+use crate::{
+    error::Error,
+    repo::{Fork, Name, Owner, Repo, Url},
+};
+use serde_json::Value;
+
+/// The environment variables checked for a token if none is set in Git config, in order.
+const TOKEN_ENV_VARS: [&str; 2] = ["ADD_REMOTE_GITEA_TOKEN", "GITEA_TOKEN"];
+
+/// A Gitea or Forgejo instance, such as Codeberg, whose API is accessed using the optional token
+/// set as `add-remote.giteaToken` or in one of `TOKEN_ENV_VARS`.
+#[derive(Debug)]
+pub(super) struct Gitea {
+    /// The base URL for sending GET requests for retrieving info about repositories.
+    api: String,
+    /// The value of the `Authorization` header derived from the token.
+    authorisation: Option<String>,
+}
+
+impl Gitea {
+    pub(super) fn new(repo: &Repo, url: &Url) -> Self {
+        Gitea {
+            api: format!("{}/api/v1/repos/", url.web_host()),
+            authorisation: repo
+                .read_token("giteaToken", &url.host(), &TOKEN_ENV_VARS)
+                .map(|token| format!("token {token}")),
+        }
+    }
+}
+
+impl ForgeProvider for Gitea {
+    fn name(&self) -> &'static str {
+        "Gitea"
+    }
+
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::Gitea
+    }
+
+    fn api_url(&self) -> String {
+        self.api.clone()
+    }
+
+    fn auth_header(&self) -> Option<&String> {
+        self.authorisation.as_ref()
+    }
+
+    /// Gitea only reports whether an owner is an organisation to members of it, so forks aren't
+    /// ordered by membership.
+    fn get_source(&self, repo: &Repo, owner: &Owner, name: &Name) -> Result<Source, Error> {
+        let request = format!("{}{}/{}", self.api, owner.0, name.0);
+        let response_as_json = repo.get_json(&request, self.auth_header())?;
+        let source = match &response_as_json["parent"] {
+            Value::Null => &response_as_json,
+            parent => parent,
+        };
+        let fork = parse_repo(source, ForgeKind::Gitea, "updated_at", "stars_count")
+            .map_err(|reason| Error::unexpected_response(&request, reason))?;
+        Ok(Source {
+            owner: fork.owner.clone(),
+            name: fork.name.clone(),
+            url: fork.url.clone(),
+            listing: Some(fork),
+            org_members_request: None,
+        })
+    }
+
+    fn list_forks(&self, repo: &Repo, owner: &Owner, name: &Name) -> Result<Vec<Fork>, Error> {
+        let request = format!("{}{}/{}/forks?limit=50", self.api, owner.0, name.0);
+        repo.get_fork_list_values(&request, self.auth_header(), owner, name)?
+            .iter()
+            .map(|value| parse_repo(value, ForgeKind::Gitea, "updated_at", "stars_count"))
+            .collect::<Result<_, _>>()
+            .map_err(|reason| Error::unexpected_response(&request, reason))
+    }
+
+    fn list_pull_request_forks(
+        &self,
+        repo: &Repo,
+        owner: &Owner,
+        name: &Name,
+    ) -> Result<Vec<Fork>, Error> {
+        let request = format!(
+            "{}{}/{}/pulls?state=open&limit=50",
+            self.api, owner.0, name.0
+        );
+        repo.get_all_array_values(&request, self.auth_header())?
+            .iter()
+            .map(|value| &value["head"]["repo"])
+            .filter(|head_repo| head_repo.is_object())
+            .map(|head_repo| parse_repo(head_repo, ForgeKind::Gitea, "updated_at", "stars_count"))
+            .collect::<Result<_, _>>()
+            .map_err(|reason| Error::unexpected_response(&request, reason))
+    }
+
+    /// Gitea redirects requests for a repository under its owner's old name.
+    fn current_owner(&self, repo: &Repo, owner: &Owner, name: &Name) -> Option<Owner> {
+        let request = format!("{}{}/{}", self.api, owner.0, name.0);
+        let page = repo.try_send_get(&request, self.auth_header()).ok()?;
+        let value: Value = serde_json::from_str(&page.content).ok()?;
+        Some(Owner(value["owner"]["login"].as_str()?.to_string()))
+    }
+
+    fn repo_state(&self, repo: &Repo, owner: &Owner, name: &Name) -> Result<RepoState, String> {
+        let request = format!("{}{}/{}", self.api, owner.0, name.0);
+        repo_state_from(
+            &repo.probe(&request, self.auth_header())?,
+            Some("/archived"),
+            Some("/full_name"),
+        )
+    }
+
+    /// Gitea doesn't report a token's scopes, so only its validity is checked.
+    fn check_token(&self, repo: &Repo) -> Option<Result<String, String>> {
+        let authorisation = self.auth_header()?;
+        let request = format!("{}user", self.api.trim_end_matches("repos/"));
+        Some(
+            repo.probe(&request, Some(authorisation))
+                .and_then(|probe| {
+                    if probe.status.is_success() {
+                        Ok(probe.json()["login"].as_str().unwrap_or("?").to_string())
+                    } else {
+                        Err(token_rejected("Gitea", &probe))
+                    }
+                })
+                .map(|user| format!("authenticates as {user}")),
+        )
+    }
+}
@@ -0,0 +1,538 @@
+use super::{
+    graphql::{ForkFields, ForkQuery},
+    parse_repo, repo_state_from, token_rejected, ForgeKind, ForgeProvider, RepoState, Source,
+};
+use crate::{
+    cli::ForkApi,
+    error::Error,
+    repo::{Fork, Name, Owner, Repo},
+};
+use base64::Engine as _;
+use colour::yellow_ln;
+use serde_json::{json, Value};
+
+/// The base URL of GitHub's API.
+const GITHUB_API: &str = "https://api.github.com";
+/// The GraphQL query listing up to 100 forks of a repository at a time.
+const FORKS_QUERY: &str = "query($owner: String!, $name: String!, $cursor: String) {
+  repository(owner: $owner, name: $name) {
+    forks(first: 100, after: $cursor) {
+      pageInfo { hasNextPage endCursor }
+      nodes {
+        name
+        sshUrl
+        pushedAt
+        stargazerCount
+        forkCount
+        owner { login avatarUrl }
+        defaultBranchRef { name }
+      }
+    }
+  }
+}";
+/// Where the details of each fork are found in the nodes returned by `FORKS_QUERY`.
+const FORK_FIELDS: ForkFields = ForkFields {
+    owner: "/owner/login",
+    name: "/name",
+    ssh_url: "/sshUrl",
+    last_activity: "/pushedAt",
+    stars: "/stargazerCount",
+    default_branch: "/defaultBranchRef/name",
+    avatar_url: Some("/owner/avatarUrl"),
+    subfork_count: Some("/forkCount"),
+};
+/// The environment variables checked for a token if none is set in Git config, in order.
+const TOKEN_ENV_VARS: [&str; 3] = ["ADD_REMOTE_GITHUB_TOKEN", "GITHUB_TOKEN", "GH_TOKEN"];
+
+/// GitHub, whose API is accessed using the optional token set as `add-remote.gitHubToken` or in one
+/// of `TOKEN_ENV_VARS`.
+#[derive(Debug)]
+pub(super) struct GitHub {
+    /// The base URL of the API, which is `GITHUB_API` other than in tests.
+    api: String,
+    /// The value of the `Authorization` header derived from the token.
+    authorisation: Option<String>,
+}
+
+impl GitHub {
+    pub(super) fn new(repo: &Repo) -> Self {
+        let authorisation = repo
+            .read_token("gitHubToken", "github.com", &TOKEN_ENV_VARS)
+            .and_then(|token| match authorisation(&token) {
+                Ok(authorisation) => Some(authorisation),
+                Err(error) => {
+                    yellow_ln!("Ignoring the configured GitHub token: {}", error);
+                    None
+                }
+            });
+        GitHub {
+            api: GITHUB_API.to_string(),
+            authorisation,
+        }
+    }
+
+    /// Returns the API request for the repository `owner/name`, or for `suffix` under it.
+    fn repo_request(&self, owner: &Owner, name: &Name, suffix: &str) -> String {
+        format!("{}/repos/{}/{}{}", self.api, owner.0, name.0, suffix)
+    }
+}
+
+impl ForgeProvider for GitHub {
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::GitHub
+    }
+
+    fn api_url(&self) -> String {
+        format!("{}/repos/", self.api)
+    }
+
+    fn auth_header(&self) -> Option<&String> {
+        self.authorisation.as_ref()
+    }
+
+    fn get_source(&self, repo: &Repo, owner: &Owner, name: &Name) -> Result<Source, Error> {
+        let request = self.repo_request(owner, name, "");
+        let response_as_json = repo.get_json(&request, self.auth_header())?;
+        let source = match &response_as_json["source"] {
+            Value::Null => &response_as_json,
+            source => source,
+        };
+        let fork = parse_repo(source, ForgeKind::GitHub, "pushed_at", "stargazers_count")
+            .map_err(|reason| Error::unexpected_response(&request, reason))?;
+        let org_members_request = (source["owner"]["type"] == "Organization")
+            .then(|| format!("{}/orgs/{}/members?per_page=100", self.api, fork.owner.0));
+        Ok(Source {
+            owner: fork.owner.clone(),
+            name: fork.name.clone(),
+            url: fork.url.clone(),
+            listing: Some(fork),
+            org_members_request,
+        })
+    }
+
+    /// If GraphQL was chosen, and a token is set (as GitHub's GraphQL API requires one), the forks
+    /// are listed via GraphQL, 100 at a time.  Otherwise they're listed via REST.
+    fn list_forks(&self, repo: &Repo, owner: &Owner, name: &Name) -> Result<Vec<Fork>, Error> {
+        if repo.fork_api == ForkApi::GraphQl && !repo.offline {
+            if let Some(authorisation) = self.auth_header() {
+                let endpoint = format!("{}/graphql", self.api);
+                let query = ForkQuery {
+                    kind: ForgeKind::GitHub,
+                    endpoint: &endpoint,
+                    query: FORKS_QUERY,
+                    variables: json!({ "owner": owner.0, "name": name.0 }),
+                    connection: "/repository/forks",
+                    repository: format!("{}/{}", owner.0, name.0),
+                    fields: &FORK_FIELDS,
+                };
+                return repo.list_forks_via_graphql(&query, Some(authorisation));
+            }
+            repo.progress.suspend(|| {
+                yellow_ln!("GitHub's GraphQL API needs a token, so the forks are listed via REST.");
+            });
+        }
+        let request = self.repo_request(owner, name, "/forks?per_page=100");
+        repo.get_fork_list_values(&request, self.auth_header(), owner, name)?
+            .iter()
+            .map(|value| parse_repo(value, ForgeKind::GitHub, "pushed_at", "stargazers_count"))
+            .collect::<Result<_, _>>()
+            .map_err(|reason| Error::unexpected_response(&request, reason))
+    }
+
+    fn list_pull_request_forks(
+        &self,
+        repo: &Repo,
+        owner: &Owner,
+        name: &Name,
+    ) -> Result<Vec<Fork>, Error> {
+        let request = self.repo_request(owner, name, "/pulls?state=open&per_page=100");
+        repo.get_all_array_values(&request, self.auth_header())?
+            .iter()
+            .map(|value| &value["head"]["repo"])
+            .filter(|head_repo| head_repo.is_object())
+            .map(|head_repo| {
+                parse_repo(
+                    head_repo,
+                    ForgeKind::GitHub,
+                    "pushed_at",
+                    "stargazers_count",
+                )
+            })
+            .collect::<Result<_, _>>()
+            .map_err(|reason| Error::unexpected_response(&request, reason))
+    }
+
+    /// GitHub redirects requests for a repository under its owner's old name.
+    fn current_owner(&self, repo: &Repo, owner: &Owner, name: &Name) -> Option<Owner> {
+        let request = self.repo_request(owner, name, "");
+        let page = repo.try_send_get(&request, self.auth_header()).ok()?;
+        let value: Value = serde_json::from_str(&page.content).ok()?;
+        Some(Owner(value["owner"]["login"].as_str()?.to_string()))
+    }
+
+    fn repo_state(&self, repo: &Repo, owner: &Owner, name: &Name) -> Result<RepoState, String> {
+        let request = self.repo_request(owner, name, "");
+        repo_state_from(
+            &repo.probe(&request, self.auth_header())?,
+            Some("/archived"),
+            Some("/full_name"),
+        )
+    }
+
+    /// Classic tokens list their scopes in the `X-OAuth-Scopes` header, which must include "repo"
+    /// for private repositories to be listed.  Other tokens' permissions can't be checked.
+    fn check_token(&self, repo: &Repo) -> Option<Result<String, String>> {
+        let user_request = format!("{}/user", self.api);
+        let probe = match repo.probe(&user_request, Some(self.auth_header()?)) {
+            Ok(probe) => probe,
+            Err(error) => return Some(Err(error)),
+        };
+        if !probe.status.is_success() {
+            return Some(Err(token_rejected("GitHub", &probe)));
+        }
+        let user = probe.json()["login"].as_str().unwrap_or("?").to_string();
+        let Some(scopes) = probe.headers.get("x-oauth-scopes") else {
+            return Some(Ok(format!(
+                "authenticates as {user}, though its permissions can't be checked since it's not \
+                 a classic token"
+            )));
+        };
+        let scopes = scopes.to_str().unwrap_or_default();
+        Some(if scopes.split(',').any(|scope| scope.trim() == "repo") {
+            Ok(format!("authenticates as {user} with the 'repo' scope"))
+        } else {
+            Err(format!(
+                "authenticates as {user}, but lacks the 'repo' scope needed to list private \
+                 repositories' forks (its scopes are '{scopes}')"
+            ))
+        })
+    }
+}
+
+/// Returns the value of the `Authorization` header to use for the GitHub token `value`.  A bare
+/// token is accepted if it looks like a GitHub token (i.e. has one of the prefixes GitHub gives its
+/// tokens, or is a 40-character hex string as per older classic tokens), and is sent using Bearer
+/// auth.  The older format `<username>:<token>` is sent using Basic auth.  Anything else is
+/// explained as an error.
+fn authorisation(value: &str) -> Result<String, String> {
+    const TOKEN_PREFIXES: [&str; 6] = ["ghp_", "gho_", "ghu_", "ghs_", "ghr_", "github_pat_"];
+    let expected = "Expected a GitHub token, optionally preceded by '<GitHub Username>:'.";
+    if value.chars().any(char::is_whitespace) {
+        return Err(format!("it contains whitespace.  {expected}"));
+    }
+    match value.split_once(':') {
+        Some((user, token)) if !user.is_empty() && !token.is_empty() => Ok(format!(
+            "Basic {}",
+            base64::prelude::BASE64_STANDARD.encode(value)
+        )),
+        Some(_) => Err(format!("the username or token is empty.  {expected}")),
+        None if TOKEN_PREFIXES
+            .iter()
+            .any(|prefix| value.starts_with(prefix))
+            || (value.len() == 40 && value.chars().all(|c| c.is_ascii_hexdigit())) =>
+        {
+            Ok(format!("Bearer {value}"))
+        }
+        None => Err(format!(
+            "it has no username and doesn't look like a GitHub token.  {expected}"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            tests::{mock_api, mount},
+            ForgeKind, ForgeProvider,
+        },
+        GitHub,
+    };
+    use crate::{
+        error::Error,
+        repo::{rate_limit, Fork, Name, Owner, RemoteAlias, Repo, Url},
+    };
+    use serde_json::{json, Value};
+    use std::ops::Range;
+    use wiremock::{
+        matchers::{method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    /// Returns a `Repo` along with a mock of GitHub's API and a `GitHub` which queries it.
+    fn mock_github() -> (Repo, MockServer, GitHub) {
+        let repo = Repo::new_uninitialised();
+        let server = mock_api(&repo);
+        let github = GitHub {
+            api: server.uri(),
+            authorisation: None,
+        };
+        (repo, server, github)
+    }
+
+    /// Returns the repository `owner/cargo` as described by GitHub's API.
+    fn repo_json(owner: &str) -> Value {
+        json!({
+            "owner": { "login": owner, "type": "User" },
+            "name": "cargo",
+            "ssh_url": format!("git@github.com:{owner}/cargo.git"),
+            "pushed_at": "2024-05-01T12:00:00Z",
+            "stargazers_count": 3,
+        })
+    }
+
+    /// Returns a page of forks, one owned by `owner<index>` for each index in `owners`.
+    fn forks_page(owners: Range<usize>) -> Value {
+        Value::Array(
+            owners
+                .map(|index| repo_json(&format!("owner{index}")))
+                .collect(),
+        )
+    }
+
+    /// Mounts `body` as the page of forks numbered `page`, or as the first page if `page` is
+    /// `None`, with the given `Link` header if any.
+    fn mount_forks_page(
+        repo: &Repo,
+        server: &MockServer,
+        page: Option<&str>,
+        body: Value,
+        link: Option<String>,
+    ) {
+        let mut mock = Mock::given(method("GET")).and(path("/repos/Fraser999/cargo/forks"));
+        if let Some(page) = page {
+            mock = mock.and(query_param("page", page));
+        }
+        let mut response = ResponseTemplate::new(200).set_body_json(body);
+        if let Some(link) = link {
+            response = response.insert_header("link", link);
+        }
+        // Pages with a `page` parameter take priority over the first page, which has none.
+        let priority = if page.is_some() { 1 } else { 2 };
+        mount(
+            repo,
+            server,
+            mock.respond_with(response).with_priority(priority),
+        );
+    }
+
+    fn owners(forks: &[Fork]) -> Vec<String> {
+        forks.iter().map(|fork| fork.owner.0.clone()).collect()
+    }
+
+    fn cargo() -> (Owner, Name) {
+        (Owner("Fraser999".to_string()), Name("cargo".to_string()))
+    }
+
+    #[test]
+    fn list_forks_via_last_link() {
+        let (repo, server, github) = mock_github();
+        let link = |page: usize, rel: &str| {
+            format!(
+                "<{}/repos/Fraser999/cargo/forks?per_page=100&page={page}>; rel=\"{rel}\"",
+                server.uri()
+            )
+        };
+        let first_link = format!("{}, {}", link(2, "next"), link(3, "last"));
+        mount_forks_page(&repo, &server, None, forks_page(0..100), Some(first_link));
+        mount_forks_page(&repo, &server, Some("2"), forks_page(100..200), None);
+        mount_forks_page(&repo, &server, Some("3"), forks_page(200..201), None);
+
+        let (owner, name) = cargo();
+        let forks = github.list_forks(&repo, &owner, &name).unwrap();
+        let expected: Vec<String> = (0..201).map(|index| format!("owner{index}")).collect();
+        assert_eq!(owners(&forks), expected);
+    }
+
+    #[test]
+    fn list_forks_via_next_links() {
+        let (repo, server, github) = mock_github();
+        let next = |page: usize| {
+            format!(
+                "<{}/repos/Fraser999/cargo/forks?per_page=100&page={page}>; rel=\"next\"",
+                server.uri()
+            )
+        };
+        mount_forks_page(&repo, &server, None, forks_page(0..2), Some(next(2)));
+        mount_forks_page(&repo, &server, Some("2"), forks_page(2..4), Some(next(3)));
+        mount_forks_page(&repo, &server, Some("3"), forks_page(4..5), None);
+
+        let (owner, name) = cargo();
+        let forks = github.list_forks(&repo, &owner, &name).unwrap();
+        assert_eq!(
+            owners(&forks),
+            ["owner0", "owner1", "owner2", "owner3", "owner4"]
+        );
+    }
+
+    #[test]
+    fn get_source_of_fork() {
+        let (repo, server, github) = mock_github();
+        let mut fork = repo_json("alice");
+        fork["source"] = repo_json("Fraser999");
+        fork["source"]["owner"]["type"] = json!("Organization");
+        let mock = Mock::given(method("GET")).and(path("/repos/alice/cargo"));
+        mount(
+            &repo,
+            &server,
+            mock.respond_with(ResponseTemplate::new(200).set_body_json(fork)),
+        );
+
+        let source = github
+            .get_source(
+                &repo,
+                &Owner("alice".to_string()),
+                &Name("cargo".to_string()),
+            )
+            .unwrap();
+        assert_eq!(source.owner.0, "Fraser999");
+        assert_eq!(source.url.value(), "git@github.com:Fraser999/cargo.git");
+        assert_eq!(
+            source.org_members_request,
+            Some(format!(
+                "{}/orgs/Fraser999/members?per_page=100",
+                server.uri()
+            ))
+        );
+    }
+
+    #[test]
+    fn error_body_is_reported() {
+        let (repo, server, github) = mock_github();
+        let body = json!({ "message": "Not Found", "documentation_url": "https://docs" });
+        let mock = Mock::given(method("GET")).and(path("/repos/Fraser999/cargo"));
+        mount(
+            &repo,
+            &server,
+            mock.respond_with(ResponseTemplate::new(404).set_body_json(body)),
+        );
+
+        let (owner, name) = cargo();
+        let Err(Error::Request(message)) = github.get_source(&repo, &owner, &name) else {
+            panic!("request should fail");
+        };
+        assert!(message.contains("404 Not Found"), "{}", message);
+        assert!(
+            message.contains(r#"\"message\":\"Not Found\""#),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn rate_limited_request_is_retried() {
+        let (repo, server, github) = mock_github();
+        let mock = Mock::given(method("GET")).and(path("/repos/Fraser999/cargo"));
+        mount(
+            &repo,
+            &server,
+            mock.respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+                .up_to_n_times(1)
+                .with_priority(1),
+        );
+        let mock = Mock::given(method("GET")).and(path("/repos/Fraser999/cargo"));
+        mount(
+            &repo,
+            &server,
+            mock.respond_with(ResponseTemplate::new(200).set_body_json(repo_json("Fraser999")))
+                .expect(1),
+        );
+
+        let (owner, name) = cargo();
+        let source = github.get_source(&repo, &owner, &name).unwrap();
+        assert_eq!(source.owner.0, "Fraser999");
+    }
+
+    #[test]
+    fn long_rate_limit_is_not_waited_for() {
+        let (repo, server, github) = mock_github();
+        let reset = rate_limit::now() + 2 * 60 * 60;
+        let response = ResponseTemplate::new(403)
+            .insert_header("x-ratelimit-remaining", "0")
+            .insert_header("x-ratelimit-reset", reset.to_string().as_str())
+            .set_body_json(json!({ "message": "API rate limit exceeded" }));
+        let mock = Mock::given(method("GET")).and(path("/repos/Fraser999/cargo"));
+        mount(&repo, &server, mock.respond_with(response).expect(1));
+
+        let (owner, name) = cargo();
+        let Err(Error::Request(message)) = github.get_source(&repo, &owner, &name) else {
+            panic!("request should fail");
+        };
+        assert!(message.contains("403 Forbidden"), "{}", message);
+        assert!(message.contains("API rate limit exceeded"), "{}", message);
+    }
+
+    #[test]
+    fn populate_available_forks() {
+        let (mut repo, server, github) = mock_github();
+        let mock = Mock::given(method("GET")).and(path("/repos/Fraser999/cargo"));
+        mount(
+            &repo,
+            &server,
+            mock.respond_with(ResponseTemplate::new(200).set_body_json(repo_json("Fraser999"))),
+        );
+        let last_link = format!(
+            "<{}/repos/Fraser999/cargo/forks?per_page=100&page=2>; rel=\"last\"",
+            server.uri()
+        );
+        let mut first_page = forks_page(0..100);
+        first_page[0] = repo_json("Fraser999");
+        mount_forks_page(&repo, &server, None, first_page, Some(last_link));
+        mount_forks_page(&repo, &server, Some("2"), forks_page(100..150), None);
+
+        let url = Url::of_forge(ForgeKind::GitHub, "git@github.com:Fraser999/cargo.git");
+        repo.forge = Some(Box::new(github));
+        let (owner, name) = cargo();
+        let _ = repo.local_remotes.insert(
+            owner.clone(),
+            (name.clone(), RemoteAlias("origin".to_string()), url),
+        );
+        let source = repo.forge().get_source(&repo, &owner, &name).unwrap();
+        let forks = repo.fetch_forks(&source.owner, &source.name).unwrap();
+        repo.set_main_fork(source);
+        repo.populate_available_forks(forks);
+        repo.show_available_forks();
+        // The main fork is already a remote, so only the other 149 forks are available.
+        assert_eq!(repo.available_forks.len(), 149);
+        assert!(repo
+            .available_forks
+            .iter()
+            .all(|fork| fork.owner.0 != "Fraser999"));
+    }
+
+    #[test]
+    fn authorisation() {
+        let authorisation = super::authorisation;
+        assert_eq!(
+            authorisation("Fraser999:abc123"),
+            Ok("Basic RnJhc2VyOTk5OmFiYzEyMw==".to_string())
+        );
+        assert_eq!(
+            authorisation("ghp_abc123"),
+            Ok("Bearer ghp_abc123".to_string())
+        );
+        assert_eq!(
+            authorisation("github_pat_11ABC_def"),
+            Ok("Bearer github_pat_11ABC_def".to_string())
+        );
+        // As provided to GitHub Actions, and as obtained by `add-remote login github`.
+        assert_eq!(
+            authorisation("ghs_abc123"),
+            Ok("Bearer ghs_abc123".to_string())
+        );
+        assert_eq!(
+            authorisation("gho_abc123"),
+            Ok("Bearer gho_abc123".to_string())
+        );
+        let classic = "0123456789abcdef0123456789abcdef01234567";
+        assert_eq!(authorisation(classic), Ok(format!("Bearer {classic}")));
+        assert!(authorisation("Fraser999").is_err());
+        assert!(authorisation("Fraser999:").is_err());
+        assert!(authorisation(":ghp_abc123").is_err());
+        assert!(authorisation("Fraser999: ghp_abc123").is_err());
+    }
+}
@@ -0,0 +1,409 @@
+use super::{
+    graphql::{ForkFields, ForkQuery},
+    repo_state_from, string_at, token_rejected, ForgeKind, ForgeProvider, RepoState, Source,
+};
+use crate::{
+    cli::{ForkApi, LoginForge},
+    error::Error,
+    repo::{login::OAUTH_TOKEN_PREFIX, Fork, Name, Owner, Repo, Url},
+};
+use serde_json::{json, Value};
+
+/// The base URL of GitLab's API.
+const GITLAB_API: &str = "https://gitlab.com/api";
+/// The GraphQL query listing up to 100 forks of a project at a time.
+const FORKS_QUERY: &str = "query($fullPath: ID!, $cursor: String) {
+  project(fullPath: $fullPath) {
+    forks(first: 100, after: $cursor) {
+      pageInfo { hasNextPage endCursor }
+      nodes {
+        path
+        sshUrlToRepo
+        lastActivityAt
+        starCount
+        forksCount
+        namespace { fullPath }
+        repository { rootRef }
+      }
+    }
+  }
+}";
+/// Where the details of each fork are found in the nodes returned by `FORKS_QUERY`.  GitLab's
+/// GraphQL API doesn't report namespaces' avatars.
+const FORK_FIELDS: ForkFields = ForkFields {
+    owner: "/namespace/fullPath",
+    name: "/path",
+    ssh_url: "/sshUrlToRepo",
+    last_activity: "/lastActivityAt",
+    stars: "/starCount",
+    default_branch: "/repository/rootRef",
+    avatar_url: None,
+    subfork_count: Some("/forksCount"),
+};
+/// The environment variables checked for a token if none is set in Git config, in order.
+const TOKEN_ENV_VARS: [&str; 2] = ["ADD_REMOTE_GITLAB_TOKEN", "GITLAB_TOKEN"];
+
+/// GitLab, whose API is accessed using the token set as `add-remote.gitLabToken` or in one of
+/// `TOKEN_ENV_VARS`.  The token is passed in the query string of each REST request rather than in
+/// a header, but as a bearer token in the header of each GraphQL request.
+#[derive(Debug)]
+pub(super) struct GitLab {
+    /// The base URL of the API, which is `GITLAB_API` other than in tests.
+    api: String,
+    /// The query parameter carrying the token: `private_token=<token>` for a Personal Access
+    /// Token, or `access_token=<token>` for an OAuth access token.
+    token_parameter: String,
+    /// The `Authorization` header for GraphQL requests: `Bearer <token>`.
+    bearer: String,
+}
+
+impl GitLab {
+    /// Reads the token, returning an error if it's not set since GitLab requires one.  An OAuth
+    /// token stored by `add-remote login gitlab` is refreshed first if it has expired.
+    pub(super) fn new(repo: &Repo) -> Result<Self, Error> {
+        let _ = repo.refresh_login(LoginForge::GitLab, true);
+        let token = repo
+            .read_token("gitLabToken", "gitlab.com", &TOKEN_ENV_VARS)
+            .ok_or(Error::MissingGitLabToken)?;
+        let token_parameter = match token.strip_prefix(OAUTH_TOKEN_PREFIX) {
+            Some(access_token) => format!("access_token={access_token}"),
+            None => format!("private_token={token}"),
+        };
+        let bearer = format!(
+            "Bearer {}",
+            token.strip_prefix(OAUTH_TOKEN_PREFIX).unwrap_or(&token)
+        );
+        Ok(GitLab {
+            api: GITLAB_API.to_string(),
+            token_parameter,
+            bearer,
+        })
+    }
+
+    /// Returns the API request for the project `owner/name`, or for `suffix` under it.
+    fn project_request(&self, owner: &Owner, name: &Name, suffix: &str) -> String {
+        let separator = if suffix.contains('?') { '&' } else { '?' };
+        format!(
+            "{}/v4/projects/{}%2F{}{}{}{}",
+            self.api,
+            owner.0,
+            name.0.replace('/', "%2F"),
+            suffix,
+            separator,
+            self.token_parameter
+        )
+    }
+}
+
+impl ForgeProvider for GitLab {
+    fn name(&self) -> &'static str {
+        "GitLab"
+    }
+
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::GitLab
+    }
+
+    fn api_url(&self) -> String {
+        format!("{}/v4/projects/", self.api)
+    }
+
+    fn auth_header(&self) -> Option<&String> {
+        None
+    }
+
+    /// Follows the chain of projects each was forked from until reaching one which isn't a fork.
+    fn get_source(&self, repo: &Repo, owner: &Owner, name: &Name) -> Result<Source, Error> {
+        let (mut owner, mut name) = (owner.clone(), name.clone());
+        loop {
+            let request = self.project_request(&owner, &name, "");
+            let response_as_json = repo.get_json(&request, None)?;
+            let unexpected = |reason| Error::unexpected_response(&request, reason);
+            if let Value::Null = response_as_json["forked_from_project"] {
+                let org_members_request =
+                    (response_as_json["namespace"]["kind"] == "group").then(|| {
+                        format!(
+                            "{}/v4/groups/{}/members/all?per_page=100&{}",
+                            self.api, response_as_json["namespace"]["id"], self.token_parameter
+                        )
+                    });
+                let url = string_at(&response_as_json, "/ssh_url_to_repo").map_err(unexpected)?;
+                return Ok(Source {
+                    owner,
+                    name,
+                    url: Url::of_forge(ForgeKind::GitLab, url),
+                    listing: parse_project(&response_as_json).ok(),
+                    org_members_request,
+                });
+            }
+            (owner, name) = split_path(
+                &response_as_json,
+                "/forked_from_project/path_with_namespace",
+            )
+            .map_err(unexpected)?;
+        }
+    }
+
+    /// GitLab only lists direct forks, so the number of forks of each is recorded in order to
+    /// warn that they're being ignored.  If GraphQL was chosen, the forks are listed via GraphQL,
+    /// 100 at a time.
+    fn list_forks(&self, repo: &Repo, owner: &Owner, name: &Name) -> Result<Vec<Fork>, Error> {
+        if repo.fork_api == ForkApi::GraphQl && !repo.offline {
+            let endpoint = format!("{}/graphql", self.api);
+            let query = ForkQuery {
+                kind: ForgeKind::GitLab,
+                endpoint: &endpoint,
+                query: FORKS_QUERY,
+                variables: json!({ "fullPath": format!("{}/{}", owner.0, name.0) }),
+                connection: "/project/forks",
+                repository: format!("{}/{}", owner.0, name.0),
+                fields: &FORK_FIELDS,
+            };
+            return repo.list_forks_via_graphql(&query, Some(&self.bearer));
+        }
+        let request = self.project_request(owner, name, "/forks");
+        repo.get_fork_list_values(&request, None, owner, name)?
+            .iter()
+            .map(|value| {
+                Ok(Fork {
+                    subfork_count: value["forks_count"].as_u64().unwrap_or_default(),
+                    ..parse_project(value)?
+                })
+            })
+            .collect::<Result<_, String>>()
+            .map_err(|reason| Error::unexpected_response(&request, reason))
+    }
+
+    /// Merge requests only give the ID of their source project, so each project's details need a
+    /// further request.
+    fn list_pull_request_forks(
+        &self,
+        repo: &Repo,
+        owner: &Owner,
+        name: &Name,
+    ) -> Result<Vec<Fork>, Error> {
+        let request =
+            self.project_request(owner, name, "/merge_requests?state=opened&per_page=100");
+        let mut project_ids = Vec::new();
+        for value in repo.get_all_array_values(&request, None)? {
+            let source = value["source_project_id"].as_u64();
+            if let Some(id) = source.filter(|id| Some(*id) != value["target_project_id"].as_u64()) {
+                if !project_ids.contains(&id) {
+                    project_ids.push(id);
+                }
+            }
+        }
+        let links: Vec<String> = project_ids
+            .iter()
+            .map(|id| format!("{}{id}?{}", self.api_url(), self.token_parameter))
+            .collect();
+        Ok(repo
+            .send_gets_concurrently(&links, None)
+            .into_iter()
+            .flatten()
+            .filter_map(|page| serde_json::from_str::<Value>(&page.content).ok())
+            .filter_map(|value| parse_project(&value).ok())
+            .collect())
+    }
+
+    fn member_login<'a>(&self, member: &'a Value) -> Option<&'a str> {
+        member["username"].as_str()
+    }
+
+    /// GitLab redirects requests for a project under its owner's old name.
+    fn current_owner(&self, repo: &Repo, owner: &Owner, name: &Name) -> Option<Owner> {
+        let request = self.project_request(owner, name, "");
+        let page = repo.try_send_get(&request, None).ok()?;
+        let value: Value = serde_json::from_str(&page.content).ok()?;
+        Some(Url::split_owner_and_repo(value["path_with_namespace"].as_str()?)?.0)
+    }
+
+    fn repo_state(&self, repo: &Repo, owner: &Owner, name: &Name) -> Result<RepoState, String> {
+        let request = self.project_request(owner, name, "");
+        repo_state_from(
+            &repo.probe(&request, None)?,
+            Some("/archived"),
+            Some("/path_with_namespace"),
+        )
+    }
+
+    /// A Personal Access Token's scopes must include `read_api` or `api`.  An OAuth token from
+    /// `add-remote login gitlab` was requested with `read_api`, so only its validity is checked.
+    fn check_token(&self, repo: &Repo) -> Option<Result<String, String>> {
+        let probe = |path: &str| {
+            let request = format!("{}/v4/{path}?{}", self.api, self.token_parameter);
+            let probe = repo.probe(&request, None)?;
+            if probe.status.is_success() {
+                Ok(probe.json())
+            } else {
+                Err(token_rejected("GitLab", &probe))
+            }
+        };
+        let user = match probe("user") {
+            Ok(user) => user["username"].as_str().unwrap_or("?").to_string(),
+            Err(error) => return Some(Err(error)),
+        };
+        if !self.token_parameter.starts_with("private_token=") {
+            return Some(Ok(format!("authenticates as {user}")));
+        }
+        let token = match probe("personal_access_tokens/self") {
+            Ok(token) => token,
+            Err(error) => return Some(Err(error)),
+        };
+        let scopes: Vec<&str> = token["scopes"]
+            .as_array()
+            .map(|scopes| scopes.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        Some(
+            if scopes
+                .iter()
+                .any(|scope| ["read_api", "api"].contains(scope))
+            {
+                Ok(format!("authenticates as {user} with the 'read_api' scope"))
+            } else {
+                Err(format!(
+                    "authenticates as {user}, but lacks the 'read_api' scope (its scopes are \
+                     '{}')",
+                    scopes.join(", ")
+                ))
+            },
+        )
+    }
+}
+
+/// Returns the owner and name of a project from its path, the string at `pointer` in `value`, or a
+/// description of the problem if it's missing or has no owner.
+fn split_path(value: &Value, pointer: &str) -> Result<(Owner, Name), String> {
+    let path = string_at(value, pointer)?;
+    Url::split_owner_and_repo(path)
+        .ok_or_else(|| format!("expected 'owner/name' at '{pointer}' but got '{path}'"))
+}
+
+/// Parses a project as returned by the GitLab API, returning a description of the problem if any
+/// of the required fields are missing.
+fn parse_project(value: &Value) -> Result<Fork, String> {
+    let (owner, name) = split_path(value, "/path_with_namespace")?;
+    let url = Url::of_forge(ForgeKind::GitLab, string_at(value, "/ssh_url_to_repo")?);
+    let last_activity = value["last_activity_at"].as_str();
+    Ok(Fork {
+        avatar_url: value["namespace"]["avatar_url"]
+            .as_str()
+            .map(ToString::to_string),
+        stars: value["star_count"].as_u64(),
+        default_branch: value["default_branch"].as_str().map(ToString::to_string),
+        ..Fork::new(owner, name, url, last_activity)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            tests::{mock_api, mount},
+            ForgeProvider,
+        },
+        GitLab,
+    };
+    use crate::{
+        error::Error,
+        repo::{Name, Owner, Repo},
+    };
+    use serde_json::{json, Value};
+    use wiremock::{
+        matchers::{method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    /// Returns a `Repo` along with a mock of GitLab's API and a `GitLab` which queries it.
+    fn mock_gitlab() -> (Repo, MockServer, GitLab) {
+        let repo = Repo::new_uninitialised();
+        let server = mock_api(&repo);
+        let gitlab = GitLab {
+            api: format!("{}/api", server.uri()),
+            token_parameter: "private_token=secret".to_string(),
+            bearer: "Bearer secret".to_string(),
+        };
+        (repo, server, gitlab)
+    }
+
+    /// Returns the project at `path_with_namespace` as described by GitLab's API, forked from
+    /// `parent` if set.
+    fn project_json(path_with_namespace: &str, parent: Option<&str>) -> Value {
+        let mut project = json!({
+            "path_with_namespace": path_with_namespace,
+            "ssh_url_to_repo": format!("git@gitlab.com:{path_with_namespace}.git"),
+            "last_activity_at": "2024-05-01T12:00:00Z",
+            "star_count": 7,
+            "namespace": { "kind": "user", "id": 1 },
+        });
+        if let Some(parent) = parent {
+            project["forked_from_project"] = json!({ "path_with_namespace": parent });
+        }
+        project
+    }
+
+    /// Mounts `response` as the reply to requests for the project `encoded_path`.
+    fn mount_project(repo: &Repo, server: &MockServer, encoded_path: &str, response: Value) {
+        let mock = Mock::given(method("GET"))
+            .and(path(format!("/api/v4/projects/{encoded_path}")))
+            .and(query_param("private_token", "secret"));
+        let response = ResponseTemplate::new(200).set_body_json(response);
+        mount(repo, server, mock.respond_with(response).expect(1));
+    }
+
+    #[test]
+    fn get_source_walks_fork_parents() {
+        let (repo, server, gitlab) = mock_gitlab();
+        mount_project(
+            &repo,
+            &server,
+            "carol%2Fproject",
+            project_json("carol/project", Some("bob/project")),
+        );
+        mount_project(
+            &repo,
+            &server,
+            "bob%2Fproject",
+            project_json("bob/project", Some("group/sub/project")),
+        );
+        let mut source = project_json("group/sub/project", None);
+        source["namespace"] = json!({ "kind": "group", "id": 42 });
+        mount_project(&repo, &server, "group%2Fsub%2Fproject", source);
+
+        let source = gitlab
+            .get_source(
+                &repo,
+                &Owner("carol".to_string()),
+                &Name("project".to_string()),
+            )
+            .unwrap();
+        assert_eq!(source.owner.0, "group");
+        assert_eq!(source.name.0, "sub/project");
+        assert_eq!(source.url.value(), "git@gitlab.com:group/sub/project.git");
+        assert_eq!(source.listing.unwrap().stars, Some(7));
+        assert_eq!(
+            source.org_members_request,
+            Some(format!(
+                "{}/api/v4/groups/42/members/all?per_page=100&private_token=secret",
+                server.uri()
+            ))
+        );
+    }
+
+    #[test]
+    fn get_source_reports_missing_parent() {
+        let (repo, server, gitlab) = mock_gitlab();
+        let mut project = project_json("carol/project", None);
+        project["forked_from_project"] = json!({ "id": 3 });
+        mount_project(&repo, &server, "carol%2Fproject", project);
+
+        let result = gitlab.get_source(
+            &repo,
+            &Owner("carol".to_string()),
+            &Name("project".to_string()),
+        );
+        let Err(Error::UnexpectedResponse { .. }) = result else {
+            panic!("a parent without a path should be reported");
+        };
+    }
+}
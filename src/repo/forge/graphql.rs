@@ -0,0 +1,233 @@
+use super::{string_at, ForgeKind};
+use crate::{
+    error::Error,
+    repo::{Fork, Name, Owner, Repo, Url},
+};
+use reqwest::{header::CONTENT_TYPE, Method, StatusCode};
+use serde_json::{json, Value};
+use tracing::debug;
+
+/// The JSON pointers to the details of a fork within a node of a GraphQL connection of forks,
+/// which differ between forges.
+pub(super) struct ForkFields {
+    pub owner: &'static str,
+    pub name: &'static str,
+    pub ssh_url: &'static str,
+    pub last_activity: &'static str,
+    pub stars: &'static str,
+    pub default_branch: &'static str,
+    /// The owner's avatar, if the forge's GraphQL API reports it.
+    pub avatar_url: Option<&'static str>,
+    /// The number of forks of the fork, only selected if the forge's REST API reports it too, so
+    /// that both APIs list the same details.
+    pub subfork_count: Option<&'static str>,
+}
+
+/// A query for a connection of forks, paginated by cursors as per the Relay specification which
+/// both GitHub and GitLab follow.  The query must pass its `$cursor` variable as the connection's
+/// `after` argument, and must select the connection's `pageInfo` and `nodes`.
+pub(super) struct ForkQuery<'a> {
+    pub kind: ForgeKind,
+    pub endpoint: &'a str,
+    pub query: &'static str,
+    /// The query's variables other than `cursor`.
+    pub variables: Value,
+    /// The JSON pointer to the connection within the response's `data`.
+    pub connection: &'static str,
+    /// The repository whose forks are listed, as "owner/name", shown by the spinner.
+    pub repository: String,
+    pub fields: &'a ForkFields,
+}
+
+impl Repo {
+    /// Sends `query` to the forge's GraphQL API, then again with the cursor of each page of forks
+    /// returned until the last, and returns all the forks listed.
+    pub(super) fn list_forks_via_graphql(
+        &self,
+        query: &ForkQuery,
+        authorisation: Option<&String>,
+    ) -> Result<Vec<Fork>, Error> {
+        let mut variables = query.variables.clone();
+        let mut forks = Vec::new();
+        let what = format!("the forks of {}", query.repository);
+        for page in 1.. {
+            self.progress.page(&what, page, None);
+            let response =
+                self.post_graphql(query.endpoint, query.query, &variables, authorisation)?;
+            let connection = response
+                .pointer(&format!("/data{}", query.connection))
+                .filter(|connection| connection.is_object())
+                .ok_or_else(|| {
+                    Error::unexpected_response(
+                        query.endpoint,
+                        format!("no forks at '{}' in {response}", query.connection),
+                    )
+                })?;
+            for node in connection["nodes"].as_array().into_iter().flatten() {
+                let fork = parse_fork(node, query.kind, query.fields)
+                    .map_err(|reason| Error::unexpected_response(query.endpoint, reason))?;
+                forks.push(fork);
+            }
+            let page_info = &connection["pageInfo"];
+            match page_info["endCursor"].as_str() {
+                Some(cursor) if page_info["hasNextPage"] == true => {
+                    debug!(cursor, "fetching the next page of forks");
+                    variables["cursor"] = json!(cursor);
+                }
+                _ => break,
+            }
+        }
+        Ok(forks)
+    }
+
+    /// Posts `query` with `variables` to the GraphQL API at `endpoint` and returns the response,
+    /// or an error if it couldn't be sent or it reports any errors.  Transient failures are retried
+    /// as for any other request.
+    fn post_graphql(
+        &self,
+        endpoint: &str,
+        query: &str,
+        variables: &Value,
+        authorisation: Option<&String>,
+    ) -> Result<Value, Error> {
+        let body = json!({ "query": query, "variables": variables }).to_string();
+        self.runtime.block_on(async {
+            let response = self
+                .send_with_retries(Method::POST, endpoint, authorisation, |builder| {
+                    builder
+                        .header(CONTENT_TYPE, "application/json")
+                        .body(body.clone())
+                })
+                .await
+                .map_err(Error::Request)?;
+            let status = response.status();
+            let body = response
+                .text()
+                .await
+                .map_err(|error| Error::Request(format!("Failed to POST {endpoint}: {error}")))?;
+            let value: Value = serde_json::from_str(&body).unwrap_or_default();
+            let errors: Vec<&str> = value["errors"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|error| error["message"].as_str())
+                .collect();
+            if !status.is_success() || !errors.is_empty() {
+                let details = if errors.is_empty() {
+                    body
+                } else {
+                    errors.join("\n")
+                };
+                let message = format!(
+                    "\nFailed to POST {endpoint}\nResponse status: {status}\nErrors:\n{details}"
+                );
+                return Err(if status == StatusCode::UNAUTHORIZED {
+                    Error::AuthRequired(message)
+                } else {
+                    Error::Request(message)
+                });
+            }
+            Ok(value)
+        })
+    }
+}
+
+/// Parses a node of a connection of forks, whose details are found at the pointers in `fields`.
+/// Returns a description of the problem if the owner, name or SSH URL is missing.
+fn parse_fork(node: &Value, kind: ForgeKind, fields: &ForkFields) -> Result<Fork, String> {
+    let owner = Owner(string_at(node, fields.owner)?.to_string());
+    let name = Name(string_at(node, fields.name)?.to_string());
+    let url = Url::of_forge(kind, string_at(node, fields.ssh_url)?);
+    let text = |pointer: &str| node.pointer(pointer).and_then(Value::as_str);
+    let number = |pointer: &str| node.pointer(pointer).and_then(Value::as_u64);
+    Ok(Fork {
+        avatar_url: fields.avatar_url.and_then(text).map(ToString::to_string),
+        stars: number(fields.stars),
+        default_branch: text(fields.default_branch).map(ToString::to_string),
+        subfork_count: fields.subfork_count.and_then(number).unwrap_or_default(),
+        ..Fork::new(owner, name, url, text(fields.last_activity))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_fork, ForkFields};
+    use crate::repo::forge::ForgeKind;
+    use serde_json::json;
+
+    #[test]
+    fn parse_fork_node() {
+        let fields = ForkFields {
+            owner: "/owner/login",
+            name: "/name",
+            ssh_url: "/sshUrl",
+            last_activity: "/pushedAt",
+            stars: "/stargazerCount",
+            default_branch: "/defaultBranchRef/name",
+            avatar_url: Some("/owner/avatarUrl"),
+            subfork_count: None,
+        };
+        let node = json!({
+            "name": "cargo",
+            "sshUrl": "git@github.com:alice/cargo.git",
+            "pushedAt": "2024-05-01T12:00:00Z",
+            "stargazerCount": 7,
+            "forkCount": 2,
+            "owner": { "login": "alice", "avatarUrl": "https://avatars.example/alice" },
+            "defaultBranchRef": { "name": "main" }
+        });
+        let fork = parse_fork(&node, ForgeKind::GitHub, &fields).unwrap();
+        assert_eq!(fork.owner.0, "alice");
+        assert_eq!(fork.name.0, "cargo");
+        assert_eq!(fork.url.value(), "git@github.com:alice/cargo.git");
+        assert_eq!(fork.last_activity.as_deref(), Some("2024-05-01T12:00:00Z"));
+        assert_eq!(fork.stars, Some(7));
+        assert_eq!(fork.default_branch.as_deref(), Some("main"));
+        assert_eq!(
+            fork.avatar_url.as_deref(),
+            Some("https://avatars.example/alice")
+        );
+        assert_eq!(fork.subfork_count, 0);
+
+        // An empty repository has no default branch.
+        let node = json!({
+            "name": "cargo",
+            "sshUrl": "git@github.com:bob/cargo.git",
+            "owner": { "login": "bob" },
+            "defaultBranchRef": null
+        });
+        let fork = parse_fork(&node, ForgeKind::GitHub, &fields).unwrap();
+        assert_eq!(fork.default_branch, None);
+        assert_eq!(fork.stars, None);
+
+        assert!(parse_fork(&json!({ "name": "cargo" }), ForgeKind::GitHub, &fields).is_err());
+
+        // GitLab's forks can be owned by subgroups, and report their own numbers of forks.
+        let fields = ForkFields {
+            owner: "/namespace/fullPath",
+            name: "/path",
+            ssh_url: "/sshUrlToRepo",
+            last_activity: "/lastActivityAt",
+            stars: "/starCount",
+            default_branch: "/repository/rootRef",
+            avatar_url: None,
+            subfork_count: Some("/forksCount"),
+        };
+        let node = json!({
+            "path": "tool",
+            "sshUrlToRepo": "git@gitlab.com:group/sub/tool.git",
+            "lastActivityAt": "2024-06-01T08:30:00Z",
+            "starCount": 0,
+            "forksCount": 3,
+            "namespace": { "fullPath": "group/sub" },
+            "repository": { "rootRef": "develop" }
+        });
+        let fork = parse_fork(&node, ForgeKind::GitLab, &fields).unwrap();
+        assert_eq!(fork.owner.0, "group/sub");
+        assert_eq!(fork.name.0, "tool");
+        assert_eq!(fork.stars, Some(0));
+        assert_eq!(fork.default_branch.as_deref(), Some("develop"));
+        assert_eq!(fork.avatar_url, None);
+        assert_eq!(fork.subfork_count, 3);
+    }
+}
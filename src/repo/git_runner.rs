@@ -0,0 +1,164 @@
+use crate::git_url;
+#[cfg(test)]
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+use std::{
+    fmt::Debug,
+    io,
+    path::PathBuf,
+    process::{Command, ExitStatus, Output},
+};
+use tracing::debug;
+
+/// Runs Git commands.  `Repo` only ever runs Git via this trait, so its logic can be tested against a
+/// `FakeGit` rather than a real repository, and the commands it runs most can be answered by
+/// `NativeGit` without a Git process.
+pub(super) trait GitRunner: Debug + Send + Sync {
+    /// Returns a command which runs Git, to which the caller adds the arguments.
+    fn command(&self) -> Command;
+
+    /// Runs `command`, capturing its output.
+    fn output(&self, command: &mut Command) -> io::Result<Output>;
+
+    /// Runs `command` with its stdin, stdout and stderr inherited, returning its exit status.
+    fn status(&self, command: &mut Command) -> io::Result<ExitStatus>;
+}
+
+/// Runs the Git binary installed on the system.
+#[derive(Debug)]
+pub(super) struct SystemGit {
+    /// The full path to the Git binary.
+    path: PathBuf,
+}
+
+impl SystemGit {
+    /// Returns a `SystemGit` running the Git binary found on the `PATH`, or `None` if there's none.
+    pub(super) fn new() -> Option<Self> {
+        find_git::git_path().map(|path| SystemGit { path })
+    }
+}
+
+impl GitRunner for SystemGit {
+    fn command(&self) -> Command {
+        Command::new(&self.path)
+    }
+
+    fn output(&self, command: &mut Command) -> io::Result<Output> {
+        debug!(args = %describe(command), "running git");
+        let output = command.output();
+        if let Ok(output) = &output {
+            debug!(status = %output.status, "git finished");
+        }
+        output
+    }
+
+    fn status(&self, command: &mut Command) -> io::Result<ExitStatus> {
+        debug!(args = %describe(command), "running git");
+        let status = command.status();
+        if let Ok(status) = &status {
+            debug!(%status, "git finished");
+        }
+        status
+    }
+}
+
+/// Returns the arguments of `command` joined by spaces, with any credentials in URLs redacted.
+pub(super) fn describe(command: &Command) -> String {
+    let args: Vec<String> = command
+        .get_args()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect();
+    git_url::redact(&args.join(" "))
+}
+
+/// An in-memory stand-in for Git which records the commands it's given and replies to each with
+/// canned output.  Commands without a canned reply succeed without output.  Clones share their
+/// replies and records.
+#[cfg(test)]
+#[derive(Clone, Debug, Default)]
+pub(super) struct FakeGit {
+    /// The replies to give in turn to each command, keyed by its arguments joined by spaces.  The
+    /// last reply is repeated once the others have been given.
+    replies: Arc<Mutex<HashMap<String, VecDeque<Output>>>>,
+    /// The arguments of every command run, in order.
+    commands: Arc<Mutex<Vec<String>>>,
+}
+
+#[cfg(test)]
+impl FakeGit {
+    /// Makes `git <args>` succeed and print `stdout`.
+    pub(super) fn reply(&self, args: &[&str], stdout: &str) -> &Self {
+        self.add_reply(args, exit_status(0), stdout, "")
+    }
+
+    /// Makes `git <args>` fail and print `stderr`.
+    pub(super) fn fail(&self, args: &[&str], stderr: &str) -> &Self {
+        self.add_reply(args, exit_status(1), "", stderr)
+    }
+
+    /// Returns the arguments of every command run so far, each joined by spaces.
+    pub(super) fn commands(&self) -> Vec<String> {
+        self.commands.lock().unwrap().clone()
+    }
+
+    fn add_reply(&self, args: &[&str], status: ExitStatus, stdout: &str, stderr: &str) -> &Self {
+        let output = Output {
+            status,
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        };
+        self.replies
+            .lock()
+            .unwrap()
+            .entry(args.join(" "))
+            .or_default()
+            .push_back(output);
+        self
+    }
+}
+
+#[cfg(test)]
+impl GitRunner for FakeGit {
+    fn command(&self) -> Command {
+        Command::new("git")
+    }
+
+    fn output(&self, command: &mut Command) -> io::Result<Output> {
+        let args: Vec<String> = command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        let args = args.join(" ");
+        self.commands.lock().unwrap().push(args.clone());
+        let mut replies = self.replies.lock().unwrap();
+        let Some(outputs) = replies.get_mut(&args) else {
+            return Ok(Output {
+                status: exit_status(0),
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            });
+        };
+        if outputs.len() > 1 {
+            return Ok(outputs.pop_front().unwrap());
+        }
+        Ok(outputs[0].clone())
+    }
+
+    fn status(&self, command: &mut Command) -> io::Result<ExitStatus> {
+        self.output(command).map(|output| output.status)
+    }
+}
+
+/// Returns the exit status of a process which exited with `code`.
+pub(super) fn exit_status(code: u8) -> ExitStatus {
+    #[cfg(unix)]
+    {
+        std::os::unix::process::ExitStatusExt::from_raw(i32::from(code) << 8)
+    }
+    #[cfg(windows)]
+    {
+        std::os::windows::process::ExitStatusExt::from_raw(u32::from(code))
+    }
+}
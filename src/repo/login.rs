@@ -0,0 +1,434 @@
+use super::{rate_limit, Repo};
+use crate::{
+    cli::{LoginForge, Options},
+    error::Error,
+    token_store,
+};
+use colour::{dark_green_ln, yellow_ln};
+use reqwest::header::ACCEPT;
+use serde_json::{json, Value};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::debug;
+
+/// The prefix marking a GitLab token as an OAuth access token, as stored by `add-remote login
+/// gitlab`, rather than a Personal Access Token, since GitLab expects the two in different query
+/// parameters.
+pub(super) const OAUTH_TOKEN_PREFIX: &str = "oauth2:";
+
+/// The grant type with which the token is polled for, as per RFC 8628.
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// How long to wait between polls for the token if the forge doesn't say.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How much to slow down polling by each time the forge asks for it.
+const SLOW_DOWN_INCREMENT: Duration = Duration::from_secs(5);
+
+/// How many seconds before a token expires that it's refreshed, so that it doesn't expire while
+/// in use.
+const EXPIRY_MARGIN_SECS: u64 = 60;
+
+/// The details of a forge's OAuth device authorisation flow.
+struct DeviceFlow {
+    /// The name of the forge, for use in messages.
+    name: &'static str,
+    /// The host under which the resulting token is stored in the OS keyring.
+    host: &'static str,
+    /// The request for a device code and the code for the user to enter.
+    device_code_url: String,
+    /// The request polled until the user has entered their code, which then returns the token,
+    /// and with which an expired token is refreshed.
+    token_url: String,
+    /// The scopes requested, separated by spaces.
+    scope: &'static str,
+    /// The Git config key holding the client ID of the OAuth application, if not passed via
+    /// `--client-id`.
+    client_id_key: &'static str,
+    /// The client ID of the OAuth application registered for `add-remote`, used if none is passed
+    /// or set in Git config.  It's embedded at build time from `ADD_REMOTE_GITHUB_CLIENT_ID` or
+    /// `ADD_REMOTE_GITLAB_CLIENT_ID`, as set for release builds.
+    default_client_id: Option<&'static str>,
+    /// The page on which to register an OAuth application.
+    applications_url: &'static str,
+    /// The prefix added to the token before storing it.
+    token_prefix: &'static str,
+    /// How much to slow down polling by each time the forge asks for it.
+    slow_down_increment: Duration,
+}
+
+impl DeviceFlow {
+    fn of(forge: LoginForge) -> Self {
+        match forge {
+            LoginForge::GitHub => DeviceFlow {
+                name: "GitHub",
+                host: "github.com",
+                device_code_url: "https://github.com/login/device/code".to_string(),
+                token_url: "https://github.com/login/oauth/access_token".to_string(),
+                scope: "repo read:org",
+                client_id_key: "add-remote.gitHubClientId",
+                default_client_id: option_env!("ADD_REMOTE_GITHUB_CLIENT_ID"),
+                applications_url: "https://github.com/settings/developers",
+                token_prefix: "",
+                slow_down_increment: SLOW_DOWN_INCREMENT,
+            },
+            LoginForge::GitLab => DeviceFlow {
+                name: "GitLab",
+                host: "gitlab.com",
+                device_code_url: "https://gitlab.com/oauth/authorize_device".to_string(),
+                token_url: "https://gitlab.com/oauth/token".to_string(),
+                scope: "read_api",
+                client_id_key: "add-remote.gitLabClientId",
+                default_client_id: option_env!("ADD_REMOTE_GITLAB_CLIENT_ID"),
+                applications_url: "https://gitlab.com/-/user_settings/applications",
+                token_prefix: OAUTH_TOKEN_PREFIX,
+                slow_down_increment: SLOW_DOWN_INCREMENT,
+            },
+        }
+    }
+
+    /// Stores the access token in the token `response` in the OS keyring, along with its refresh
+    /// token and expiry time if the forge issued one, so that it can be refreshed once expired.
+    fn store_tokens(&self, client_id: &str, response: &Value) -> Result<(), Error> {
+        let token = response["access_token"].as_str().ok_or_else(|| {
+            Error::unexpected_response(&self.token_url, format!("no 'access_token' in {response}"))
+        })?;
+        token_store::set(self.host, &format!("{}{}", self.token_prefix, token))
+            .map_err(Error::Keyring)?;
+        if let Some(refresh_token) = response["refresh_token"].as_str() {
+            let details = json!({
+                "client_id": client_id,
+                "refresh_token": refresh_token,
+                "expires_at": response["expires_in"]
+                    .as_u64()
+                    .map(|seconds| rate_limit::now() + seconds),
+            });
+            token_store::set_refresh(self.host, &details.to_string()).map_err(Error::Keyring)?;
+        }
+        Ok(())
+    }
+}
+
+impl Repo {
+    /// Logs in to `forge` using OAuth's device authorisation flow: the user is shown a code to
+    /// enter in their browser, and the forge is polled until they have, whereupon the token it
+    /// returns is stored in the OS keyring along with any refresh token.  No Git repository is
+    /// needed.
+    pub fn login(options: Options, forge: LoginForge) -> Result<(), Error> {
+        let flow = DeviceFlow::of(forge);
+        let mut repo = Self::new_uninitialised();
+        repo.options = options;
+        let client_id = repo
+            .options
+            .client_id
+            .clone()
+            .or_else(|| repo.get_from_gitconfig(flow.client_id_key))
+            .or_else(|| flow.default_client_id.map(ToString::to_string))
+            .ok_or_else(|| {
+                Error::InvalidArgument(format!(
+                    "Logging in to {} needs the client ID of an OAuth application with the device \
+                     flow enabled.  Register one at {}, then pass its client ID via '--client-id' \
+                     or set it as '{}'.",
+                    flow.name, flow.applications_url, flow.client_id_key
+                ))
+            })?;
+        repo.configure_client(&flow.device_code_url)?;
+
+        let response = repo.post_form(
+            &flow.device_code_url,
+            &[("client_id", &client_id), ("scope", flow.scope)],
+        )?;
+        let field = |name: &str| {
+            response[name].as_str().ok_or_else(|| {
+                Error::unexpected_response(
+                    &flow.device_code_url,
+                    format!("no '{name}' in {response}"),
+                )
+            })
+        };
+        let device_code = field("device_code")?;
+        println!(
+            "To log in to {}, open {} in your browser and enter this code:",
+            flow.name,
+            field("verification_uri")?
+        );
+        yellow_ln!("    {}", field("user_code")?);
+        println!("Waiting for the code to be entered...");
+
+        let tokens = repo.poll_for_token(&flow, &client_id, device_code, &response)?;
+        flow.store_tokens(&client_id, &tokens)?;
+        dark_green_ln!(
+            "Logged in to {}.  The token is stored in the OS keyring for {}.",
+            flow.name,
+            flow.host
+        );
+        Ok(())
+    }
+
+    /// Refreshes the OAuth token stored for `forge` by `add-remote login`, using the refresh token
+    /// stored with it.  If `only_if_expired` is set, the token is only refreshed if it has expired
+    /// or is about to.  Returns whether a new token was stored.  Failures are reported as
+    /// warnings, since the existing token may still work.
+    pub(super) fn refresh_login(&self, forge: LoginForge, only_if_expired: bool) -> bool {
+        let flow = DeviceFlow::of(forge);
+        let details = match token_store::get_refresh(flow.host) {
+            Ok(Some(details)) => details,
+            Ok(None) => return false,
+            Err(error) => {
+                yellow_ln!("Ignoring the refresh token in the OS keyring: {}.", error);
+                return false;
+            }
+        };
+        let Ok(details) = serde_json::from_str::<Value>(&details) else {
+            return false;
+        };
+        let expired = details["expires_at"]
+            .as_u64()
+            .is_some_and(|expires_at| rate_limit::now() + EXPIRY_MARGIN_SECS >= expires_at);
+        if only_if_expired && !expired {
+            return false;
+        }
+        let client_id = details["client_id"].as_str().unwrap_or_default();
+        match self
+            .refresh_token(&flow, &details)
+            .and_then(|tokens| flow.store_tokens(client_id, &tokens))
+        {
+            Ok(()) => {
+                debug!(host = flow.host, "refreshed the OAuth token");
+                true
+            }
+            Err(error) => {
+                yellow_ln!(
+                    "Failed to refresh the {} token stored by 'add-remote login': {}",
+                    flow.name,
+                    error
+                );
+                false
+            }
+        }
+    }
+
+    /// Exchanges the refresh token in the stored refresh `details` for a new access token,
+    /// returning the forge's response.
+    fn refresh_token(&self, flow: &DeviceFlow, details: &Value) -> Result<Value, Error> {
+        let field = |name: &str| details[name].as_str().unwrap_or_default();
+        let response = self.post_form(
+            &flow.token_url,
+            &[
+                ("client_id", field("client_id")),
+                ("grant_type", "refresh_token"),
+                ("refresh_token", field("refresh_token")),
+            ],
+        )?;
+        if response["access_token"].is_string() {
+            return Ok(response);
+        }
+        Err(Error::unexpected_response(&flow.token_url, response))
+    }
+
+    /// Polls the forge at the interval it asked for in `device_code_response` until the user has
+    /// entered their code, returning the response holding the token, or an error if they declined
+    /// or the code expired.
+    fn poll_for_token(
+        &self,
+        flow: &DeviceFlow,
+        client_id: &str,
+        device_code: &str,
+        device_code_response: &Value,
+    ) -> Result<Value, Error> {
+        let mut interval = device_code_response["interval"]
+            .as_u64()
+            .map_or(DEFAULT_POLL_INTERVAL, Duration::from_secs);
+        let expires_at = device_code_response["expires_in"]
+            .as_u64()
+            .map(|seconds| Instant::now() + Duration::from_secs(seconds));
+        let form = [
+            ("client_id", client_id),
+            ("device_code", device_code),
+            ("grant_type", DEVICE_CODE_GRANT_TYPE),
+        ];
+        loop {
+            thread::sleep(interval);
+            let response = self.post_form(&flow.token_url, &form)?;
+            if response["access_token"].is_string() {
+                return Ok(response);
+            }
+            let expired = || {
+                Error::Request(format!(
+                    "The code expired before it was entered.  Run 'add-remote login {}' again.",
+                    flow.name.to_lowercase()
+                ))
+            };
+            match response["error"].as_str() {
+                Some("authorization_pending") => (),
+                Some("slow_down") => interval += flow.slow_down_increment,
+                Some("expired_token") => return Err(expired()),
+                Some("access_denied") => {
+                    return Err(Error::Request(format!(
+                        "Access to {} was denied.",
+                        flow.name
+                    )))
+                }
+                _ => return Err(Error::unexpected_response(&flow.token_url, response)),
+            }
+            if expires_at.is_some_and(|expires_at| Instant::now() >= expires_at) {
+                return Err(expired());
+            }
+        }
+    }
+
+    /// Sends a POST of the URL-encoded `form` to `url` and returns the response parsed as JSON.
+    /// Responses with an error status are parsed too, since that's how OAuth reports e.g. that the
+    /// user hasn't entered their code yet.
+    fn post_form(&self, url: &str, form: &[(&str, &str)]) -> Result<Value, Error> {
+        self.runtime.block_on(async {
+            let response = self
+                .client
+                .post(url)
+                .header(ACCEPT, "application/json")
+                .form(form)
+                .send()
+                .await
+                .map_err(|error| Error::Request(format!("Failed to POST {url}: {error}")))?;
+            let body = response
+                .text()
+                .await
+                .map_err(|error| Error::Request(format!("Failed to POST {url}: {error}")))?;
+            serde_json::from_str(&body).map_err(|error| Error::unexpected_response(url, error))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DeviceFlow, Repo};
+    use crate::{cli::LoginForge, error::Error};
+    use serde_json::{json, Value};
+    use std::time::{Duration, Instant};
+    use wiremock::{
+        matchers::{body_string_contains, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    /// Returns a `Repo` along with a mock of GitLab's OAuth endpoints and the device flow which
+    /// uses them, slowing down by only 50ms when asked to.
+    fn mock_gitlab() -> (Repo, MockServer, DeviceFlow) {
+        let repo = Repo::new_uninitialised();
+        let server = repo.runtime.block_on(MockServer::start());
+        let flow = DeviceFlow {
+            device_code_url: format!("{}/oauth/authorize_device", server.uri()),
+            token_url: format!("{}/oauth/token", server.uri()),
+            slow_down_increment: Duration::from_millis(50),
+            ..DeviceFlow::of(LoginForge::GitLab)
+        };
+        (repo, server, flow)
+    }
+
+    /// Mounts `first` as the reply to the first poll for the token, and a token as the reply to
+    /// any later polls.
+    fn mount_polls(repo: &Repo, server: &MockServer, first: Value) {
+        let poll = || {
+            Mock::given(method("POST"))
+                .and(path("/oauth/token"))
+                .and(body_string_contains("device_code=dev"))
+        };
+        let first = poll()
+            .respond_with(ResponseTemplate::new(400).set_body_json(first))
+            .up_to_n_times(1)
+            .with_priority(1);
+        let token = json!({ "access_token": "abc", "refresh_token": "r1", "expires_in": 7200 });
+        let then = poll().respond_with(ResponseTemplate::new(200).set_body_json(token));
+        repo.runtime.block_on(async {
+            first.mount(server).await;
+            then.mount(server).await;
+        });
+    }
+
+    /// Polls the mock for the token, as though the device code should be polled for immediately.
+    fn poll(repo: &Repo, flow: &DeviceFlow) -> Result<Value, Error> {
+        repo.poll_for_token(flow, "client", "dev", &json!({ "interval": 0 }))
+    }
+
+    #[test]
+    fn poll_waits_while_authorization_is_pending() {
+        let (repo, server, flow) = mock_gitlab();
+        mount_polls(&repo, &server, json!({ "error": "authorization_pending" }));
+        let tokens = poll(&repo, &flow).unwrap();
+        assert_eq!(tokens["access_token"], "abc");
+        assert_eq!(tokens["refresh_token"], "r1");
+        assert_eq!(tokens["expires_in"], 7200);
+    }
+
+    #[test]
+    fn poll_slows_down_when_asked() {
+        let (repo, server, flow) = mock_gitlab();
+        mount_polls(&repo, &server, json!({ "error": "slow_down" }));
+        let start = Instant::now();
+        assert_eq!(poll(&repo, &flow).unwrap()["access_token"], "abc");
+        assert!(start.elapsed() >= flow.slow_down_increment);
+    }
+
+    #[test]
+    fn poll_stops_when_the_code_expires() {
+        let (repo, server, flow) = mock_gitlab();
+        mount_polls(&repo, &server, json!({ "error": "expired_token" }));
+        let Err(Error::Request(message)) = poll(&repo, &flow) else {
+            panic!("an expired code should be an error");
+        };
+        assert_eq!(
+            message,
+            "The code expired before it was entered.  Run 'add-remote login gitlab' again."
+        );
+    }
+
+    #[test]
+    fn poll_stops_when_access_is_denied() {
+        let (repo, server, flow) = mock_gitlab();
+        mount_polls(&repo, &server, json!({ "error": "access_denied" }));
+        let Err(Error::Request(message)) = poll(&repo, &flow) else {
+            panic!("denied access should be an error");
+        };
+        assert_eq!(message, "Access to GitLab was denied.");
+    }
+
+    #[test]
+    fn refresh_token() {
+        let (repo, server, flow) = mock_gitlab();
+        let refresh = || {
+            Mock::given(method("POST"))
+                .and(path("/oauth/token"))
+                .and(body_string_contains("grant_type=refresh_token"))
+                .and(body_string_contains("client_id=client"))
+        };
+        let refreshed = json!({ "access_token": "new", "refresh_token": "r2", "expires_in": 7200 });
+        let rejected = json!({ "error": "invalid_grant" });
+        repo.runtime.block_on(async {
+            refresh()
+                .and(body_string_contains("refresh_token=r1"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(refreshed))
+                .expect(1)
+                .mount(&server)
+                .await;
+            refresh()
+                .and(body_string_contains("refresh_token=old"))
+                .respond_with(ResponseTemplate::new(400).set_body_json(rejected))
+                .expect(1)
+                .mount(&server)
+                .await;
+        });
+
+        let details =
+            |refresh_token| json!({ "client_id": "client", "refresh_token": refresh_token });
+        let tokens = repo.refresh_token(&flow, &details("r1")).unwrap();
+        assert_eq!(tokens["access_token"], "new");
+        assert_eq!(tokens["refresh_token"], "r2");
+        let Err(Error::UnexpectedResponse { reason, .. }) =
+            repo.refresh_token(&flow, &details("old"))
+        else {
+            panic!("a rejected refresh token should be an error");
+        };
+        assert!(reason.contains("invalid_grant"));
+    }
+}
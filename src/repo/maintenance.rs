@@ -0,0 +1,190 @@
+use super::Repo;
+use crate::{
+    error::Error,
+    input_getter::{is_help_request, parse_bool},
+};
+use colour::{green_ln, red_ln, yellow, yellow_ln};
+use std::{fs, path::Path};
+
+impl Repo {
+    /// Offers to register the current repository with `git maintenance`, whose hourly prefetch
+    /// task keeps the remotes' branches up to date in the background.  Nothing is offered if the
+    /// repository is already registered, if `add-remote.offerMaintenance` is false, or if
+    /// `--no-input` or `--dry-run` was passed.
+    pub fn offer_maintenance(&self) -> Result<(), Error> {
+        if self.options.no_input
+            || self.options.dry_run
+            || self.get_bool_from_gitconfig("add-remote.offerMaintenance") == Some(false)
+            || self.is_registered_for_maintenance()?
+            || !self.confirm_maintenance()?
+        {
+            return Ok(());
+        }
+        self.register_for_maintenance()
+    }
+
+    /// Runs `git maintenance start`, falling back to `git maintenance register` if scheduling
+    /// fails.  Failures are reported but not returned, since the remote has been added by now.
+    fn register_for_maintenance(&self) -> Result<(), Error> {
+        let output = self.output_of(self.git.command().args(["maintenance", "start"]))?;
+        if output.status.success() {
+            green_ln!(
+                "Registered this repository for background maintenance, including prefetching"
+            );
+            return Ok(());
+        }
+        // Scheduling can fail, e.g. if there's no cron, systemd or launchd available, but the
+        // repository can still be registered so that any later `git maintenance run --schedule`
+        // includes it.
+        yellow_ln!(
+            "Failed to schedule background maintenance: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        let output = self.output_of(self.git.command().args(["maintenance", "register"]))?;
+        if output.status.success() {
+            yellow_ln!(
+                "Registered this repository for maintenance, but it will only run once it's \
+                 scheduled, e.g. by running 'git maintenance start' or by calling 'git maintenance \
+                 run --schedule=hourly' from a scheduler."
+            );
+        } else {
+            red_ln!(
+                "Failed to register this repository for maintenance: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether the current repository is listed under `maintenance.repo` in Git config.
+    fn is_registered_for_maintenance(&self) -> Result<bool, Error> {
+        let top_level = self.git_output(&["rev-parse", "--show-toplevel"])?;
+        let Ok(top_level) = fs::canonicalize(top_level.trim()) else {
+            return Ok(false);
+        };
+        Ok(self
+            .get_all_from_gitconfig("maintenance.repo")
+            .iter()
+            .any(|repo| fs::canonicalize(Path::new(repo)).is_ok_and(|repo| repo == top_level)))
+    }
+
+    /// Asks the user whether to register the current repository with `git maintenance`.
+    fn confirm_maintenance(&self) -> Result<bool, Error> {
+        loop {
+            if self.options.plain {
+                println!(
+                    "Do you want Git to fetch from the remotes in the background?  Enter 'y', 'n' \
+                     or '?' for help.  The default is 'n'."
+                );
+            } else {
+                yellow!("Keep the remotes up to date with background fetches? [y/N/?]: ");
+            }
+            let input = self.read_answer(true)?;
+            if is_help_request(&input) {
+                println!(
+                    "Entering 'y' runs 'git maintenance start', which registers this repository \
+                     for Git's background maintenance and schedules it.  Its prefetch task fetches \
+                     from every remote hourly into 'refs/prefetch/', so later fetches are quick.  \
+                     To stop being asked, set add-remote.offerMaintenance to false."
+                );
+                println!("The default is 'n'.");
+                continue;
+            }
+            match parse_bool(&input, Some(false)) {
+                Err(error) => red_ln!("{}", error),
+                Ok(register) => return Ok(register),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Repo;
+    use crate::{error::Error, repo::git_runner::FakeGit};
+    use std::env;
+
+    /// Returns the Git commands run which start with `git maintenance`.
+    fn maintenance_commands(git: &FakeGit) -> Vec<String> {
+        git.commands()
+            .into_iter()
+            .filter(|command| command.starts_with("maintenance "))
+            .collect()
+    }
+
+    #[test]
+    fn not_offered() {
+        let git = FakeGit::default();
+        let mut repo = Repo::with_git(&git);
+        repo.options.no_input = true;
+        repo.offer_maintenance().unwrap();
+        repo.options.no_input = false;
+        repo.options.dry_run = true;
+        repo.offer_maintenance().unwrap();
+        assert!(git.commands().is_empty());
+
+        let git = FakeGit::default();
+        // Git canonicalises "False", "off", "no" and "0" to this.
+        let _ = git.reply(
+            &["config", "--type=bool", "add-remote.offerMaintenance"],
+            "false\n",
+        );
+        Repo::with_git(&git).offer_maintenance().unwrap();
+        assert!(maintenance_commands(&git).is_empty());
+    }
+
+    #[test]
+    fn not_offered_if_registered() {
+        let top_level = env::current_dir().unwrap();
+        let top_level = top_level.to_str().unwrap();
+        let git = FakeGit::default();
+        let _ = git
+            .reply(&["rev-parse", "--show-toplevel"], &format!("{top_level}\n"))
+            .reply(
+                &["config", "--get-all", "maintenance.repo"],
+                &format!("/elsewhere\n{top_level}/.\n"),
+            );
+        let repo = Repo::with_git(&git);
+        assert!(repo.is_registered_for_maintenance().unwrap());
+        repo.offer_maintenance().unwrap();
+        assert!(maintenance_commands(&git).is_empty());
+
+        let git = FakeGit::default();
+        let _ = git
+            .reply(&["rev-parse", "--show-toplevel"], &format!("{top_level}\n"))
+            .reply(&["config", "--get-all", "maintenance.repo"], "/elsewhere\n");
+        assert!(!Repo::with_git(&git)
+            .is_registered_for_maintenance()
+            .unwrap());
+
+        let git = FakeGit::default();
+        let _ = git.fail(
+            &["rev-parse", "--show-toplevel"],
+            "fatal: not a git repository\n",
+        );
+        assert!(matches!(
+            Repo::with_git(&git).offer_maintenance(),
+            Err(Error::Git(_))
+        ));
+    }
+
+    #[test]
+    fn register() {
+        let git = FakeGit::default();
+        Repo::with_git(&git).register_for_maintenance().unwrap();
+        assert_eq!(maintenance_commands(&git), ["maintenance start"]);
+
+        // If scheduling fails, the repository is still registered.
+        let git = FakeGit::default();
+        let _ = git.fail(&["maintenance", "start"], "error: no scheduler\n");
+        Repo::with_git(&git).register_for_maintenance().unwrap();
+        assert_eq!(
+            maintenance_commands(&git),
+            ["maintenance start", "maintenance register"]
+        );
+
+        // Failing to register is reported rather than returned.
+        let _ = git.fail(&["maintenance", "register"], "error: no config\n");
+        Repo::with_git(&git).register_for_maintenance().unwrap();
+    }
+}
@@ -0,0 +1,653 @@
+use super::git_runner::{describe, exit_status, GitRunner, SystemGit};
+use git2::{Config, ConfigLevel, ErrorCode, Repository};
+use regex::Regex;
+use std::{
+    env, io,
+    iter::Peekable,
+    path::PathBuf,
+    process::{Command, ExitStatus, Output},
+    str::Chars,
+};
+use tracing::debug;
+
+/// Answers the Git commands which `add-remote` runs most via libgit2 rather than by running Git,
+/// which is much quicker and needs no Git binary: listing the remotes and getting their URLs,
+/// reading Git config, and adding remotes and setting their URLs.  Any other command, e.g. `fetch`,
+/// is passed on to the Git binary, failing if there's none.
+///
+/// The repository is found once, as Git would find it, then opened afresh for each command so that
+/// changes made by Git in the meantime are seen.  libgit2 doesn't apply the config passed on the
+/// command line via `-c` (or in `GIT_CONFIG_PARAMETERS` or `GIT_CONFIG_COUNT`), so that's applied
+/// here on top of the config files, in the same order as Git applies it.
+#[derive(Debug)]
+pub(super) struct NativeGit {
+    /// The path to the repository's Git directory, or `None` if not in a repository.
+    git_dir: Option<PathBuf>,
+    /// The canonical keys and values of the config passed on the command line.
+    overrides: Vec<(String, String)>,
+    /// Runs the commands which aren't answered natively, or `None` if Git isn't installed.
+    system: Option<SystemGit>,
+}
+
+impl NativeGit {
+    /// Returns a `NativeGit` for the repository named by `GIT_DIR`, or else the one containing the
+    /// current directory, applying the config passed on the command line as found in the
+    /// environment.
+    pub(super) fn new() -> Self {
+        let git_dir = Repository::open_from_env()
+            .ok()
+            .map(|repo| repo.path().to_path_buf());
+        let var = |name: &str| env::var(name).ok();
+        let count: usize = var("GIT_CONFIG_COUNT")
+            .and_then(|count| count.parse().ok())
+            .unwrap_or_default();
+        let mut overrides: Vec<(String, String)> = (0..count)
+            .filter_map(|index| {
+                let key = var(&format!("GIT_CONFIG_KEY_{index}"))?;
+                let value = var(&format!("GIT_CONFIG_VALUE_{index}")).unwrap_or_default();
+                Some((key, value))
+            })
+            .collect();
+        overrides.extend(parse_parameters(
+            &var("GIT_CONFIG_PARAMETERS").unwrap_or_default(),
+        ));
+        NativeGit {
+            git_dir,
+            overrides: overrides
+                .into_iter()
+                .filter_map(|(key, value)| Some((canonical_key(&key)?, value)))
+                .collect(),
+            system: SystemGit::new(),
+        }
+    }
+
+    /// Returns the Git binary, or an error if it's not installed.
+    fn system(&self) -> io::Result<&SystemGit> {
+        self.system.as_ref().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "Git isn't installed, and is needed for this",
+            )
+        })
+    }
+
+    /// Returns the output Git would give for `git <args>`, or `None` if it's not a command which
+    /// is answered natively.
+    fn run(&self, args: &[&str]) -> Option<Output> {
+        let output = match *args {
+            ["remote"] => self.remote_names(),
+            ["remote", "get-url", "--push", alias] => self.remote_url(alias, true),
+            ["remote", "get-url", "--all", alias] => self.remote_url(alias, false),
+            ["remote", "add", "-t", branch, alias, url] => {
+                self.add_remote(alias, url, Some(branch))
+            }
+            ["remote", "add", alias, url] => self.add_remote(alias, url, None),
+            ["remote", "set-url", "--push", alias, url] => self.set_remote_url(alias, url, true)?,
+            ["remote", "set-url", alias, url] => self.set_remote_url(alias, url, false)?,
+            ["config", "--get-all", key] => values(&self.get_all(key)),
+            ["config", "--get-regexp", regex] => self.get_regexp(regex),
+            ["config", "--type=bool", key] => self.get_bool(key),
+            ["config", "--type=path", key] => value(self.get_path(key)),
+            ["config", "--local", key] => value(self.get_in_scope(&[ConfigLevel::Local], key)),
+            // As with Git, the global scope includes `$XDG_CONFIG_HOME/git/config`, which
+            // `~/.gitconfig` overrides.
+            ["config", "--global", key] => {
+                value(self.get_in_scope(&[ConfigLevel::Global, ConfigLevel::XDG], key))
+            }
+            ["config", key] if !key.starts_with('-') => value(self.get(key)),
+            _ => return None,
+        };
+        Some(output)
+    }
+
+    /// Opens the repository.
+    fn open(&self) -> Result<Repository, Output> {
+        let not_a_repository = || {
+            failure(
+                128,
+                "fatal: not a git repository (or any of the parent directories): .git",
+            )
+        };
+        let git_dir = self.git_dir.as_ref().ok_or_else(not_a_repository)?;
+        Repository::open(git_dir).map_err(|_| not_a_repository())
+    }
+
+    /// Lists the names of the remotes in alphabetical order, as per `git remote`.
+    fn remote_names(&self) -> Output {
+        let repo = match self.open() {
+            Ok(repo) => repo,
+            Err(output) => return output,
+        };
+        match repo.remotes() {
+            Ok(names) => {
+                let mut names: Vec<&str> = names.iter().flatten().collect();
+                names.sort_unstable();
+                success(lines(&names))
+            }
+            Err(error) => failure(1, &format!("error: {}", error.message())),
+        }
+    }
+
+    /// Prints the URLs which the remote `alias` fetches from, or if `push` is true, pushes to, as
+    /// per `git remote get-url --all` or `git remote get-url --push`.
+    fn remote_url(&self, alias: &str, push: bool) -> Output {
+        let repo = match self.open() {
+            Ok(repo) => repo,
+            Err(output) => return output,
+        };
+        let Ok(remote) = repo.find_remote(alias) else {
+            return no_such_remote(alias);
+        };
+        let url = if push {
+            remote.pushurl().or_else(|| remote.url())
+        } else {
+            remote.url()
+        };
+        let mut urls = vec![url.unwrap_or_default().to_string()];
+        if !push {
+            // Only the first URL is rewritten by `url.<base>.insteadOf`, as it's the one fetched
+            // from, so the others are listed as they're set.
+            urls.extend(
+                self.get_all(&format!("remote.{alias}.url"))
+                    .into_iter()
+                    .skip(1),
+            );
+        }
+        values(&urls)
+    }
+
+    /// Adds the remote `alias` fetching from `url`, as per `git remote add [-t <branch>]`.  If
+    /// `branch` is set, only that branch is fetched.
+    fn add_remote(&self, alias: &str, url: &str, branch: Option<&str>) -> Output {
+        let repo = match self.open() {
+            Ok(repo) => repo,
+            Err(output) => return output,
+        };
+        if repo.find_remote(alias).is_ok() {
+            return failure(3, &format!("error: remote {alias} already exists."));
+        }
+        let result = match branch {
+            Some(branch) => {
+                let refspec = format!("+refs/heads/{branch}:refs/remotes/{alias}/{branch}");
+                repo.remote_with_fetch(alias, url, &refspec)
+            }
+            None => repo.remote(alias, url),
+        };
+        match result {
+            Ok(_) => success(String::new()),
+            Err(error) => failure(1, &format!("error: {}", error.message())),
+        }
+    }
+
+    /// Sets the URL which the remote `alias` fetches from, or if `push` is true, replaces the URLs
+    /// it pushes to, as per `git remote set-url [--push]`.  Returns `None` if the remote fetches
+    /// from several URLs, since only Git knows which of them to change.
+    fn set_remote_url(&self, alias: &str, url: &str, push: bool) -> Option<Output> {
+        let repo = match self.open() {
+            Ok(repo) => repo,
+            Err(output) => return Some(output),
+        };
+        if repo.find_remote(alias).is_err() {
+            return Some(no_such_remote(alias));
+        }
+        let key = format!("remote.{alias}.{}", if push { "pushurl" } else { "url" });
+        if !push && self.get_all(&key).len() > 1 {
+            return None;
+        }
+        let result = repo
+            .config()
+            .and_then(|config| config.open_level(ConfigLevel::Local))
+            .and_then(|mut config| {
+                match config.remove_multivar(&key, ".*") {
+                    Err(error) if error.code() != ErrorCode::NotFound => return Err(error),
+                    _ => (),
+                }
+                config.set_str(&key, url)
+            });
+        Some(match result {
+            Ok(()) => success(String::new()),
+            Err(error) => failure(1, &format!("error: {}", error.message())),
+        })
+    }
+
+    /// Returns the value of `key`, as per `git config <key>`.
+    fn get(&self, key: &str) -> Option<String> {
+        if let Some(value) = self.overridden_values(key).last() {
+            return Some(value.clone());
+        }
+        self.config()?.get_string(key).ok()
+    }
+
+    /// Returns the value of `key` with a leading `~/` expanded to the user's home directory, as
+    /// per `git config --type=path <key>`.
+    fn get_path(&self, key: &str) -> Option<String> {
+        if let Some(value) = self.overridden_values(key).last() {
+            return Some(expand_home(value));
+        }
+        let path = self.config()?.get_path(key).ok()?;
+        Some(path.to_string_lossy().to_string())
+    }
+
+    /// Prints the value of `key` as `true` or `false`, as per `git config --type=bool <key>`.
+    /// Fails as Git does if the value isn't a boolean.
+    fn get_bool(&self, key: &str) -> Output {
+        let parsed = match self.overridden_values(key).last() {
+            Some(value) => Some(Config::parse_bool(value.as_str()).map_err(|_| value.clone())),
+            None => self.config().and_then(|config| match config.get_bool(key) {
+                Ok(parsed) => Some(Ok(parsed)),
+                Err(error) if error.code() == ErrorCode::NotFound => None,
+                Err(_) => Some(Err(config.get_string(key).unwrap_or_default())),
+            }),
+        };
+        match parsed {
+            None => failure(1, ""),
+            Some(Ok(parsed)) => success(format!("{parsed}\n")),
+            Some(Err(invalid)) => failure(
+                128,
+                &format!("fatal: bad boolean config value '{invalid}' for '{key}'"),
+            ),
+        }
+    }
+
+    /// Returns every value of `key`, as per `git config --get-all <key>`.
+    fn get_all(&self, key: &str) -> Vec<String> {
+        let mut values = Vec::new();
+        if let Some(config) = self.config() {
+            if let Ok(mut entries) = config.multivar(key, None) {
+                while let Some(Ok(entry)) = entries.next() {
+                    values.push(entry.value().unwrap_or_default().to_string());
+                }
+            }
+        }
+        values.extend(self.overridden_values(key).cloned());
+        values
+    }
+
+    /// Prints every canonical key which matches `regex` along with its value, as per
+    /// `git config --get-regexp <regex>`.  Keys without a value are printed alone.
+    fn get_regexp(&self, regex: &str) -> Output {
+        let mut lines = Vec::new();
+        if let Some(config) = self.config() {
+            if let Ok(mut matches) = config.entries(Some(regex)) {
+                while let Some(Ok(entry)) = matches.next() {
+                    let Some(name) = entry.name() else {
+                        continue;
+                    };
+                    lines.push(match entry.value() {
+                        Some(value) if entry.has_value() => format!("{name} {value}"),
+                        _ => name.to_string(),
+                    });
+                }
+            }
+        }
+        if let Ok(regex) = Regex::new(regex) {
+            lines.extend(
+                (self.overrides.iter())
+                    .filter(|(key, _)| regex.is_match(key))
+                    .map(|(key, value)| format!("{key} {value}")),
+            );
+        }
+        values(&lines)
+    }
+
+    /// Returns the value of `key` in the config files of `levels` alone, the first taking
+    /// precedence, as per `git config --local <key>` or `git config --global <key>`.
+    fn get_in_scope(&self, levels: &[ConfigLevel], key: &str) -> Option<String> {
+        let config = self.config()?;
+        levels.iter().find_map(|&level| {
+            let config = config.open_level(level).ok()?;
+            config.get_string(key).ok()
+        })
+    }
+
+    /// Returns a snapshot of the config files which apply to the repository, or which apply
+    /// globally if not in one.
+    fn config(&self) -> Option<Config> {
+        let mut config = match self.open() {
+            Ok(repo) => repo.config().ok()?,
+            Err(_) => Config::open_default().ok()?,
+        };
+        config.snapshot().ok()
+    }
+
+    /// Returns the values passed on the command line for `key`, in the order Git applies them.
+    fn overridden_values<'a>(&'a self, key: &str) -> impl Iterator<Item = &'a String> {
+        let key = canonical_key(key);
+        self.overrides
+            .iter()
+            .filter(move |(overridden, _)| Some(overridden) == key.as_ref())
+            .map(|(_, value)| value)
+    }
+}
+
+impl GitRunner for NativeGit {
+    fn command(&self) -> Command {
+        self.system
+            .as_ref()
+            .map_or_else(|| Command::new("git"), SystemGit::command)
+    }
+
+    fn output(&self, command: &mut Command) -> io::Result<Output> {
+        // A command given its own directory or environment is left to Git, which applies them.
+        if command.get_current_dir().is_none() && command.get_envs().next().is_none() {
+            let args: Vec<String> = command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect();
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            if let Some(output) = self.run(&args) {
+                debug!(args = %describe(command), status = %output.status, "answered natively");
+                return Ok(output);
+            }
+        }
+        self.system()?.output(command)
+    }
+
+    fn status(&self, command: &mut Command) -> io::Result<ExitStatus> {
+        self.system()?.status(command)
+    }
+}
+
+/// Returns the output of a Git command which succeeded and printed `stdout`.
+fn success(stdout: String) -> Output {
+    Output {
+        status: exit_status(0),
+        stdout: stdout.into_bytes(),
+        stderr: Vec::new(),
+    }
+}
+
+/// Returns the output of a Git command which exited with `code` and printed the line `stderr`.
+fn failure(code: u8, stderr: &str) -> Output {
+    let stderr = if stderr.is_empty() {
+        String::new()
+    } else {
+        format!("{stderr}\n")
+    };
+    Output {
+        status: exit_status(code),
+        stdout: Vec::new(),
+        stderr: stderr.into_bytes(),
+    }
+}
+
+/// Returns the output of Git when asked for the URL of the nonexistent remote `alias`.
+fn no_such_remote(alias: &str) -> Output {
+    failure(2, &format!("error: No such remote '{alias}'"))
+}
+
+/// Returns the output of `git config` when printing `value`, failing as Git does if it's unset.
+fn value(value: Option<String>) -> Output {
+    values(&value.into_iter().collect::<Vec<_>>())
+}
+
+/// Returns the output of Git when printing each of `lines`, failing as `git config` does if there
+/// are none.
+fn values(lines: &[String]) -> Output {
+    if lines.is_empty() {
+        return failure(1, "");
+    }
+    success(self::lines(lines))
+}
+
+/// Returns each of `lines` followed by a newline.
+fn lines<T: AsRef<str>>(lines: &[T]) -> String {
+    lines
+        .iter()
+        .flat_map(|line| [line.as_ref(), "\n"])
+        .collect()
+}
+
+/// Returns `key` as Git compares it, i.e. with its section and variable name lowercased but any
+/// subsection left as is, or `None` if it has no section.
+fn canonical_key(key: &str) -> Option<String> {
+    let (section, rest) = key.split_once('.')?;
+    let (subsection, name) = match rest.rsplit_once('.') {
+        Some((subsection, name)) => (Some(subsection), name),
+        None => (None, rest),
+    };
+    if section.is_empty() || name.is_empty() {
+        return None;
+    }
+    Some(match subsection {
+        Some(subsection) => format!(
+            "{}.{subsection}.{}",
+            section.to_lowercase(),
+            name.to_lowercase()
+        ),
+        None => format!("{}.{}", section.to_lowercase(), name.to_lowercase()),
+    })
+}
+
+/// Returns `path` with a leading `~/` replaced by the user's home directory.
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{rest}", home.trim_end_matches('/')),
+        _ => path.to_string(),
+    }
+}
+
+/// Parses the keys and values which Git passes to its subcommands in `GIT_CONFIG_PARAMETERS`,
+/// e.g. `'core.editor'='vim' 'user.name'='O'\''Brien'`.  Entries are either a quoted key and
+/// value separated by `=`, or, as written by older versions of Git, a single quoted `key=value`.
+/// A key without a value has the empty value.  Parsing stops at the first malformed entry.
+fn parse_parameters(parameters: &str) -> Vec<(String, String)> {
+    let mut chars = parameters.chars().peekable();
+    let mut entries = Vec::new();
+    loop {
+        while chars.next_if(|char| char.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            return entries;
+        }
+        let Some(key) = dequote(&mut chars) else {
+            return entries;
+        };
+        if chars.next_if_eq(&'=').is_some() {
+            let value = if chars.peek() == Some(&'\'') {
+                let Some(value) = dequote(&mut chars) else {
+                    return entries;
+                };
+                value
+            } else {
+                String::new()
+            };
+            entries.push((key, value));
+        } else {
+            let (key, value) = key.split_once('=').unwrap_or((&key, ""));
+            entries.push((key.to_string(), value.to_string()));
+        }
+    }
+}
+
+/// Reads a single-quoted string from `chars`, in which a quote is written as `'\''`, returning
+/// `None` if it's malformed.
+fn dequote(chars: &mut Peekable<Chars>) -> Option<String> {
+    let mut value = String::new();
+    let _ = chars.next_if_eq(&'\'')?;
+    loop {
+        match chars.next()? {
+            '\'' => {
+                if chars.next_if_eq(&'\\').is_none() {
+                    return Some(value);
+                }
+                value.push(chars.next()?);
+                let _ = chars.next_if_eq(&'\'')?;
+            }
+            char => value.push(char),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonical_key, parse_parameters, NativeGit};
+    use crate::repo::git_runner::GitRunner;
+    use git2::Repository;
+    use std::{env, fs, io, process};
+
+    #[test]
+    fn canonical_keys() {
+        assert_eq!(canonical_key("Core.Editor").as_deref(), Some("core.editor"));
+        assert_eq!(
+            canonical_key("add-remote.forkAlias.Bob").as_deref(),
+            Some("add-remote.forkAlias.bob")
+        );
+        assert_eq!(
+            canonical_key("URL.https://Example.com/.insteadOf").as_deref(),
+            Some("url.https://Example.com/.insteadof")
+        );
+        assert!(canonical_key("core").is_none());
+        assert!(canonical_key(".editor").is_none());
+        assert!(canonical_key("core.").is_none());
+    }
+
+    #[test]
+    fn parse_git_config_parameters() {
+        let owned = |entries: &[(&str, &str)]| {
+            entries
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            parse_parameters(r"'a.B.c'='x' 'q.w'='it'\''s' 'flag.x'= 'empty.x'=''"),
+            owned(&[
+                ("a.B.c", "x"),
+                ("q.w", "it's"),
+                ("flag.x", ""),
+                ("empty.x", "")
+            ])
+        );
+        assert_eq!(
+            parse_parameters(r"'core.editor=vim' 'flag.x'"),
+            owned(&[("core.editor", "vim"), ("flag.x", "")])
+        );
+        assert_eq!(
+            parse_parameters(r"'a.b'='c' 'unterminated"),
+            owned(&[("a.b", "c")])
+        );
+        assert!(parse_parameters("").is_empty());
+    }
+
+    #[test]
+    fn answers_without_git() {
+        let dir = env::temp_dir().join(format!("add-remote-native-git-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let repo = Repository::init(&dir).unwrap();
+        let git = NativeGit {
+            git_dir: Some(repo.path().to_path_buf()),
+            overrides: vec![("add-remote.nativeTest.flag".to_string(), "off".to_string())],
+            system: None,
+        };
+        let run = |args: &[&str]| {
+            let output = git.output(git.command().args(args)).unwrap();
+            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            (output.status.code().unwrap(), stdout + &stderr)
+        };
+        let ok = |text: &str| (0, text.to_string());
+
+        assert_eq!(run(&["remote"]), ok(""));
+        assert_eq!(
+            run(&["remote", "add", "bob", "git@github.com:bob/r.git"]),
+            ok("")
+        );
+        assert_eq!(
+            run(&["remote", "add", "bob", "git@github.com:bob/r.git"]),
+            (3, "error: remote bob already exists.\n".to_string())
+        );
+        assert_eq!(
+            run(&[
+                "remote",
+                "add",
+                "-t",
+                "main",
+                "al",
+                "https://github.com/al/r"
+            ]),
+            ok("")
+        );
+        assert_eq!(run(&["remote"]), ok("al\nbob\n"));
+        assert_eq!(
+            run(&["config", "--get-all", "remote.al.fetch"]),
+            ok("+refs/heads/main:refs/remotes/al/main\n")
+        );
+        assert_eq!(
+            run(&["remote", "get-url", "--all", "bob"]),
+            ok("git@github.com:bob/r.git\n")
+        );
+        assert_eq!(
+            run(&["remote", "get-url", "--push", "bob"]),
+            ok("git@github.com:bob/r.git\n")
+        );
+        assert_eq!(
+            run(&["remote", "set-url", "--push", "bob", "no_push"]),
+            ok("")
+        );
+        assert_eq!(run(&["remote", "set-url", "--push", "bob", "nope"]), ok(""));
+        assert_eq!(run(&["remote", "get-url", "--push", "bob"]), ok("nope\n"));
+        assert_eq!(
+            run(&["remote", "set-url", "al", "git@github.com:al/r.git"]),
+            ok("")
+        );
+        assert_eq!(
+            run(&["config", "--local", "remote.al.url"]),
+            ok("git@github.com:al/r.git\n")
+        );
+        assert_eq!(
+            run(&["remote", "set-url", "carol", "x"]),
+            (2, "error: No such remote 'carol'\n".to_string())
+        );
+        assert_eq!(
+            run(&["config", "--get-regexp", r"^remote\.bob\."]),
+            ok("remote.bob.url git@github.com:bob/r.git\n\
+                remote.bob.fetch +refs/heads/*:refs/remotes/bob/*\n\
+                remote.bob.pushurl nope\n")
+        );
+
+        let mut config = repo.config().unwrap();
+        config.set_str("add-remote.nativeTest.yes", "yes").unwrap();
+        config
+            .set_str("add-remote.nativeTest.bad", "maybe")
+            .unwrap();
+        assert_eq!(
+            run(&["config", "--type=bool", "add-remote.nativeTest.yes"]),
+            ok("true\n")
+        );
+        assert_eq!(
+            run(&["config", "--type=bool", "add-remote.nativeTest.flag"]),
+            ok("false\n")
+        );
+        assert_eq!(
+            run(&["config", "--type=bool", "add-remote.nativeTest.bad"]),
+            (
+                128,
+                "fatal: bad boolean config value 'maybe' for 'add-remote.nativeTest.bad'\n"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            run(&["config", "add-remote.nativeTest.unset"]),
+            (1, String::new())
+        );
+        assert_eq!(
+            run(&["config", "--get-regexp", r"^add-remote\.nativeTest\.flag$"]),
+            ok("add-remote.nativeTest.flag off\n")
+        );
+
+        // Anything else needs the Git binary.
+        let error = git
+            .output(git.command().args(["fetch", "bob"]))
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+        let outside = NativeGit {
+            git_dir: None,
+            overrides: Vec::new(),
+            system: None,
+        };
+        let output = outside.output(outside.command().arg("remote")).unwrap();
+        assert_eq!(output.status.code(), Some(128));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
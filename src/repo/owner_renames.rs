@@ -0,0 +1,278 @@
+use super::{Fork, Name, Owner, Repo, Url, OWNER_MARKER};
+use crate::{
+    cli::AliasScope,
+    error::Error,
+    git_url::{self, GitUrl},
+    input_getter::{is_help_request, parse_bool},
+};
+use colour::{green_ln, red_ln, yellow, yellow_ln};
+
+impl Repo {
+    /// Finds the local remotes whose owner isn't among `forks`, but whose repository the forge
+    /// reports under a different owner, i.e. whose owner has been renamed.  For each, offers to
+    /// migrate the owner's `add-remote.forkAlias` entry to the new name and, if the remote is
+    /// managed by `add-remote`, its URL and recorded owner too.
+    pub(super) fn migrate_renamed_owners(&mut self, forks: &[Fork]) -> Result<(), Error> {
+        // Only a subset of the forks is known when listing those with open pull requests.
+        if self.options.from_prs {
+            return Ok(());
+        }
+        let renames: Vec<(Owner, Owner, String)> = self
+            .local_remotes
+            .iter()
+            .filter(|(owner, _)| {
+                **owner != self.main_fork_owner && !forks.iter().any(|fork| fork.owner == **owner)
+            })
+            .filter_map(|(owner, (name, alias, url))| {
+                let new_owner = self.current_owner(owner, name, url)?;
+                Some((owner.clone(), new_owner, alias.0.clone()))
+            })
+            .collect();
+
+        let mut migrated = false;
+        for (old_owner, new_owner, alias) in renames {
+            yellow_ln!(
+                "'{}' appears to have been renamed to '{}'.",
+                old_owner.0,
+                new_owner.0
+            );
+            if self.options.no_input || !self.confirm_owner_migration(&old_owner, &new_owner)? {
+                continue;
+            }
+            let changes = self.migrate_owner(&old_owner, &new_owner, &alias)?;
+            if self.options.dry_run {
+                continue;
+            }
+            for change in changes {
+                green_ln!("{}", change);
+            }
+            if let Some(details) = self.local_remotes.remove(&old_owner) {
+                let _ = self.local_remotes.insert(new_owner, details);
+            }
+            migrated = true;
+        }
+        if migrated {
+            self.config = self.read_config_snapshot();
+        }
+        Ok(())
+    }
+
+    /// Queries the forge for the repository `owner/name` and returns its owner if it differs from
+    /// `owner`.  Remotes hosted on a different forge to the main fork/source are skipped.
+    fn current_owner(&self, owner: &Owner, name: &Name, url: &Url) -> Option<Owner> {
+        if url.forge != self.main_fork_url.forge || url.host() != self.main_fork_url.host() {
+            return None;
+        }
+        let current = self.forge().current_owner(self, owner, name)?;
+        (current != *owner).then_some(current)
+    }
+
+    /// Moves `add-remote.forkAlias.<old_owner>` to `add-remote.forkAlias.<new_owner>` in whichever
+    /// Git config files it's set, and if the remote `alias` is managed by `add-remote`, replaces
+    /// the old owner in its fetch URL and recorded owner.  Returns a description of each change.
+    /// With `--dry-run`, the Git commands which would make the changes are printed instead.
+    fn migrate_owner(
+        &self,
+        old_owner: &Owner,
+        new_owner: &Owner,
+        alias: &str,
+    ) -> Result<Vec<String>, Error> {
+        let mut changes = Vec::new();
+        let old_key = format!("add-remote.forkAlias.{}", old_owner.0);
+        let new_key = format!("add-remote.forkAlias.{}", new_owner.0);
+        for scope in [AliasScope::Local, AliasScope::Global] {
+            let Some(fork_alias) = self.get_in_scope_from_gitconfig(scope, &old_key) else {
+                continue;
+            };
+            let fork_alias = fork_alias.trim();
+            let scope = scope.git_config_option();
+            self.run_git_unless_dry_run(&["config", scope, &new_key, fork_alias])?;
+            self.run_git_unless_dry_run(&["config", scope, "--unset", &old_key])?;
+            changes.push(format!("Moved {old_key} to {new_key}"));
+        }
+
+        let is_managed = self
+            .managed_remotes()
+            .iter()
+            .any(|(managed_alias, _)| managed_alias.0 == alias);
+        if !is_managed {
+            return Ok(changes);
+        }
+        if let Some(url) = self.get_fetch_url_from_gitconfig(alias) {
+            if let Some(new_url) = replace_owner_in_url(&url, old_owner, new_owner) {
+                let old_url_pattern = format!("^{}$", regex::escape(&url));
+                self.run_git_unless_dry_run(&[
+                    "remote",
+                    "set-url",
+                    alias,
+                    &new_url,
+                    &old_url_pattern,
+                ])?;
+                changes.push(format!(
+                    "Changed the URL of '{}' from {} to {}",
+                    alias,
+                    git_url::redact(&url),
+                    git_url::redact(&new_url)
+                ));
+            }
+        }
+        let owner_key = format!("remote.{alias}.{OWNER_MARKER}");
+        if self.get_from_gitconfig(&owner_key).is_some() {
+            self.run_git_unless_dry_run(&["config", "--local", &owner_key, &new_owner.0])?;
+        }
+        Ok(changes)
+    }
+
+    /// Asks the user whether to migrate the configuration for `old_owner` to `new_owner`.
+    fn confirm_owner_migration(&self, old_owner: &Owner, new_owner: &Owner) -> Result<bool, Error> {
+        loop {
+            if self.options.plain {
+                println!(
+                    "Do you want to update the configuration for {} to use {}?  Enter 'y', 'n' or \
+                     '?' for help.  The default is 'y'.",
+                    old_owner.0, new_owner.0
+                );
+            } else {
+                yellow!(
+                    "Update the configuration for '{}' to '{}'? [Y/n/?]: ",
+                    old_owner.0,
+                    new_owner.0
+                );
+            }
+            let input = self.read_answer(true)?;
+            if is_help_request(&input) {
+                println!(
+                    "GitLab/GitHub now lists this fork under '{}'.  Entering 'y' moves any \
+                     add-remote.forkAlias entry for '{}' to the new name, and if its remote was \
+                     added by 'add-remote', updates the remote's URL too.",
+                    new_owner.0, old_owner.0
+                );
+                println!("The default is 'y'.");
+                continue;
+            }
+            match parse_bool(&input, Some(true)) {
+                Err(error) => red_ln!("{}", error),
+                Ok(migrate) => return Ok(migrate),
+            }
+        }
+    }
+}
+
+/// Returns `url` with its owner replaced by `new_owner`, or `None` if its owner isn't `old_owner`
+/// or it can't be parsed.
+fn replace_owner_in_url(url: &str, old_owner: &Owner, new_owner: &Owner) -> Option<String> {
+    let parsed = GitUrl::parse(url)?;
+    let (owner, _) = parsed.path_without_extension().split_once('/')?;
+    if !owner.eq_ignore_ascii_case(&old_owner.0) {
+        return None;
+    }
+    // The owner is the first path segment, so it's the first occurrence of it following the host.
+    let host_end = url.to_ascii_lowercase().find(&parsed.host)? + parsed.host.len();
+    let owner_start = host_end + url[host_end..].find(owner)?;
+    Some(format!(
+        "{}{}{}",
+        &url[..owner_start],
+        new_owner.0,
+        &url[owner_start + owner.len()..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{replace_owner_in_url, Owner, Repo};
+    use crate::repo::git_runner::FakeGit;
+
+    /// Returns a `FakeGit` for a repository with the managed remote `oldie` whose owner, `OldName`,
+    /// has an alias configured in the local Git config.
+    fn renamed_owner_git() -> FakeGit {
+        let git = FakeGit::default();
+        let _ = git
+            .reply(
+                &["config", "--local", "add-remote.forkAlias.OldName"],
+                "oldie\n",
+            )
+            .fail(&["config", "--global", "add-remote.forkAlias.OldName"], "")
+            .reply(
+                &[
+                    "config",
+                    "--get-regexp",
+                    r"^remote\..*\.addremote(managed|owner)$",
+                ],
+                "remote.oldie.addremotemanaged true\nremote.oldie.addremoteowner OldName\n",
+            )
+            .reply(
+                &["config", "--get-all", "remote.oldie.url"],
+                "git@github.com:OldName/repo.git\n",
+            )
+            .reply(&["config", "remote.oldie.addRemoteOwner"], "OldName\n");
+        git
+    }
+
+    #[test]
+    fn migrate_owner() {
+        let git = renamed_owner_git();
+        let repo = Repo::with_git(&git);
+        let old = Owner("OldName".to_string());
+        let new = Owner("NewName".to_string());
+        let changes = repo.migrate_owner(&old, &new, "oldie").unwrap();
+        assert_eq!(changes.len(), 2);
+        let commands = git.commands();
+        for write in [
+            "config --local add-remote.forkAlias.NewName oldie",
+            "config --local --unset add-remote.forkAlias.OldName",
+            concat!(
+                "remote set-url oldie git@github.com:NewName/repo.git ",
+                r"^git@github\.com:OldName/repo\.git$"
+            ),
+            "config --local remote.oldie.addRemoteOwner NewName",
+        ] {
+            assert!(commands.contains(&write.to_string()), "{}", write);
+        }
+    }
+
+    #[test]
+    fn migrate_owner_dry_run() {
+        let git = renamed_owner_git();
+        let mut repo = Repo::with_git(&git);
+        repo.options.dry_run = true;
+        let old = Owner("OldName".to_string());
+        let new = Owner("NewName".to_string());
+        let _ = repo.migrate_owner(&old, &new, "oldie").unwrap();
+        // Only the reads are run.
+        assert_eq!(
+            git.commands(),
+            [
+                "config --local add-remote.forkAlias.OldName",
+                "config --global add-remote.forkAlias.OldName",
+                r"config --get-regexp ^remote\..*\.addremote(managed|owner)$",
+                "config --get-all remote.oldie.url",
+                "config remote.oldie.addRemoteOwner",
+            ]
+        );
+    }
+
+    #[test]
+    fn replace_owner() {
+        let old = Owner("OldName".to_string());
+        let new = Owner("new-name".to_string());
+        let replace = |url: &str| replace_owner_in_url(url, &old, &new);
+        assert_eq!(
+            replace("git@github.com:oldname/repo.git").as_deref(),
+            Some("git@github.com:new-name/repo.git")
+        );
+        assert_eq!(
+            replace("https://github.com/OldName/OldName.git").as_deref(),
+            Some("https://github.com/new-name/OldName.git")
+        );
+        assert_eq!(
+            replace("https://GitHub.com/oldname/repo").as_deref(),
+            Some("https://GitHub.com/new-name/repo")
+        );
+        assert_eq!(
+            replace("ssh://git@gitlab.com:22/OldName/sub/project").as_deref(),
+            Some("ssh://git@gitlab.com:22/new-name/sub/project")
+        );
+        assert!(replace("git@github.com:someone/repo.git").is_none());
+        assert!(replace("not a url").is_none());
+    }
+}
@@ -0,0 +1,168 @@
+use super::{
+    forge::{self, ForgeProvider, RepoState, RepoStatus},
+    Name, Owner, Repo,
+};
+use crate::{
+    error::Error,
+    input_getter::{is_help_request, parse_bool},
+};
+use colour::{green_ln, red_ln, yellow, yellow_ln};
+use std::{collections::HashSet, thread};
+
+impl Repo {
+    /// Queries the forges for the repository of each remote hosted on a known forge, up to `jobs`
+    /// at once, then offers to remove each remote whose repository has been deleted or archived.
+    /// Remotes whose repositories can't be checked, or which local branches track, are left alone.
+    /// With `--dry-run`, the Git commands are printed rather than run.
+    pub fn prune(&mut self) -> Result<(), Error> {
+        let mut checks = Vec::new();
+        for alias in self.get_local_remote_aliases()? {
+            let Some((owner, (name, alias, url))) =
+                Self::get_local_remote(&*self.git, &self.mirrors, &alias)
+            else {
+                continue;
+            };
+            match forge::new_provider(self, &url) {
+                Ok(forge) => checks.push((owner, name, alias, forge)),
+                Err(error) => yellow_ln!("Not checking '{}': {}", alias.0, error),
+            }
+        }
+        let Some((.., forge)) = checks.first() else {
+            return Err(Error::UnsupportedHost);
+        };
+        let api_url = forge.api_url();
+        self.configure_client(&api_url)?;
+
+        let repos: Vec<_> = checks
+            .iter()
+            .map(|(owner, name, _, forge)| (owner, name, forge.as_ref()))
+            .collect();
+        let statuses = self.query_repo_states(&repos);
+
+        let mut dead = Vec::new();
+        for ((owner, name, alias, _), status) in checks.iter().zip(statuses) {
+            let fork = format!("{}/{}", owner.0, name.0);
+            match status.map(|state| state.status) {
+                Ok(RepoStatus::Active) => (),
+                Ok(RepoStatus::Archived) => {
+                    dead.push((alias, format!("{fork} has been archived"), false));
+                }
+                Ok(RepoStatus::Missing) => {
+                    let reason = format!("{fork} no longer exists, or is private");
+                    dead.push((alias, reason, true));
+                }
+                Err(error) => yellow_ln!("Couldn't check '{}' ({}): {}", alias.0, fork, error),
+            }
+        }
+        if dead.is_empty() {
+            green_ln!(
+                "The repositories of all {} remotes checked are still active.",
+                checks.len()
+            );
+            return Ok(());
+        }
+        let tracked = self.tracked_remotes();
+        let (kept, dead): (Vec<_>, Vec<_>) = dead
+            .into_iter()
+            .partition(|(alias, ..)| tracked.contains(&alias.0));
+        for (alias, reason, _) in &kept {
+            yellow_ln!(
+                "Keeping '{}' since local branches track it: {}.",
+                alias.0,
+                reason
+            );
+        }
+        if dead.is_empty() {
+            return Ok(());
+        }
+        if self.options.no_input {
+            for (alias, reason, _) in &dead {
+                yellow_ln!("'{}': {}.", alias.0, reason);
+            }
+            return Err(Self::input_needed(
+                "Run 'add-remote prune' without '--no-input' to confirm each removal.",
+            ));
+        }
+        for (alias, reason, is_missing) in dead {
+            if self.confirm_prune(&alias.0, &reason, is_missing)? {
+                self.run_git_unless_dry_run(&["remote", "remove", &alias.0])?;
+                if !self.options.dry_run {
+                    green_ln!("Removed the remote '{}'", alias.0);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the names of the remotes which local branches track, i.e. the values of
+    /// `branch.<name>.remote`.  Removing one of these would leave its branches without an upstream.
+    fn tracked_remotes(&self) -> HashSet<String> {
+        self.get_regexp_from_gitconfig(r"^branch\..*\.remote$")
+            .into_iter()
+            .map(|(_, remote)| remote)
+            .collect()
+    }
+
+    /// Queries the forge of each of `repos` for its state, up to `jobs` at once.
+    pub(super) fn query_repo_states(
+        &self,
+        repos: &[(&Owner, &Name, &dyn ForgeProvider)],
+    ) -> Vec<Result<RepoState, String>> {
+        let mut states = Vec::new();
+        for batch in repos.chunks(self.jobs) {
+            states.extend(thread::scope(|scope| {
+                let handles: Vec<_> = batch
+                    .iter()
+                    .map(|&(owner, name, forge)| {
+                        scope.spawn(move || forge.repo_state(self, owner, name))
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("Failed to check a remote."))
+                    .collect::<Vec<_>>()
+            }));
+        }
+        states
+    }
+
+    /// Asks the user whether to remove the remote `alias`, whose repository is dead for `reason`.
+    /// The default is to remove it if `is_missing`, i.e. it's not merely archived.
+    fn confirm_prune(&self, alias: &str, reason: &str, is_missing: bool) -> Result<bool, Error> {
+        let (choices, default) = if is_missing {
+            ("Y/n/?", "y")
+        } else {
+            ("y/N/?", "n")
+        };
+        loop {
+            if self.options.plain {
+                println!(
+                    "{reason}.  Do you want to remove the remote {alias}?  Enter 'y', 'n' or '?' \
+                     for help.  The default is '{default}'."
+                );
+            } else {
+                yellow!("{}.  Remove '{}'? [{}]: ", reason, alias, choices);
+            }
+            let input = self.read_answer(true)?;
+            if is_help_request(&input) {
+                println!(
+                    "The forge reports that the repository which the remote '{alias}' fetches from \
+                     {}.  Entering 'y' removes the remote along with its remote-tracking branches, \
+                     as per 'git remote remove'.",
+                    if is_missing {
+                        "has been deleted (or made private, or it can't be seen without a token)"
+                    } else {
+                        "has been archived, so it's read-only, though its branches can still be \
+                         fetched"
+                    }
+                );
+                println!("The default is '{default}'.");
+                continue;
+            }
+            match parse_bool(&input, Some(is_missing)) {
+                Err(error) => red_ln!("{}", error),
+                Ok(remove) => return Ok(remove),
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::progress::Progress;
+use reqwest::{header::HeaderMap, StatusCode};
+use std::{
+    io::{self, IsTerminal, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{sync::Mutex, time};
+
+/// How many times a rate-limited request is retried before giving up.
+pub(super) const MAX_RETRIES: usize = 3;
+
+/// The longest wait for a rate limit to reset before giving up.  GitHub's limits reset hourly.
+const MAX_WAIT: Duration = Duration::from_mins(65);
+
+/// Held while waiting for a rate limit to reset, so that when several concurrent requests are
+/// rate-limited, only one countdown is shown and the rest retry once it ends.
+static WAITING: Mutex<()> = Mutex::const_new(());
+
+/// Returns how long to wait before retrying a request which received a response with `status` and
+/// `headers`, or `None` if the response isn't due to a rate limit, or if the limit won't reset
+/// within `MAX_WAIT`.  `now` is the current time in seconds since the Unix epoch.
+///
+/// A `Retry-After` header (sent by GitHub for secondary rate limits, and by GitLab) is honoured
+/// first.  Otherwise, if no requests remain, the wait lasts until the time given by
+/// `X-RateLimit-Reset` (GitHub, Gitea) or `RateLimit-Reset` (GitLab).
+pub(super) fn retry_delay(status: StatusCode, headers: &HeaderMap, now: u64) -> Option<Duration> {
+    if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let header = |names: &[&str]| {
+        names.iter().find_map(|name| {
+            headers
+                .get(*name)?
+                .to_str()
+                .ok()?
+                .trim()
+                .parse::<u64>()
+                .ok()
+        })
+    };
+    let delay = if let Some(seconds) = header(&["retry-after"]) {
+        Duration::from_secs(seconds)
+    } else {
+        if header(&["x-ratelimit-remaining", "ratelimit-remaining"])? != 0 {
+            return None;
+        }
+        let reset = header(&["x-ratelimit-reset", "ratelimit-reset"])?;
+        // Allow a second's leeway in case the forge's clock is slightly ahead.
+        Duration::from_secs(reset.saturating_sub(now) + 1)
+    };
+    (delay <= MAX_WAIT).then_some(delay)
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+pub(super) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Waits until `deadline` (in seconds since the Unix epoch), showing a countdown on stderr if it's
+/// a terminal, or else a single message.  `progress` is hidden meanwhile.
+pub(super) async fn wait_until(deadline: u64, progress: &Progress) {
+    let _guard = WAITING.lock().await;
+    let remaining = deadline.saturating_sub(now());
+    if remaining == 0 {
+        return;
+    }
+    let is_terminal = io::stderr().is_terminal();
+    if !is_terminal {
+        progress
+            .suspend(|| eprintln!("Rate limited by the forge's API; retrying in {remaining}s."));
+        time::sleep(Duration::from_secs(remaining)).await;
+        return;
+    }
+    progress.pause();
+    for left in (1..=remaining).rev() {
+        eprint!(
+            "\rRate limited by the forge's API; retrying in {}:{:02}... ",
+            left / 60,
+            left % 60
+        );
+        let _ = io::stderr().flush();
+        time::sleep(Duration::from_secs(1)).await;
+    }
+    eprint!("\r{:60}\r", "");
+    progress.resume();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{retry_delay, MAX_WAIT};
+    use reqwest::{header::HeaderMap, StatusCode};
+    use std::time::Duration;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn retry_delays() {
+        let now = 1_700_000_000;
+        let reset = (now + 30).to_string();
+        let exhausted = headers(&[
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", &reset),
+        ]);
+        assert_eq!(
+            retry_delay(StatusCode::FORBIDDEN, &exhausted, now),
+            Some(Duration::from_secs(31))
+        );
+        assert_eq!(retry_delay(StatusCode::NOT_FOUND, &exhausted, now), None);
+        assert_eq!(
+            retry_delay(
+                StatusCode::TOO_MANY_REQUESTS,
+                &headers(&[("retry-after", "60")]),
+                now
+            ),
+            Some(Duration::from_mins(1))
+        );
+        let gitlab = headers(&[("ratelimit-remaining", "0"), ("ratelimit-reset", &reset)]);
+        assert_eq!(
+            retry_delay(StatusCode::TOO_MANY_REQUESTS, &gitlab, now),
+            Some(Duration::from_secs(31))
+        );
+
+        // A 403 for any other reason, e.g. a missing permission, isn't retried.
+        let remaining = headers(&[
+            ("x-ratelimit-remaining", "12"),
+            ("x-ratelimit-reset", &reset),
+        ]);
+        assert_eq!(retry_delay(StatusCode::FORBIDDEN, &remaining, now), None);
+        assert_eq!(retry_delay(StatusCode::FORBIDDEN, &headers(&[]), now), None);
+
+        let too_long = (MAX_WAIT.as_secs() + 1).to_string();
+        assert_eq!(
+            retry_delay(
+                StatusCode::FORBIDDEN,
+                &headers(&[("retry-after", &too_long)]),
+                now
+            ),
+            None
+        );
+        let past = (now - 5).to_string();
+        let stale = headers(&[("x-ratelimit-remaining", "0"), ("x-ratelimit-reset", &past)]);
+        assert_eq!(
+            retry_delay(StatusCode::FORBIDDEN, &stale, now),
+            Some(Duration::from_secs(1))
+        );
+    }
+}
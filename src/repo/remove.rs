@@ -0,0 +1,118 @@
+use super::{Owner, Repo};
+use crate::{
+    error::Error,
+    input_getter::{is_help_request, parse_bool},
+};
+use colour::{green_ln, red_ln, yellow};
+
+impl Repo {
+    /// Removes the remote `alias` if given, or else lists the remotes and asks which to remove.
+    /// Then, if its owner has an entry under `add-remote.forkAlias`, offers to delete that too, or
+    /// deletes it without asking if `--forget-alias` was passed.  With `--dry-run`, the Git
+    /// commands are printed rather than run.
+    pub fn remove(&self, alias: Option<&str>) -> Result<(), Error> {
+        let aliases = self.get_local_remote_aliases()?;
+        let alias = match alias {
+            Some(alias) => aliases
+                .iter()
+                .find(|existing| *existing == alias)
+                .cloned()
+                .ok_or_else(|| Error::NotFound(format!("There is no remote named '{alias}'.")))?,
+            None if aliases.is_empty() => {
+                return Err(Error::NotFound(
+                    "This repository has no remotes to remove.".to_string(),
+                ))
+            }
+            None if self.options.no_input => {
+                return Err(Self::input_needed(
+                    "Pass the name of the remote to remove, e.g. 'add-remote remove Herb'.",
+                ))
+            }
+            None => self.choose_remote(
+                &aliases,
+                "remove",
+                "Which remote should be removed?  Its remote-tracking branches and configuration \
+                 are removed too, as per 'git remote remove'.",
+            )?,
+        };
+        let owner = self.remote_owner(&alias);
+        self.run_git_unless_dry_run(&["remote", "remove", &alias])?;
+        if !self.options.dry_run {
+            green_ln!("Removed the remote '{}'", alias);
+        }
+        match owner {
+            Some(owner) => self.forget_fork_alias(&owner),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the owner of the fork which the remote `alias` fetches from, or else the owner
+    /// recorded for it if it's managed by `add-remote`.
+    pub(super) fn remote_owner(&self, alias: &str) -> Option<Owner> {
+        Self::get_local_remote(&*self.git, &self.mirrors, alias)
+            .map(|(owner, _)| owner)
+            .or_else(|| {
+                self.managed_remotes()
+                    .into_iter()
+                    .find(|(managed_alias, _)| managed_alias.0 == alias)
+                    .and_then(|(_, owner)| owner)
+            })
+    }
+
+    /// Deletes `add-remote.forkAlias.<owner>` from whichever Git config files it's set in, if
+    /// `--forget-alias` was passed or the user confirms it.
+    fn forget_fork_alias(&self, owner: &Owner) -> Result<(), Error> {
+        let key = format!("add-remote.forkAlias.{}", owner.0);
+        for (scope, description, fork_alias) in self.fork_alias_entries(owner) {
+            let forget = self.options.forget_alias
+                || (!self.options.no_input
+                    && self.confirm_forget_alias(&key, &fork_alias, description)?);
+            if forget {
+                self.run_git_unless_dry_run(&["config", scope, "--unset-all", &key])?;
+                if !self.options.dry_run {
+                    green_ln!("Deleted {} from the {} Git config", key, description);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Asks the user whether to delete `key`, whose value is `fork_alias`, from the Git config
+    /// file described by `scope`.
+    fn confirm_forget_alias(
+        &self,
+        key: &str,
+        fork_alias: &str,
+        scope: &str,
+    ) -> Result<bool, Error> {
+        loop {
+            if self.options.plain {
+                println!(
+                    "Do you want to also delete {key} ('{fork_alias}') from the {scope} Git \
+                     config?  Enter 'y', 'n' or '?' for help.  The default is 'n'."
+                );
+            } else {
+                yellow!(
+                    "Also delete {} ('{}') from the {} Git config? [y/N/?]: ",
+                    key,
+                    fork_alias,
+                    scope
+                );
+            }
+            let input = self.read_answer(true)?;
+            if is_help_request(&input) {
+                println!(
+                    "'{fork_alias}' is suggested as the name of the remote whenever this owner's \
+                     fork is added.  Entering 'y' deletes it, so that a name is suggested as for \
+                     any other owner in future."
+                );
+                println!("The default is 'n'.");
+                continue;
+            }
+            match parse_bool(&input, Some(false)) {
+                Err(error) => red_ln!("{}", error),
+                Ok(forget) => return Ok(forget),
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use super::{Owner, Repo};
+use crate::{
+    error::Error,
+    input_getter::{is_help_request, QUIT_ESCAPE},
+    remote_name,
+};
+use colour::{green_ln, red_ln, yellow};
+
+impl Repo {
+    /// Renames the remote `old` to `new`, asking for whichever isn't given.  If the remote's owner
+    /// has an entry under `add-remote.forkAlias`, it's updated to the new name so that the same
+    /// name is suggested whenever that owner's fork is added in future.
+    pub fn rename(&self, old: Option<&str>, new: Option<&str>) -> Result<(), Error> {
+        let aliases = self.get_local_remote_aliases()?;
+        let old = match old {
+            Some(old) => aliases
+                .iter()
+                .find(|existing| *existing == old)
+                .cloned()
+                .ok_or_else(|| Error::NotFound(format!("There is no remote named '{old}'.")))?,
+            None if aliases.is_empty() => {
+                return Err(Error::NotFound(
+                    "This repository has no remotes to rename.".to_string(),
+                ))
+            }
+            None if self.options.no_input => {
+                return Err(Self::input_needed(
+                    "Pass the current and new names of the remote, e.g. 'add-remote rename Herb \
+                     Herbert'.",
+                ))
+            }
+            None => self.choose_remote(
+                &aliases,
+                "rename",
+                "Which remote should be renamed?  Its remote-tracking branches and configuration \
+                 are renamed too, as per 'git remote rename'.",
+            )?,
+        };
+        let new = match new {
+            Some(new) => {
+                check_new_name(new, &aliases).map_err(Error::InvalidRemoteName)?;
+                new.to_string()
+            }
+            None if self.options.no_input => {
+                return Err(Self::input_needed(&format!(
+                    "Pass the new name of the remote, e.g. 'add-remote rename {old} Herbert'."
+                )))
+            }
+            None => self.choose_new_name(&old, &aliases)?,
+        };
+
+        let owner = self.remote_owner(&old);
+        self.run_git(&["remote", "rename", &old, &new])?;
+        green_ln!("Renamed '{}' to '{}'", old, new);
+        if let Some(owner) = owner {
+            self.update_fork_alias(&owner, &new)?;
+        }
+        Ok(())
+    }
+
+    /// Asks the user for the new name of the remote `old`, which mustn't be one of `aliases`.
+    fn choose_new_name(&self, old: &str, aliases: &[String]) -> Result<String, Error> {
+        loop {
+            if self.options.plain {
+                println!(
+                    "Enter the new name for the remote {old}, '?' for help, or '{QUIT_ESCAPE}' to \
+                     quit."
+                );
+            } else {
+                yellow!(
+                    "Choose new name for '{}' ('?' for help, or '{}' to quit): ",
+                    old,
+                    QUIT_ESCAPE
+                );
+            }
+            let value = self.read_name(false)?;
+            if is_help_request(&value) {
+                println!(
+                    "What should the remote '{old}' be called instead?  This is the name used to \
+                     refer to it in Git commands, e.g. 'git fetch <name>'.  There is no default."
+                );
+                continue;
+            }
+            match check_new_name(&value, aliases) {
+                Err(error) => red_ln!("{}", error),
+                Ok(()) => return Ok(value),
+            }
+        }
+    }
+
+    /// Sets each entry of `add-remote.forkAlias.<owner>` to `alias`, in whichever Git config files
+    /// it's set.
+    fn update_fork_alias(&self, owner: &Owner, alias: &str) -> Result<(), Error> {
+        let key = format!("add-remote.forkAlias.{}", owner.0);
+        for (scope, description, fork_alias) in self.fork_alias_entries(owner) {
+            if fork_alias != alias {
+                self.run_git(&["config", scope, "--replace-all", &key, alias])?;
+                green_ln!(
+                    "Updated {} from '{}' to '{}' in the {} Git config",
+                    key,
+                    fork_alias,
+                    alias,
+                    description
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks that `new` is a valid name for a remote and isn't already the name of one of
+/// `aliases`.
+fn check_new_name(new: &str, aliases: &[String]) -> Result<(), String> {
+    remote_name::validate(new)?;
+    if aliases.iter().any(|alias| alias == new) {
+        return Err(format!("A remote named '{new}' already exists."));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_new_name;
+
+    #[test]
+    fn new_names() {
+        let aliases = ["origin".to_string(), "Herb".to_string()];
+        assert!(check_new_name("Herbert", &aliases).is_ok());
+        assert!(check_new_name("herb", &aliases).is_ok());
+        assert!(check_new_name("Herb", &aliases).is_err());
+        assert!(check_new_name("", &aliases).is_err());
+        assert!(check_new_name("Herb bert", &aliases).is_err());
+    }
+}
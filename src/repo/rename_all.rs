@@ -0,0 +1,96 @@
+use super::Repo;
+use crate::{
+    error::Error,
+    input_getter::{is_help_request, parse_bool},
+    suggest::{Candidate, SuggestionContext},
+};
+use colour::{green_ln, red_ln, yellow, yellow_ln};
+
+impl Repo {
+    /// Finds the remotes whose owners have an entry under `add-remote.forkAlias` but which are
+    /// named differently, and offers to rename each of them to its configured alias.
+    pub fn rename_all(&self) -> Result<(), Error> {
+        let aliases = self.get_local_remote_aliases()?;
+        let renames = self.configured_alias_mismatches(&aliases);
+        if renames.is_empty() {
+            green_ln!("Every remote with a configured alias is already named accordingly.");
+            return Ok(());
+        }
+        if self.options.no_input {
+            return Err(Self::input_needed(
+                "Run 'add-remote rename-all' without '--no-input' to confirm each rename.",
+            ));
+        }
+
+        for (alias, suggested) in renames {
+            let is_taken = aliases
+                .iter()
+                .any(|other| *other == suggested && !other.eq_ignore_ascii_case(&alias));
+            if is_taken {
+                yellow_ln!(
+                    "Not renaming '{}' since a remote named '{}' already exists.",
+                    alias,
+                    suggested
+                );
+            } else if self.confirm_rename(&alias, &suggested)? {
+                self.run_git(&["remote", "rename", &alias, &suggested])?;
+                green_ln!("Renamed '{}' to '{}'", alias, suggested);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the alias and configured alias of each of the remotes `aliases` whose owner has an
+    /// entry under `add-remote.forkAlias` which differs from the remote's alias.
+    fn configured_alias_mismatches(&self, aliases: &[String]) -> Vec<(String, String)> {
+        // The main fork/source isn't known without querying GitLab/GitHub, so it's treated like any
+        // other fork here.
+        let context = SuggestionContext {
+            available_forks: Vec::new(),
+            main_fork_owner: "",
+            config: &self.config,
+        };
+        aliases
+            .iter()
+            .filter_map(|alias| Self::get_local_remote(&*self.git, &self.mirrors, alias))
+            .filter(|(owner, _)| context.was_previously_chosen(&owner.0))
+            .filter_map(|(owner, (name, alias, _))| {
+                let candidate = Candidate {
+                    owner: &owner.0,
+                    name: &name.0,
+                    ..Candidate::default()
+                };
+                let suggested = self.strategy.suggest_alias(&context, &candidate);
+                (suggested != alias.0).then_some((alias.0, suggested))
+            })
+            .collect()
+    }
+
+    /// Asks the user whether to rename the remote `alias` to `suggested`.
+    fn confirm_rename(&self, alias: &str, suggested: &str) -> Result<bool, Error> {
+        loop {
+            if self.options.plain {
+                println!(
+                    "Do you want to rename the remote {alias} to {suggested}?  Enter 'y', 'n' or \
+                     '?' for help.  The default is 'y'."
+                );
+            } else {
+                yellow!("Rename '{}' -> '{}'? [Y/n/?]: ", alias, suggested);
+            }
+            let input = self.read_answer(true)?;
+            if is_help_request(&input) {
+                println!(
+                    "Should the remote '{alias}' be renamed to '{suggested}', the alias configured \
+                     for its owner under add-remote.forkAlias?  Its remote-tracking branches are \
+                     renamed too."
+                );
+                println!("The default is 'y'.");
+                continue;
+            }
+            match parse_bool(&input, Some(true)) {
+                Err(error) => red_ln!("{}", error),
+                Ok(rename) => return Ok(rename),
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use super::Repo;
+use crate::{error::Error, input_getter::is_help_request};
+use colour::{cyan_ln, red_ln, yellow, yellow_ln};
+
+/// How to fetch a new remote into a shallow clone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ShallowFetch {
+    /// Fetch only the tip of each branch, keeping the repository shallow.
+    Depth,
+    /// Fetch the rest of the history of the existing remotes first, then fetch as normal.
+    Unshallow,
+    /// Fetch as normal, which fetches the full history of the new remote's branches.
+    Full,
+}
+
+impl Repo {
+    /// Whether the current repository is a shallow clone.
+    pub(super) fn is_shallow(&self) -> Result<bool, Error> {
+        Ok(self
+            .git_output(&["rev-parse", "--is-shallow-repository"])?
+            .trim()
+            .eq_ignore_ascii_case("true"))
+    }
+
+    /// Warns that the current repository is a shallow clone and asks how to fetch from `alias`,
+    /// deepening the repository first if that's what's chosen.  Returns the extra options to pass
+    /// to `git fetch`.  If `--no-input` was passed, only the tips of the branches are fetched.
+    pub(super) fn prepare_shallow_fetch(&self, alias: &str) -> Result<Vec<&'static str>, Error> {
+        yellow_ln!(
+            "This repository is a shallow clone, so a normal fetch from '{}' fetches the full \
+             history of its branches, which may be slow or fail on large repositories.",
+            alias
+        );
+        let choice = if self.options.no_input {
+            ShallowFetch::Depth
+        } else {
+            self.choose_shallow_fetch()?
+        };
+        Ok(match choice {
+            ShallowFetch::Depth => vec!["--depth=1"],
+            ShallowFetch::Unshallow => {
+                let mut command = self.git.command();
+                let _ = command.args(["fetch", "--unshallow", &format!("--jobs={}", self.jobs)]);
+                if !self.options.dry_run {
+                    cyan_ln!("Fetching the rest of the history of the existing remotes\n");
+                }
+                let Some(output) = self.run_unless_dry_run(&mut command)? else {
+                    return Ok(Vec::new());
+                };
+                if !output.status.success() {
+                    yellow_ln!(
+                        "Failed to deepen the repository, so only the tips of the branches of '{}' \
+                         will be fetched: {}",
+                        alias,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                    return Ok(vec!["--depth=1"]);
+                }
+                Vec::new()
+            }
+            ShallowFetch::Full => Vec::new(),
+        })
+    }
+
+    /// Asks the user how to fetch a new remote into a shallow clone.
+    fn choose_shallow_fetch(&self) -> Result<ShallowFetch, Error> {
+        loop {
+            if self.options.plain {
+                println!(
+                    "How do you want to fetch?  Enter 'd' to fetch only the tips of the branches, \
+                     'u' to unshallow the repository first, 'f' for a full fetch, or '?' for help.  \
+                     The default is 'd'."
+                );
+            } else {
+                yellow!("Fetch with [d]epth 1, [u]nshallow first, or [f]ull fetch? [D/u/f/?]: ");
+            }
+            let input = self.read_answer(true)?;
+            if is_help_request(&input) {
+                println!(
+                    "'d' passes '--depth=1' to 'git fetch', keeping the repository shallow.  'u' \
+                     runs 'git fetch --unshallow' to fetch the rest of the history of the existing \
+                     remotes, then fetches the new remote as normal.  'f' fetches the new remote as \
+                     normal, leaving the existing history shallow."
+                );
+                println!("The default is 'd'.");
+                continue;
+            }
+            match parse_shallow_fetch(&input) {
+                Ok(choice) => return Ok(choice),
+                Err(error) => red_ln!("{}", error),
+            }
+        }
+    }
+}
+
+/// Parses `input` as a choice of how to fetch into a shallow clone, defaulting to
+/// `ShallowFetch::Depth` if it's empty.
+fn parse_shallow_fetch(input: &str) -> Result<ShallowFetch, String> {
+    match input.to_ascii_lowercase().as_str() {
+        "" | "d" => Ok(ShallowFetch::Depth),
+        "u" => Ok(ShallowFetch::Unshallow),
+        "f" => Ok(ShallowFetch::Full),
+        _ => Err("Enter 'd', 'u' or 'f' only.".to_string()),
+    }
+}
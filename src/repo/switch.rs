@@ -0,0 +1,261 @@
+use super::Repo;
+use crate::{
+    error::Error,
+    fuzzy,
+    input_getter::{is_help_request, parse_bool, parse_uint},
+};
+use colour::{red_ln, yellow};
+
+/// The maximum number of matching branches listed for the user to choose from.
+const MAX_LISTED_BRANCHES: usize = 20;
+
+impl Repo {
+    /// Searches the remote-tracking branches of every remote managed by `add-remote` for those
+    /// which fuzzily match `pattern`, asks the user to choose one if there are several, then checks
+    /// out a local branch which tracks the chosen one.
+    pub fn switch(&mut self, pattern: &str) -> Result<(), Error> {
+        let mut matches: Vec<(i64, String, String)> = Vec::new();
+        for (alias, _) in self.managed_remotes() {
+            for branch in self.remote_branches(&alias.0)? {
+                let remote_branch = format!("{}/{}", alias.0, branch);
+                if let Some(score) = fuzzy::score(pattern, &remote_branch) {
+                    matches.push((score, alias.0.clone(), branch));
+                }
+            }
+        }
+        if matches.is_empty() {
+            return Err(Error::NotFound(format!(
+                "No branches of the remotes managed by 'add-remote' match '{pattern}'."
+            )));
+        }
+        // Best matches first, with ties broken alphabetically.
+        matches.sort_by(
+            |(score, alias, branch), (other_score, other_alias, other_branch)| {
+                other_score
+                    .cmp(score)
+                    .then_with(|| (alias, branch).cmp(&(other_alias, other_branch)))
+            },
+        );
+        matches.truncate(MAX_LISTED_BRANCHES);
+
+        let index = if matches.len() == 1 {
+            0
+        } else if self.options.no_input {
+            return Err(Self::input_needed(&format!(
+                "{} branches match '{}'.  Pass a more specific pattern.",
+                matches.len(),
+                pattern
+            )));
+        } else {
+            self.choose_branch(pattern, &matches)?
+        };
+        let (_, alias, branch) = &matches[index];
+        self.check_out_tracking_branch(alias, branch)
+    }
+
+    /// Lists `matches` and asks the user to choose one, returning its index.
+    fn choose_branch(
+        &mut self,
+        pattern: &str,
+        matches: &[(i64, String, String)],
+    ) -> Result<usize, Error> {
+        println!("Matching branches:");
+        let first_column_width = self.index_column_width(matches.len());
+        for (index, (_, alias, branch)) in matches.iter().enumerate() {
+            if self.options.plain {
+                println!("option {index}: {alias}/{branch}");
+            } else {
+                println!("{index:<first_column_width$}{alias}/{branch}");
+            }
+        }
+        loop {
+            if self.options.plain {
+                println!(
+                    "Enter the option number of the branch to check out, '?' for help, or 'q' to \
+                     quit.  The default is option 0."
+                );
+            } else {
+                yellow!("Choose branch (enter index number, '?' for help, or 'q' to quit) [0]: ");
+            }
+            let input = self.read_answer(true)?;
+            if is_help_request(&input) {
+                println!(
+                    "Which branch should be checked out?  A local branch tracking it will be \
+                     created if there isn't one already."
+                );
+                println!(
+                    "The default is 0 because it's the closest match for '{pattern}'.  Press \
+                     <return> to accept it."
+                );
+                continue;
+            }
+            match parse_uint(&input, Some(0)) {
+                Err(error) => red_ln!("{}", error),
+                #[allow(clippy::cast_possible_truncation)]
+                Ok(value) if (value as usize) < matches.len() => return Ok(value as usize),
+                Ok(_) => red_ln!("Must be one of the listed indices."),
+            }
+        }
+    }
+
+    /// Offers to check out a local branch tracking the new remote `alias`'s branch passed via
+    /// `--branch`, or else its default branch.  Nothing is offered if the branch isn't known, if
+    /// `add-remote.offerCheckout` is false, or if `--no-input` was passed.
+    pub(super) fn offer_checkout(&self, alias: &str) -> Result<(), Error> {
+        if self.options.no_input
+            || self.get_bool_from_gitconfig("add-remote.offerCheckout") == Some(false)
+        {
+            return Ok(());
+        }
+        let branch = self.options.branch.clone().or_else(|| {
+            self.available_forks[self.chosen_fork_index]
+                .default_branch
+                .clone()
+        });
+        let remote_branches = self.remote_branches(alias)?;
+        let Some(branch) = branch.filter(|branch| remote_branches.contains(branch)) else {
+            return Ok(());
+        };
+        if self.confirm_checkout(alias, &branch)? {
+            self.check_out_tracking_branch(alias, &branch)?;
+        }
+        Ok(())
+    }
+
+    /// Asks the user whether to check out a local branch tracking `<alias>/<branch>`.
+    fn confirm_checkout(&self, alias: &str, branch: &str) -> Result<bool, Error> {
+        loop {
+            if self.options.plain {
+                println!(
+                    "Do you want to check out {alias}/{branch} as a local branch?  Enter 'y', 'n' \
+                     or '?' for help.  The default is 'n'."
+                );
+            } else {
+                yellow!(
+                    "Check out '{}/{}' as a local branch? [y/N/?]: ",
+                    alias,
+                    branch
+                );
+            }
+            let input = self.read_answer(true)?;
+            if is_help_request(&input) {
+                println!(
+                    "Entering 'y' runs 'git switch --track -c {branch} {alias}/{branch}', or if a \
+                     local branch named '{branch}' already tracks something else, names the new \
+                     branch '{alias}-{branch}' instead.  To stop being asked, set \
+                     add-remote.offerCheckout to false."
+                );
+                println!("The default is 'n'.");
+                continue;
+            }
+            match parse_bool(&input, Some(false)) {
+                Err(error) => red_ln!("{}", error),
+                Ok(check_out) => return Ok(check_out),
+            }
+        }
+    }
+
+    /// Checks out a local branch tracking `<alias>/<branch>`.  The local branch is named `branch`
+    /// unless a branch of that name already exists and tracks something else, in which case it's
+    /// named `<alias>-<branch>`.
+    fn check_out_tracking_branch(&self, alias: &str, branch: &str) -> Result<(), Error> {
+        let remote_branch = format!("{alias}/{branch}");
+        let mut local_branch = branch.to_string();
+        let mut upstream = self.local_branch_upstream(&local_branch)?;
+        if upstream
+            .as_ref()
+            .is_some_and(|upstream| *upstream != remote_branch)
+        {
+            local_branch = format!("{alias}-{branch}");
+            upstream = self.local_branch_upstream(&local_branch)?;
+        }
+
+        let mut command = self.git.command();
+        let _ = match upstream {
+            Some(upstream) if upstream == remote_branch => command.args(["switch", &local_branch]),
+            Some(_) => {
+                return Err(Error::NotFound(format!(
+                    "Local branches '{branch}' and '{local_branch}' already exist and don't track \
+                     '{remote_branch}'."
+                )));
+            }
+            None => command.args(["switch", "--track", "-c", &local_branch, &remote_branch]),
+        };
+        if !self
+            .git
+            .status(&mut command)
+            .is_ok_and(|status| status.success())
+        {
+            return Err(Error::Git(format!("Failed to run {command:?}")));
+        }
+        Ok(())
+    }
+
+    /// Returns `None` if there's no local branch named `branch`, or else the short name of the
+    /// branch it tracks (which is empty if it doesn't track one).
+    fn local_branch_upstream(&self, branch: &str) -> Result<Option<String>, Error> {
+        let ref_name = format!("refs/heads/{branch}");
+        Ok(self
+            .git_output(&["for-each-ref", "--format=%(upstream:short)", &ref_name])?
+            .lines()
+            .next()
+            .map(|upstream| upstream.trim().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{git_runner::FakeGit, Repo};
+
+    fn upstream_query(branch: &str) -> [String; 3] {
+        [
+            "for-each-ref".to_string(),
+            "--format=%(upstream:short)".to_string(),
+            format!("refs/heads/{branch}"),
+        ]
+    }
+
+    fn reply_upstream(git: &FakeGit, branch: &str, upstream: &str) {
+        let query = upstream_query(branch);
+        let args: Vec<&str> = query.iter().map(String::as_str).collect();
+        let _ = git.reply(&args, upstream);
+    }
+
+    #[test]
+    fn check_out_new_tracking_branch() {
+        let git = FakeGit::default();
+        let repo = Repo::with_git(&git);
+        assert!(repo.check_out_tracking_branch("alice", "main").is_ok());
+        assert_eq!(
+            git.commands().last().unwrap(),
+            "switch --track -c main alice/main"
+        );
+    }
+
+    #[test]
+    fn check_out_existing_tracking_branch() {
+        let git = FakeGit::default();
+        reply_upstream(&git, "main", "alice/main\n");
+        let repo = Repo::with_git(&git);
+        assert!(repo.check_out_tracking_branch("alice", "main").is_ok());
+        assert_eq!(git.commands().last().unwrap(), "switch main");
+    }
+
+    #[test]
+    fn check_out_renamed_tracking_branch() {
+        let git = FakeGit::default();
+        reply_upstream(&git, "main", "origin/main\n");
+        let repo = Repo::with_git(&git);
+        assert!(repo.check_out_tracking_branch("alice", "main").is_ok());
+        assert_eq!(
+            git.commands().last().unwrap(),
+            "switch --track -c alice-main alice/main"
+        );
+
+        // Both names are taken by branches tracking something else.
+        reply_upstream(&git, "alice-main", "\n");
+        let commands_before = git.commands().len();
+        assert!(repo.check_out_tracking_branch("alice", "main").is_err());
+        assert_eq!(git.commands().len(), commands_before + 2);
+    }
+}
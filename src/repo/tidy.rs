@@ -0,0 +1,346 @@
+use super::{Owner, Repo, Url};
+use crate::{
+    error::Error,
+    git_url,
+    suggest::{Candidate, SuggestionContext},
+};
+use colour::{green_ln, yellow_ln};
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+impl Repo {
+    /// Normalises the configuration of the remotes managed by `add-remote`, i.e. canonicalises
+    /// their URLs, removes duplicate fetch refspecs, fixes the case of their names to match the
+    /// configured aliases, and groups their sections in alphabetical order at the end of the
+    /// repo-local Git config.  Reports each change made.
+    pub fn tidy(&self) -> Result<(), Error> {
+        let managed = self.managed_remotes();
+        if managed.is_empty() {
+            yellow_ln!("There are no remotes managed by 'add-remote' to tidy.");
+            return Ok(());
+        }
+        let mut changes = Vec::new();
+        let mut aliases = Vec::new();
+        for (alias, owner) in managed {
+            changes.extend(self.canonicalise_url(&alias.0)?);
+            changes.extend(self.remove_duplicate_fetch_refspecs(&alias.0)?);
+            let (alias, change) = self.normalise_alias_case(alias.0, owner)?;
+            changes.extend(change);
+            aliases.push(alias);
+        }
+        changes.extend(self.reorder_sections(&aliases)?);
+
+        if changes.is_empty() {
+            green_ln!("Nothing to tidy.");
+        }
+        for change in changes {
+            green_ln!("{}", change);
+        }
+        Ok(())
+    }
+
+    /// Replaces the fetch URL of the remote `alias` with its canonical form if they differ.  Any
+    /// further URLs used only for pushing are left unchanged.
+    fn canonicalise_url(&self, alias: &str) -> Result<Option<String>, Error> {
+        let Some((url, _, _)) = self
+            .get_fetch_url_from_gitconfig(alias)
+            .and_then(|url| Url::new(&url))
+        else {
+            return Ok(None);
+        };
+        let Some(canonical) = url.canonical() else {
+            return Ok(None);
+        };
+        if canonical.value() == url.value() {
+            return Ok(None);
+        }
+        let old_url_pattern = format!("^{}$", regex::escape(url.value()));
+        self.run_git(&[
+            "remote",
+            "set-url",
+            alias,
+            canonical.value(),
+            &old_url_pattern,
+        ])?;
+        Ok(Some(format!(
+            "Changed the URL of '{}' from {} to {}",
+            alias,
+            git_url::redact(url.value()),
+            canonical.value()
+        )))
+    }
+
+    /// Removes any repeated fetch refspecs of the remote `alias`, preserving the order of the rest.
+    fn remove_duplicate_fetch_refspecs(&self, alias: &str) -> Result<Option<String>, Error> {
+        let key = format!("remote.{alias}.fetch");
+        let refspecs = self.get_all_from_gitconfig(&key);
+        let mut unique: Vec<&String> = Vec::new();
+        for refspec in &refspecs {
+            if !unique.contains(&refspec) {
+                unique.push(refspec);
+            }
+        }
+        let duplicate_count = refspecs.len() - unique.len();
+        if duplicate_count == 0 {
+            return Ok(None);
+        }
+        self.run_git(&["config", "--local", "--unset-all", &key])?;
+        for refspec in unique {
+            self.run_git(&["config", "--local", "--add", &key, refspec])?;
+        }
+        Ok(Some(format!(
+            "Removed {} duplicate fetch refspec{} from '{}'",
+            duplicate_count,
+            if duplicate_count > 1 { "s" } else { "" },
+            alias
+        )))
+    }
+
+    /// Renames the remote `alias` if it only differs in case from the alias which would be
+    /// suggested for `owner` now.  Returns the resulting alias.
+    fn normalise_alias_case(
+        &self,
+        alias: String,
+        owner: Option<Owner>,
+    ) -> Result<(String, Option<String>), Error> {
+        let Some(owner) = owner else {
+            return Ok((alias, None));
+        };
+        let name = self
+            .get_fetch_url_from_gitconfig(&alias)
+            .and_then(|url| Url::new(&url))
+            .map(|(_, _, name)| name.0)
+            .unwrap_or_default();
+        // The main fork/source isn't known without querying GitLab/GitHub, so it's treated like any
+        // other fork here.
+        let context = SuggestionContext {
+            available_forks: Vec::new(),
+            main_fork_owner: "",
+            config: &self.config,
+        };
+        let candidate = Candidate {
+            owner: &owner.0,
+            name: &name,
+            ..Candidate::default()
+        };
+        let suggested = self.strategy.suggest_alias(&context, &candidate);
+        if suggested == alias || !suggested.eq_ignore_ascii_case(&alias) {
+            return Ok((alias, None));
+        }
+        self.run_git(&["remote", "rename", &alias, &suggested])?;
+        let change = format!("Renamed '{alias}' to '{suggested}'");
+        Ok((suggested, Some(change)))
+    }
+
+    /// Moves the `[remote "<alias>"]` sections of the managed remotes to the end of the repo-local
+    /// Git config in alphabetical order, unless they're already arranged that way.  The config
+    /// file is rewritten as a whole, with every line kept verbatim, via `config.lock` as Git does,
+    /// so it's never left partially rewritten and no concurrent Git command can interleave.
+    fn reorder_sections(&self, managed_aliases: &[String]) -> Result<Option<String>, Error> {
+        let path = PathBuf::from(
+            self.git_output(&["rev-parse", "--git-path", "config"])?
+                .trim(),
+        );
+        let config = fs::read_to_string(&path)
+            .map_err(|error| Error::Git(format!("Failed to read '{}': {error}", path.display())))?;
+        let Some(reordered) = reorder_remote_sections(&config, managed_aliases) else {
+            return Ok(None);
+        };
+        replace_file(&path, &reordered).map_err(|error| {
+            Error::Git(format!("Failed to update '{}': {error}", path.display()))
+        })?;
+        Ok(Some(
+            "Moved the managed remotes' sections to the end of the repository's Git config in \
+             alphabetical order"
+                .to_string(),
+        ))
+    }
+}
+
+/// Returns `config`, the contents of a Git config file, with the `[remote "<alias>"]` sections of
+/// `managed_aliases` moved to the end in alphabetical order, or `None` if they're there already.
+/// Each section is moved along with any comments and blank lines directly above its header, and
+/// the lines following it up to the next of those.
+fn reorder_remote_sections(config: &str, managed_aliases: &[String]) -> Option<String> {
+    let mut sections: Vec<(Option<String>, Vec<&str>)> = vec![(None, Vec::new())];
+    let mut continued = false;
+    for line in config.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        if !continued && content.trim_start().starts_with('[') {
+            let previous = &mut sections.last_mut()?.1;
+            let comments_start = previous
+                .iter()
+                .rposition(|line| !is_comment_or_blank(line))
+                .map_or(0, |index| index + 1);
+            let comments = previous.split_off(comments_start);
+            sections.push((remote_section_alias(content), comments));
+        }
+        // A value ending in an unescaped backslash continues onto the next line.
+        continued = (content.len() - content.trim_end_matches('\\').len()) % 2 == 1;
+        sections.last_mut()?.1.push(line);
+    }
+
+    let mut sorted: Vec<&String> = managed_aliases.iter().collect();
+    sorted.sort_by_key(|alias| alias.to_lowercase());
+    let is_managed = |alias: &Option<String>| alias.as_ref().is_some_and(|a| sorted.contains(&a));
+    let mut reordered = String::new();
+    let moved = sorted.iter().flat_map(|alias| {
+        sections
+            .iter()
+            .filter(move |(other, _)| other.as_ref() == Some(*alias))
+    });
+    for (_, text) in sections
+        .iter()
+        .filter(|(alias, _)| !is_managed(alias))
+        .chain(moved)
+    {
+        if !reordered.is_empty() && !reordered.ends_with('\n') {
+            reordered.push('\n');
+        }
+        reordered.extend(text.iter().copied());
+    }
+    (reordered != config).then_some(reordered)
+}
+
+/// Whether `line` of a Git config file is blank or only holds a comment.
+fn is_comment_or_blank(line: &str) -> bool {
+    let line = line.trim_start();
+    line.is_empty() || line.starts_with(['#', ';'])
+}
+
+/// Returns the alias if `header` is the header of a `[remote "<alias>"]` section.
+fn remote_section_alias(header: &str) -> Option<String> {
+    let rest = header.trim_start().strip_prefix('[')?;
+    let (section, rest) = rest.split_once(|char: char| char.is_whitespace())?;
+    if !section.eq_ignore_ascii_case("remote") {
+        return None;
+    }
+    let mut chars = rest.trim_start().strip_prefix('"')?.chars();
+    let mut alias = String::new();
+    loop {
+        match chars.next()? {
+            '"' => break,
+            '\\' => alias.push(chars.next()?),
+            char => alias.push(char),
+        }
+    }
+    (chars.next() == Some(']')).then_some(alias)
+}
+
+/// Replaces the contents of the file at `path` with `contents`, keeping its permissions.  As Git
+/// does, the new contents are written to `<path>.lock`, which must not already exist, then renamed
+/// over the original.
+fn replace_file(path: &Path, contents: &str) -> io::Result<()> {
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let lock_path = PathBuf::from(lock_path);
+    let mut lock = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&lock_path)?;
+    let result = lock
+        .write_all(contents.as_bytes())
+        .and_then(|()| lock.sync_all())
+        .and_then(|()| fs::set_permissions(&lock_path, fs::metadata(path)?.permissions()))
+        .and_then(|()| fs::rename(&lock_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&lock_path);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reorder_remote_sections, replace_file, Repo};
+    use crate::repo::git_runner::FakeGit;
+    use std::{env, fs, process};
+
+    const CONFIG: &str = "\
+[core]
+\tbare = false
+[remote \"zoe\"]
+\turl = https://github.com/zoe/repo.git
+\tfetch = +refs/heads/*:refs/remotes/zoe/*
+\taddRemoteManaged
+# The upstream repository.
+[remote \"origin\"]
+\turl = https://github.com/owner/repo.git
+[remote \"Alice\"]
+\turl = https://github.com/alice/repo.git
+\taddRemoteManaged = true
+[branch \"main\"]
+\tremote = origin
+\tmerge = refs/heads/main
+[remote \"zoe\"]
+\tpushurl = no_push
+";
+
+    #[test]
+    fn reorder_keeps_every_line_verbatim() {
+        let managed = ["zoe".to_string(), "Alice".to_string()];
+        let reordered = reorder_remote_sections(CONFIG, &managed).unwrap();
+        assert_eq!(
+            reordered,
+            "\
+[core]
+\tbare = false
+# The upstream repository.
+[remote \"origin\"]
+\turl = https://github.com/owner/repo.git
+[branch \"main\"]
+\tremote = origin
+\tmerge = refs/heads/main
+[remote \"Alice\"]
+\turl = https://github.com/alice/repo.git
+\taddRemoteManaged = true
+[remote \"zoe\"]
+\turl = https://github.com/zoe/repo.git
+\tfetch = +refs/heads/*:refs/remotes/zoe/*
+\taddRemoteManaged
+[remote \"zoe\"]
+\tpushurl = no_push
+"
+        );
+        assert!(reorder_remote_sections(&reordered, &managed).is_none());
+        assert!(reorder_remote_sections(CONFIG, &[]).is_none());
+    }
+
+    #[test]
+    fn reorder_ignores_headers_in_continued_values() {
+        let config = "[alias]\n\tl = !echo \\\n[remote \"a\"]\n[remote \"b\"]\n\turl = x\n";
+        assert!(reorder_remote_sections(config, &["a".to_string()]).is_none());
+    }
+
+    #[test]
+    fn reorder_sections_rewrites_the_config_file() {
+        let dir = env::temp_dir().join(format!("add-remote-tidy-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config");
+        fs::write(&path, CONFIG).unwrap();
+        let git = FakeGit::default();
+        let _ = git.reply(
+            &["rev-parse", "--git-path", "config"],
+            &format!("{}\n", path.display()),
+        );
+        let repo = Repo::with_git(&git);
+        let managed = ["Alice".to_string(), "zoe".to_string()];
+        assert!(repo.reorder_sections(&managed).unwrap().is_some());
+        let reordered = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            Some(reordered.as_str()),
+            reorder_remote_sections(CONFIG, &managed).as_deref()
+        );
+        assert!(!dir.join("config.lock").exists());
+        assert!(repo.reorder_sections(&managed).unwrap().is_none());
+
+        // The file is left untouched while another process holds the lock.
+        fs::write(&path, CONFIG).unwrap();
+        fs::write(dir.join("config.lock"), "").unwrap();
+        assert!(replace_file(&path, "").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), CONFIG);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,507 @@
+use super::{format_details, Repo};
+use colour::yellow_ln;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, List, ListItem, ListState, Paragraph},
+    DefaultTerminal, Frame,
+};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    env,
+    fmt::Write as _,
+    io::{self, IsTerminal},
+    process::{self, Stdio},
+    sync::{
+        mpsc::{self, Sender},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long a fork must stay highlighted before its branches are listed, so that scrolling through
+/// the list doesn't start a `git ls-remote` for every fork passed on the way.
+const PREVIEW_DELAY: Duration = Duration::from_millis(300);
+/// How long to wait for a key press before checking whether any previews have arrived.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// The number of forks skipped by `PageUp` and `PageDown`.
+const PAGE_SIZE: isize = 10;
+/// The keys available, shown at the foot of the screen.
+const KEY_HELP: &str =
+    "↑/↓ move   PgUp/PgDn page   type to search   Space mark   Enter add   Esc quit";
+/// The keys available when only one fork may be chosen.
+const SINGLE_KEY_HELP: &str = "↑/↓ move   PgUp/PgDn page   type to search   Enter add   Esc quit";
+
+/// The branches of a fork shown in the preview pane.
+#[derive(PartialEq, Eq, Debug)]
+enum Preview {
+    /// `git ls-remote` is still running.
+    Loading,
+    /// The names of the fork's branches.
+    Branches(Vec<String>),
+    /// `git ls-remote` failed, e.g. as the fork is private.
+    Failed,
+}
+
+/// What to do in response to a key press.
+#[derive(PartialEq, Eq, Debug)]
+enum Action {
+    /// Keep showing the picker.
+    Continue,
+    /// Add the forks at the given indices of `available_forks`, in the given order.
+    Choose(Vec<usize>),
+    /// Exit without adding a fork.
+    Quit,
+}
+
+/// The state of the full-screen fork picker.
+struct Picker {
+    /// The search pattern typed so far.
+    search: String,
+    /// The indices into `available_forks` of the forks whose owner matches `search`, closest
+    /// match first.
+    matches: Vec<usize>,
+    /// The position within `matches` of the highlighted fork.
+    list_state: ListState,
+    /// When the highlighted fork last changed.
+    highlighted_at: Instant,
+    /// The branches of the forks previewed so far, keyed by their index in `available_forks`.
+    previews: HashMap<usize, Preview>,
+    /// Whether several forks may be marked to be added together.
+    allows_several: bool,
+    /// The indices into `available_forks` of the marked forks, in the order they were marked.
+    marked: Vec<usize>,
+}
+
+impl Picker {
+    /// Returns a picker listing all of `repo`'s available forks, with `default` highlighted.
+    /// Several forks may be chosen unless `--alias` was passed.
+    fn new(repo: &Repo, default: Option<usize>) -> Self {
+        let mut picker = Picker {
+            search: String::new(),
+            matches: Vec::new(),
+            list_state: ListState::default(),
+            highlighted_at: Instant::now(),
+            previews: HashMap::new(),
+            allows_several: repo.options.alias.is_none(),
+            marked: Vec::new(),
+        };
+        picker.update_matches(repo);
+        if let Some(position) = default.and_then(|index| picker.position_of(index)) {
+            picker.list_state.select(Some(position));
+        }
+        picker
+    }
+
+    /// Returns the position of the fork at `index` of `available_forks` within `matches`.
+    fn position_of(&self, index: usize) -> Option<usize> {
+        self.matches.iter().position(|matched| *matched == index)
+    }
+
+    /// Returns the index into `available_forks` of the highlighted fork, if any.
+    fn highlighted(&self) -> Option<usize> {
+        self.list_state
+            .selected()
+            .and_then(|position| self.matches.get(position).copied())
+    }
+
+    /// Returns the index of the highlighted fork if its branches should now be listed, i.e. if
+    /// they haven't been already and it's been highlighted for long enough.
+    fn preview_due(&self) -> Option<usize> {
+        self.highlighted().filter(|index| {
+            !self.previews.contains_key(index) && self.highlighted_at.elapsed() >= PREVIEW_DELAY
+        })
+    }
+
+    /// Re-runs the search, highlighting the closest match.
+    fn update_matches(&mut self, repo: &Repo) {
+        self.matches = repo.search_forks(&self.search);
+        self.highlight((!self.matches.is_empty()).then_some(0));
+    }
+
+    /// Highlights the fork at `position` within `matches`.
+    fn highlight(&mut self, position: Option<usize>) {
+        if position != self.list_state.selected() {
+            self.list_state.select(position);
+            self.highlighted_at = Instant::now();
+        }
+    }
+
+    /// Moves the highlight by `offset` places, stopping at either end of the list.
+    fn move_highlight(&mut self, offset: isize) {
+        let Some(last) = self.matches.len().checked_sub(1) else {
+            return;
+        };
+        let position = self
+            .list_state
+            .selected()
+            .map_or(0, |position| position.saturating_add_signed(offset))
+            .min(last);
+        self.highlight(Some(position));
+    }
+
+    /// Updates the picker in response to `key`.
+    fn handle_key(&mut self, key: KeyEvent, repo: &Repo) -> Action {
+        let is_control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if is_control => return Action::Quit,
+            KeyCode::Esc if self.search.is_empty() => return Action::Quit,
+            KeyCode::Esc => {
+                self.search.clear();
+                self.update_matches(repo);
+            }
+            KeyCode::Enter if !self.marked.is_empty() => {
+                return Action::Choose(self.marked.clone());
+            }
+            KeyCode::Enter => {
+                if let Some(index) = self.highlighted() {
+                    return Action::Choose(vec![index]);
+                }
+            }
+            KeyCode::Char(' ') if self.allows_several => {
+                if let Some(index) = self.highlighted() {
+                    match self.marked.iter().position(|marked| *marked == index) {
+                        Some(position) => drop(self.marked.remove(position)),
+                        None => self.marked.push(index),
+                    }
+                }
+            }
+            KeyCode::Up => self.move_highlight(-1),
+            KeyCode::Down => self.move_highlight(1),
+            KeyCode::PageUp => self.move_highlight(-PAGE_SIZE),
+            KeyCode::PageDown => self.move_highlight(PAGE_SIZE),
+            KeyCode::Home => self.move_highlight(isize::MIN),
+            KeyCode::End => self.move_highlight(isize::MAX),
+            KeyCode::Backspace => {
+                let _ = self.search.pop();
+                self.update_matches(repo);
+            }
+            KeyCode::Char(character) if !is_control => {
+                self.search.push(character);
+                self.update_matches(repo);
+            }
+            _ => (),
+        }
+        Action::Continue
+    }
+}
+
+impl Repo {
+    /// Whether the fork should be chosen in the full-screen interface, i.e. `--tui` was passed and
+    /// both stdin and stdout are terminals capable of displaying it.  `TERM` is only unset on
+    /// Windows, where the console can display it.
+    pub fn uses_tui(&self) -> bool {
+        self.options.tui
+            && !self.options.plain
+            && !self.options.stable
+            && self.stdin.is_terminal()
+            && io::stdout().is_terminal()
+            && env::var("TERM").map_or(cfg!(windows), |term| term != "dumb")
+    }
+
+    /// Shows the available forks in a full-screen interface, with `default` highlighted, and
+    /// records the ones chosen, returning true.  Exits if the user quits.  If the interface can't
+    /// be shown, the forks are listed as usual instead and false is returned.
+    pub(super) fn choose_fork_in_tui(&mut self, default: Option<usize>) -> bool {
+        let result = ratatui::try_init().and_then(|mut terminal| {
+            let result = self.run_picker(&mut terminal, Picker::new(self, default));
+            ratatui::restore();
+            result
+        });
+        match result {
+            Ok(Some(indices)) => {
+                self.chosen_fork_indices = indices;
+                true
+            }
+            Ok(None) => process::exit(0),
+            Err(error) => {
+                yellow_ln!("Can't show the full-screen interface: {}", error);
+                self.show_available_forks();
+                false
+            }
+        }
+    }
+
+    /// Redraws the picker and handles key presses until a fork is chosen or the user quits.
+    fn run_picker(
+        &self,
+        terminal: &mut DefaultTerminal,
+        mut picker: Picker,
+    ) -> io::Result<Option<Vec<usize>>> {
+        let (sender, receiver) = mpsc::channel();
+        loop {
+            while let Ok((index, preview)) = receiver.try_recv() {
+                let _ = picker.previews.insert(index, preview);
+            }
+            if let Some(index) = picker.preview_due() {
+                let _ = picker.previews.insert(index, Preview::Loading);
+                self.list_branches_in_background(index, sender.clone());
+            }
+            let _ = terminal.draw(|frame| self.draw_picker(frame, &mut picker))?;
+            if !event::poll(POLL_INTERVAL)? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match picker.handle_key(key, self) {
+                    Action::Continue => (),
+                    Action::Choose(indices) => return Ok(Some(indices)),
+                    Action::Quit => return Ok(None),
+                }
+            }
+        }
+    }
+
+    /// Runs `git ls-remote` on a separate thread to list the branches of the fork at `index` of
+    /// `available_forks`, then sends them via `sender`.  The fork is queried over HTTPS so that
+    /// SSH can't prompt for a passphrase while the terminal is in raw mode.
+    fn list_branches_in_background(&self, index: usize, sender: Sender<(usize, Preview)>) {
+        let mut url = self.available_forks[index].url.clone();
+        url.change_to_https();
+        let git = Arc::clone(&self.git);
+        let mut command = git.command();
+        let _ = command
+            .args(["ls-remote", "--heads", url.value()])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .stderr(Stdio::null());
+        let _ = thread::spawn(move || {
+            let preview = match git.output(&mut command) {
+                Ok(output) if output.status.success() => {
+                    Preview::Branches(branch_names(&String::from_utf8_lossy(&output.stdout)))
+                }
+                _ => Preview::Failed,
+            };
+            let _ = sender.send((index, preview));
+        });
+    }
+
+    /// Draws the search box, the matching forks, the highlighted fork's branches and the key help.
+    fn draw_picker(&self, frame: &mut Frame, picker: &mut Picker) {
+        let [search_area, main_area, help_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(main_area);
+
+        let search =
+            Paragraph::new(picker.search.as_str()).block(Block::bordered().title(" Search "));
+        frame.render_widget(search, search_area);
+        let cursor_offset = u16::try_from(picker.search.chars().count()).unwrap_or(u16::MAX);
+        frame.set_cursor_position((
+            search_area
+                .x
+                .saturating_add(1)
+                .saturating_add(cursor_offset),
+            search_area.y + 1,
+        ));
+
+        let rows: Vec<_> = picker
+            .matches
+            .iter()
+            .map(|&index| {
+                let fork = &self.available_forks[index];
+                (index, fork, fork.details())
+            })
+            .collect();
+        let widths = self.detail_column_widths(&rows);
+        let items: Vec<_> = rows
+            .iter()
+            .map(|(index, fork, details)| {
+                let mark = match (picker.allows_several, picker.marked.contains(index)) {
+                    (false, _) => "",
+                    (true, false) => "  ",
+                    (true, true) => "✓ ",
+                };
+                let owner_width = widths[0];
+                let mut row = format!(
+                    "{mark}{:<owner_width$}{}",
+                    fork.owner.0,
+                    format_details(details, &widths)
+                );
+                if self.org_members.contains(&fork.owner) {
+                    let _ = write!(row, " (member of {})", self.main_fork_owner.0);
+                }
+                if let Some(parent) = &fork.parent {
+                    let _ = write!(row, " (fork of {parent})");
+                }
+                ListItem::new(row.trim_end().to_string())
+            })
+            .collect();
+        let title = format!(
+            " Forks ({} of {}) ",
+            picker.matches.len(),
+            self.available_forks.len()
+        );
+        let list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut picker.list_state);
+
+        let (title, lines) = match picker.highlighted() {
+            Some(index) => {
+                let title = format!(" Branches of {} ", self.available_forks[index].owner.0);
+                let lines = match picker.previews.get(&index) {
+                    None | Some(Preview::Loading) => vec![Line::from("Loading...")],
+                    Some(Preview::Failed) => vec![Line::from("Couldn't list the branches.")],
+                    Some(Preview::Branches(branches)) if branches.is_empty() => {
+                        vec![Line::from("No branches.")]
+                    }
+                    Some(Preview::Branches(branches)) => branches
+                        .iter()
+                        .map(|branch| Line::from(branch.as_str()))
+                        .collect(),
+                };
+                (title, lines)
+            }
+            None => (" Branches ".to_string(), Vec::new()),
+        };
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            preview_area,
+        );
+
+        let key_help = if picker.allows_several {
+            KEY_HELP
+        } else {
+            SINGLE_KEY_HELP
+        };
+        frame.render_widget(Paragraph::new(key_help), help_area);
+    }
+}
+
+/// Returns the names of the branches listed in `output` from `git ls-remote --heads`.
+fn branch_names(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('\t')?.1.strip_prefix("refs/heads/"))
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::{forge::ForgeKind, Fork, Name, Owner, Url};
+    use ratatui::{backend::TestBackend, buffer::Cell, Terminal};
+
+    fn repo_with_forks(owners: &[&str]) -> Repo {
+        let mut repo = Repo::new_uninitialised();
+        for owner in owners {
+            repo.available_forks.push(Fork::new(
+                Owner(owner.to_string()),
+                Name("cargo".to_string()),
+                Url::of_forge(
+                    ForgeKind::GitHub,
+                    &format!("git@github.com:{owner}/cargo.git"),
+                ),
+                None,
+            ));
+        }
+        repo
+    }
+
+    fn press(picker: &mut Picker, repo: &Repo, code: KeyCode) -> Action {
+        picker.handle_key(KeyEvent::new(code, KeyModifiers::NONE), repo)
+    }
+
+    #[test]
+    fn picker_keys() {
+        let repo = repo_with_forks(&["alice", "Fraser999", "freddie"]);
+        let mut picker = Picker::new(&repo, Some(1));
+        assert_eq!(picker.highlighted(), Some(1));
+        assert_eq!(press(&mut picker, &repo, KeyCode::Down), Action::Continue);
+        assert_eq!(picker.highlighted(), Some(2));
+        let _ = press(&mut picker, &repo, KeyCode::PageDown);
+        assert_eq!(picker.highlighted(), Some(2));
+        let _ = press(&mut picker, &repo, KeyCode::Home);
+        assert_eq!(picker.highlighted(), Some(0));
+
+        for character in "fre".chars() {
+            let _ = press(&mut picker, &repo, KeyCode::Char(character));
+        }
+        assert_eq!(picker.matches, [2, 1]);
+        assert_eq!(
+            press(&mut picker, &repo, KeyCode::Enter),
+            Action::Choose(vec![2])
+        );
+        let _ = press(&mut picker, &repo, KeyCode::Backspace);
+        assert_eq!(picker.search, "fr");
+
+        let _ = press(&mut picker, &repo, KeyCode::Char('z'));
+        assert!(picker.matches.is_empty());
+        assert_eq!(press(&mut picker, &repo, KeyCode::Enter), Action::Continue);
+        assert_eq!(press(&mut picker, &repo, KeyCode::Esc), Action::Continue);
+        assert_eq!(picker.matches, [0, 1, 2]);
+        assert_eq!(press(&mut picker, &repo, KeyCode::Esc), Action::Quit);
+        let control_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(picker.handle_key(control_c, &repo), Action::Quit);
+    }
+
+    #[test]
+    fn picker_marks() {
+        let mut repo = repo_with_forks(&["alice", "Fraser999", "freddie"]);
+        let mut picker = Picker::new(&repo, Some(2));
+        let _ = press(&mut picker, &repo, KeyCode::Char(' '));
+        let _ = press(&mut picker, &repo, KeyCode::Home);
+        let _ = press(&mut picker, &repo, KeyCode::Char(' '));
+        let _ = press(&mut picker, &repo, KeyCode::Down);
+        let _ = press(&mut picker, &repo, KeyCode::Char(' '));
+        let _ = press(&mut picker, &repo, KeyCode::Char(' '));
+        assert_eq!(picker.marked, [2, 0]);
+        assert_eq!(
+            press(&mut picker, &repo, KeyCode::Enter),
+            Action::Choose(vec![2, 0])
+        );
+
+        repo.options.alias = Some("upstream".to_string());
+        let mut picker = Picker::new(&repo, Some(2));
+        let _ = press(&mut picker, &repo, KeyCode::Char(' '));
+        assert!(picker.marked.is_empty());
+        assert_eq!(picker.search, " ");
+    }
+
+    #[test]
+    fn draw_picker() {
+        let repo = repo_with_forks(&["alice", "Fraser999"]);
+        let mut picker = Picker::new(&repo, None);
+        picker.marked.push(0);
+        let _ = picker
+            .previews
+            .insert(0, Preview::Branches(vec!["main".to_string()]));
+        let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+        let _ = terminal
+            .draw(|frame| repo.draw_picker(frame, &mut picker))
+            .unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(Cell::symbol)
+            .collect();
+        assert!(screen.contains("Forks (2 of 2)"));
+        assert!(screen.contains("> ✓ alice"));
+        assert!(screen.contains("  Fraser999"));
+        assert!(screen.contains("Branches of alice"));
+        assert!(screen.contains("main"));
+    }
+
+    #[test]
+    fn parse_branch_names() {
+        let output = "1234\trefs/heads/main\nabcd\trefs/heads/fix/parser\nef01\tHEAD\n";
+        assert_eq!(branch_names(output), ["main", "fix/parser"]);
+        assert!(branch_names("").is_empty());
+    }
+}
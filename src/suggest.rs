@@ -0,0 +1,471 @@
+use super::{pattern::Pattern, remote_name::RemoteNameTemplate};
+use std::{collections::HashMap, fmt::Debug};
+
+/// The alias suggested for the main fork/source if `add-remote.mainForkOwnerAlias` isn't set.
+pub const DEFAULT_MAIN_FORK_OWNER_ALIAS: &str = "upstream";
+
+/// A snapshot of the Git config values which affect the suggestions.
+#[derive(Default, Debug)]
+pub struct ConfigSnapshot {
+    /// The value of `add-remote.preferredFork`.
+    pub preferred_fork: Option<Pattern>,
+    /// The value of `add-remote.mainForkOwnerAlias`.
+    pub main_fork_owner_alias: Option<String>,
+    /// The entries under the subkey `add-remote.forkAlias`, keyed by the lowercased owner.
+    pub fork_aliases: HashMap<String, String>,
+    /// The value of `add-remote.remoteName`.
+    pub remote_name: Option<RemoteNameTemplate>,
+}
+
+/// The plain data about a single fork available to be added.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Candidate<'a> {
+    /// The owner of the fork.
+    pub owner: &'a str,
+    /// The repository name of the fork.
+    pub name: &'a str,
+    /// Whether the owner is a member of the organisation/group which owns the main fork/source.
+    pub is_org_member: bool,
+    /// The number of days since the fork was last active, if known.
+    pub days_since_activity: Option<i64>,
+}
+
+/// The plain data from which suggestions are made.
+#[derive(Debug)]
+pub struct SuggestionContext<'a> {
+    /// The forks available to be added, in the order in which they're listed.
+    pub available_forks: Vec<Candidate<'a>>,
+    /// The owner of the main fork/source.
+    pub main_fork_owner: &'a str,
+    /// The relevant Git config values.
+    pub config: &'a ConfigSnapshot,
+}
+
+impl SuggestionContext<'_> {
+    /// Whether `owner` is the owner of the main fork/source.
+    pub fn is_main_fork_owner(&self, owner: &str) -> bool {
+        owner.eq_ignore_ascii_case(self.main_fork_owner)
+    }
+
+    /// Whether `owner` has been chosen previously, i.e. has an entry under `add-remote.forkAlias`.
+    pub fn was_previously_chosen(&self, owner: &str) -> bool {
+        self.config.fork_aliases.contains_key(&owner.to_lowercase())
+    }
+}
+
+/// A heuristic for suggesting the defaults offered to the user.
+pub trait Strategy: Debug + Send + Sync {
+    /// Suggests an index of `context.available_forks` to use as a default for the user's choice,
+    /// or `None` if there's no obvious candidate.
+    fn suggest_fork(&self, context: &SuggestionContext) -> Option<usize>;
+
+    /// Explains why the fork at `index` of `context.available_forks` was suggested by
+    /// `suggest_fork()`, completing the sentence "The default is <index> because ...".
+    fn explain_fork(&self, context: &SuggestionContext, index: usize) -> String;
+
+    /// Suggests a name to use for the remote for `candidate`.
+    fn suggest_alias(&self, context: &SuggestionContext, candidate: &Candidate) -> String {
+        self.suggest_alias_with_reason(context, candidate).0
+    }
+
+    /// Suggests a name to use for the remote for `candidate`, along with the reason for it.  Uses
+    /// the Git config value for `add-remote.mainForkOwnerAlias` (or "upstream" if this is not set)
+    /// if the owner is the main fork/source owner, then falls back to the map of known users
+    /// (entries under the Git config subkey of `add-remote.forkAlias`), then the expansion of
+    /// `add-remote.remoteName`, and finally suggests the owner name.
+    fn suggest_alias_with_reason(
+        &self,
+        context: &SuggestionContext,
+        candidate: &Candidate,
+    ) -> (String, String) {
+        let owner = candidate.owner;
+        if context.is_main_fork_owner(owner) {
+            return match &context.config.main_fork_owner_alias {
+                Some(alias) => (
+                    alias.clone(),
+                    format!(
+                        "'{owner}' owns the main fork/source, and that's the value of \
+                         add-remote.mainForkOwnerAlias"
+                    ),
+                ),
+                None => (
+                    DEFAULT_MAIN_FORK_OWNER_ALIAS.to_string(),
+                    format!(
+                        "'{owner}' owns the main fork/source, and add-remote.mainForkOwnerAlias \
+                         isn't set"
+                    ),
+                ),
+            };
+        }
+        if let Some(alias) = context.config.fork_aliases.get(&owner.to_lowercase()) {
+            return (
+                alias.clone(),
+                format!("that's the value of add-remote.forkAlias.{owner}"),
+            );
+        }
+        if let Some(template) = &context.config.remote_name {
+            return (
+                template.expand(owner, candidate.name),
+                "that's the expansion of add-remote.remoteName".to_string(),
+            );
+        }
+        (
+            owner.to_string(),
+            "that's the fork owner's name, and neither add-remote.forkAlias nor \
+             add-remote.remoteName apply"
+                .to_string(),
+        )
+    }
+}
+
+/// The explanation given when the only available fork is suggested.
+const ONLY_FORK_REASON: &str = "it's the only fork available";
+
+/// The original strict order of precedence: favours the available fork if there is only one, then
+/// the main fork/source owner, then the first fork matching `add-remote.preferredFork`.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Precedence;
+
+impl Strategy for Precedence {
+    fn suggest_fork(&self, context: &SuggestionContext) -> Option<usize> {
+        if context.available_forks.len() == 1 {
+            return Some(0);
+        }
+        if let Some(index) = context
+            .available_forks
+            .iter()
+            .position(|candidate| context.is_main_fork_owner(candidate.owner))
+        {
+            return Some(index);
+        }
+        let preferred = context.config.preferred_fork.as_ref()?;
+        context
+            .available_forks
+            .iter()
+            .position(|candidate| preferred.matches(candidate.owner))
+    }
+
+    fn explain_fork(&self, context: &SuggestionContext, index: usize) -> String {
+        let owner = context.available_forks[index].owner;
+        if context.available_forks.len() == 1 {
+            ONLY_FORK_REASON.to_string()
+        } else if context.is_main_fork_owner(owner) {
+            format!("'{owner}' owns the main fork/source")
+        } else {
+            format!("'{owner}' matches add-remote.preferredFork")
+        }
+    }
+}
+
+/// The number of days over which the weight given to a fork's recent activity decays to zero.
+const ACTIVITY_WINDOW_DAYS: i64 = 365;
+
+/// The weights given to each of the signals considered by `Scoring`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Weights {
+    /// Added if the fork is the main fork/source.
+    pub source_owner: i64,
+    /// Added if the owner matches `add-remote.preferredFork`.
+    pub preferred_fork: i64,
+    /// Added in full for a fork active today, decaying linearly to zero over a year.
+    pub recent_activity: i64,
+    /// Added if the owner is a member of the main fork/source's organisation/group.
+    pub org_member: i64,
+    /// Added if the owner has been chosen previously.
+    pub previously_chosen: i64,
+}
+
+impl Weights {
+    /// Returns each weight paired with its name as used in the Git config subkey
+    /// `add-remote.weight`.
+    pub fn named_mut(&mut self) -> [(&'static str, &mut i64); 5] {
+        [
+            ("sourceOwner", &mut self.source_owner),
+            ("preferredFork", &mut self.preferred_fork),
+            ("recentActivity", &mut self.recent_activity),
+            ("orgMember", &mut self.org_member),
+            ("previouslyChosen", &mut self.previously_chosen),
+        ]
+    }
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights {
+            source_owner: 100,
+            preferred_fork: 60,
+            recent_activity: 30,
+            org_member: 20,
+            previously_chosen: 40,
+        }
+    }
+}
+
+/// Scores each available fork by summing the weights of the signals which apply to it, and
+/// favours the highest-scoring one, or the first of these in the case of a tie.  Nothing is
+/// favoured if no fork scores above zero, unless there is only one fork available.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Scoring {
+    pub weights: Weights,
+}
+
+impl Scoring {
+    /// Returns the score for `candidate`.
+    pub fn score(&self, context: &SuggestionContext, candidate: &Candidate) -> i64 {
+        self.signals(context, candidate)
+            .iter()
+            .map(|(_, weight)| weight)
+            .sum()
+    }
+
+    /// Returns a description of each signal which contributes to the score for `candidate`,
+    /// paired with its contribution.
+    pub fn signals(
+        &self,
+        context: &SuggestionContext,
+        candidate: &Candidate,
+    ) -> Vec<(String, i64)> {
+        let weights = &self.weights;
+        let mut signals = Vec::new();
+        if context.is_main_fork_owner(candidate.owner) {
+            signals.push((
+                "owns the main fork/source".to_string(),
+                weights.source_owner,
+            ));
+        }
+        if let Some(preferred) = &context.config.preferred_fork {
+            if preferred.matches(candidate.owner) {
+                signals.push((
+                    "matches add-remote.preferredFork".to_string(),
+                    weights.preferred_fork,
+                ));
+            }
+        }
+        if let Some(days) = candidate.days_since_activity {
+            let remaining_days = (ACTIVITY_WINDOW_DAYS - days.max(0)).max(0);
+            signals.push((
+                format!("was active {} days ago", days.max(0)),
+                weights.recent_activity * remaining_days / ACTIVITY_WINDOW_DAYS,
+            ));
+        }
+        if candidate.is_org_member {
+            signals.push((
+                "is a member of the main fork/source's organisation/group".to_string(),
+                weights.org_member,
+            ));
+        }
+        if context.was_previously_chosen(candidate.owner) {
+            signals.push((
+                "has an add-remote.forkAlias entry".to_string(),
+                weights.previously_chosen,
+            ));
+        }
+        signals.retain(|(_, weight)| *weight != 0);
+        signals
+    }
+}
+
+impl Strategy for Scoring {
+    fn suggest_fork(&self, context: &SuggestionContext) -> Option<usize> {
+        if context.available_forks.len() == 1 {
+            return Some(0);
+        }
+        let mut best: Option<(usize, i64)> = None;
+        for (index, candidate) in context.available_forks.iter().enumerate() {
+            let score = self.score(context, candidate);
+            if score > 0 && best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((index, score));
+            }
+        }
+        best.map(|(index, _)| index)
+    }
+
+    fn explain_fork(&self, context: &SuggestionContext, index: usize) -> String {
+        if context.available_forks.len() == 1 {
+            return ONLY_FORK_REASON.to_string();
+        }
+        let candidate = &context.available_forks[index];
+        let signals = self.signals(context, candidate);
+        let total: i64 = signals.iter().map(|(_, weight)| weight).sum();
+        let breakdown: Vec<String> = signals
+            .iter()
+            .map(|(signal, weight)| format!("{signal} ({weight:+})"))
+            .collect();
+        format!(
+            "'{}' has the highest score, {}: it {}",
+            candidate.owner,
+            total,
+            breakdown.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context<'a>(
+        available_forks: &[&'a str],
+        config: &'a ConfigSnapshot,
+    ) -> SuggestionContext<'a> {
+        SuggestionContext {
+            available_forks: available_forks.iter().copied().map(candidate).collect(),
+            main_fork_owner: "CasperLabs",
+            config,
+        }
+    }
+
+    fn candidate(owner: &str) -> Candidate<'_> {
+        Candidate {
+            owner,
+            name: "casper-node",
+            ..Candidate::default()
+        }
+    }
+
+    #[test]
+    fn suggest_fork() {
+        let mut config = ConfigSnapshot::default();
+        assert_eq!(
+            Precedence.suggest_fork(&context(&["solo"], &config)),
+            Some(0)
+        );
+        assert_eq!(
+            Precedence.suggest_fork(&context(&["a", "b"], &config)),
+            None
+        );
+        assert_eq!(
+            Precedence.suggest_fork(&context(&["a", "casperlabs", "b"], &config)),
+            Some(1)
+        );
+
+        config.preferred_fork = Some(Pattern::new("b*").unwrap());
+        assert_eq!(
+            Precedence.suggest_fork(&context(&["a", "bob", "bill"], &config)),
+            Some(1)
+        );
+        // The main fork/source owner beats the preferred fork.
+        assert_eq!(
+            Precedence.suggest_fork(&context(&["bob", "CasperLabs"], &config)),
+            Some(1)
+        );
+        assert_eq!(
+            Precedence.suggest_fork(&context(&["a", "c"], &config)),
+            None
+        );
+    }
+
+    #[test]
+    fn explain_fork() {
+        let config = ConfigSnapshot {
+            preferred_fork: Some(Pattern::new("b*").unwrap()),
+            ..ConfigSnapshot::default()
+        };
+        assert_eq!(
+            Precedence.explain_fork(&context(&["solo"], &config), 0),
+            "it's the only fork available"
+        );
+        assert_eq!(
+            Precedence.explain_fork(&context(&["bob", "CasperLabs"], &config), 1),
+            "'CasperLabs' owns the main fork/source"
+        );
+        assert_eq!(
+            Precedence.explain_fork(&context(&["a", "bob"], &config), 1),
+            "'bob' matches add-remote.preferredFork"
+        );
+
+        let scoring = Scoring::default();
+        let mut ctx = context(&["a", "bob"], &config);
+        ctx.available_forks[1].days_since_activity = Some(73);
+        ctx.available_forks[1].is_org_member = true;
+        assert_eq!(
+            scoring.explain_fork(&ctx, 1),
+            "'bob' has the highest score, 104: it matches add-remote.preferredFork (+60), was \
+             active 73 days ago (+24), is a member of the main fork/source's organisation/group \
+             (+20)"
+        );
+    }
+
+    #[test]
+    fn suggest_fork_by_score() {
+        let mut config = ConfigSnapshot::default();
+        let scoring = Scoring::default();
+        assert_eq!(scoring.suggest_fork(&context(&["solo"], &config)), Some(0));
+        assert_eq!(scoring.suggest_fork(&context(&["a", "b"], &config)), None);
+        assert_eq!(
+            scoring.suggest_fork(&context(&["a", "casperlabs", "b"], &config)),
+            Some(1)
+        );
+
+        // Recent activity and org membership can outweigh a preferred fork.
+        config.preferred_fork = Some(Pattern::new("b*").unwrap());
+        let mut ctx = context(&["a", "bob", "carol"], &config);
+        assert_eq!(scoring.suggest_fork(&ctx), Some(1));
+        ctx.available_forks[2].is_org_member = true;
+        ctx.available_forks[2].days_since_activity = Some(0);
+        assert_eq!(scoring.score(&ctx, &ctx.available_forks[2]), 50);
+        assert_eq!(scoring.suggest_fork(&ctx), Some(1));
+        ctx.available_forks[0].days_since_activity = Some(1000);
+        assert_eq!(scoring.score(&ctx, &ctx.available_forks[0]), 0);
+        let _ = config
+            .fork_aliases
+            .insert("carol".to_string(), "C".to_string());
+        let mut ctx = context(&["a", "bob", "Carol"], &config);
+        ctx.available_forks[2].days_since_activity = Some(73);
+        assert_eq!(scoring.score(&ctx, &ctx.available_forks[2]), 64);
+        assert_eq!(scoring.suggest_fork(&ctx), Some(2));
+
+        // Weights can be adjusted, and ties go to the first in the list.
+        let mut scoring = Scoring::default();
+        scoring.weights.previously_chosen = 0;
+        scoring.weights.recent_activity = 0;
+        scoring.weights.preferred_fork = -1;
+        assert_eq!(scoring.suggest_fork(&ctx), None);
+        ctx.available_forks[0].is_org_member = true;
+        ctx.available_forks[2].is_org_member = true;
+        assert_eq!(scoring.suggest_fork(&ctx), Some(0));
+    }
+
+    #[test]
+    fn suggest_alias() {
+        let mut config = ConfigSnapshot::default();
+        let _ = config
+            .fork_aliases
+            .insert("hsutter".to_string(), "Herb".to_string());
+        assert_eq!(
+            Precedence.suggest_alias(&context(&[], &config), &candidate("casperlabs")),
+            "upstream"
+        );
+        assert_eq!(
+            Precedence.suggest_alias(&context(&[], &config), &candidate("HSutter")),
+            "Herb"
+        );
+        assert_eq!(
+            Precedence.suggest_alias(&context(&[], &config), &candidate("anthonywilliams")),
+            "anthonywilliams"
+        );
+
+        config.main_fork_owner_alias = Some("owner".to_string());
+        assert_eq!(
+            Precedence.suggest_alias(&context(&[], &config), &candidate("CasperLabs")),
+            "owner"
+        );
+
+        config.remote_name = Some(RemoteNameTemplate::new("{owner}-{repo}").unwrap());
+        assert_eq!(
+            Precedence.suggest_alias(&context(&[], &config), &candidate("anthonywilliams")),
+            "anthonywilliams-casper-node"
+        );
+        assert_eq!(
+            Precedence.suggest_alias(&context(&[], &config), &candidate("HSutter")),
+            "Herb"
+        );
+
+        let (_, reason) =
+            Precedence.suggest_alias_with_reason(&context(&[], &config), &candidate("HSutter"));
+        assert_eq!(reason, "that's the value of add-remote.forkAlias.HSutter");
+        let (_, reason) =
+            Precedence.suggest_alias_with_reason(&context(&[], &config), &candidate("a"));
+        assert_eq!(reason, "that's the expansion of add-remote.remoteName");
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    convert::TryFrom,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The number of seconds in a day.
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Returns the number of days since the Unix epoch of the date at the start of `timestamp`, which
+/// should be in ISO 8601 format as returned by the GitLab and GitHub APIs (e.g.
+/// "2024-01-31T12:34:56Z").  Returns `None` if the date can't be parsed.
+pub fn days_since_epoch(timestamp: &str) -> Option<i64> {
+    let mut parts = timestamp.get(..10)?.split('-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day))
+}
+
+/// Returns the number of days since the Unix epoch of the current date (UTC).
+pub fn today() -> i64 {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    i64::try_from(seconds / SECONDS_PER_DAY).unwrap_or_default()
+}
+
+/// Returns the number of days since the Unix epoch of the date `duration` before the current date,
+/// counting only whole days of `duration`.
+pub fn days_ago(duration: Duration) -> i64 {
+    let days = i64::try_from(duration.as_secs() / SECONDS_PER_DAY).unwrap_or(i64::MAX);
+    today().saturating_sub(days)
+}
+
+/// Converts a date in the proleptic Gregorian calendar to the number of days since the Unix epoch,
+/// using Howard Hinnant's `days_from_civil` algorithm.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{days_ago, days_since_epoch, today};
+    use std::time::Duration;
+
+    #[test]
+    fn parse_days_since_epoch() {
+        assert_eq!(days_since_epoch("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(days_since_epoch("2000-03-01T10:00:00.000Z"), Some(11_017));
+        assert_eq!(days_since_epoch("2024-02-29"), Some(19_782));
+        assert_eq!(days_since_epoch("1969-12-31T23:59:59Z"), Some(-1));
+        assert!(days_since_epoch("2024-13-01T00:00:00Z").is_none());
+        assert!(days_since_epoch("yesterday").is_none());
+        assert!(days_since_epoch("").is_none());
+    }
+
+    #[test]
+    fn count_days_ago() {
+        assert_eq!(days_ago(Duration::from_hours(23)), today());
+        assert_eq!(days_ago(Duration::from_hours(72)), today() - 3);
+    }
+}
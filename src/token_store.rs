@@ -0,0 +1,80 @@
+use keyring::{Entry, Error as KeyringError};
+
+/// The service under which tokens are stored in the OS keyring, with the forge's host as the user.
+const SERVICE: &str = "add-remote";
+
+/// The suffix added to the host to form the user under which the details needed to refresh an
+/// OAuth token are stored.
+const REFRESH_SUFFIX: &str = "#refresh";
+
+/// Returns the keyring entry holding the token for `host`.
+fn entry(host: &str) -> Result<Entry, String> {
+    Entry::new(SERVICE, &host.to_lowercase())
+        .map_err(|error| format!("failed to access the entry for {host}: {error}"))
+}
+
+/// Returns the keyring entry holding the details needed to refresh the token for `host`.
+fn refresh_entry(host: &str) -> Result<Entry, String> {
+    entry(&format!("{host}{REFRESH_SUFFIX}"))
+}
+
+/// Returns the password of `entry`, or `None` if it has none.  A keyring which can't be reached
+/// (e.g. when no Secret Service is running) is treated as holding no tokens, since most users
+/// won't have stored one.
+fn read(entry: &Entry) -> Result<Option<String>, KeyringError> {
+    match entry.get_password() {
+        Ok(password) => Ok(Some(password)),
+        Err(
+            KeyringError::NoEntry
+            | KeyringError::NoStorageAccess(_)
+            | KeyringError::PlatformFailure(_),
+        ) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Removes `entry`, returning false if it didn't exist.
+fn remove(entry: &Entry) -> Result<bool, KeyringError> {
+    match entry.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(KeyringError::NoEntry) => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// Returns the token stored for `host`, or `None` if there isn't one.
+pub fn get(host: &str) -> Result<Option<String>, String> {
+    read(&entry(host)?).map_err(|error| format!("failed to read the token for {host}: {error}"))
+}
+
+/// Stores `token` for `host`, replacing any token already stored for it along with any details
+/// for refreshing that token.
+pub fn set(host: &str, token: &str) -> Result<(), String> {
+    entry(host)?
+        .set_password(token)
+        .map_err(|error| format!("failed to store the token for {host}: {error}"))?;
+    let _ = remove(&refresh_entry(host)?);
+    Ok(())
+}
+
+/// Removes the token stored for `host` and any details for refreshing it, returning false if there
+/// wasn't a token.
+pub fn delete(host: &str) -> Result<bool, String> {
+    let _ = remove(&refresh_entry(host)?);
+    remove(&entry(host)?).map_err(|error| format!("failed to remove the token for {host}: {error}"))
+}
+
+/// Returns the details stored for refreshing the OAuth token for `host`, or `None` if there are
+/// none.
+pub fn get_refresh(host: &str) -> Result<Option<String>, String> {
+    read(&refresh_entry(host)?)
+        .map_err(|error| format!("failed to read the refresh token for {host}: {error}"))
+}
+
+/// Stores `details` for refreshing the OAuth token for `host`.  This must follow `set`, which
+/// removes them.
+pub fn set_refresh(host: &str, details: &str) -> Result<(), String> {
+    refresh_entry(host)?
+        .set_password(details)
+        .map_err(|error| format!("failed to store the refresh token for {host}: {error}"))
+}
@@ -0,0 +1,283 @@
+//! A harness which runs `add-remote` end to end against scripted repositories.
+//!
+//! Each `Sandbox` has its own home directory and a working repository whose `origin` is on the
+//! forge `forge.test`.  That forge is configured as a generic forge whose API is a local mock
+//! server, and its repositories are bare repositories in the sandbox, reached via a stand-in for
+//! `ssh`, so nothing leaves the machine.
+
+// Each test crate uses only some of the harness.
+#![allow(dead_code)]
+
+use serde_json::{json, Value};
+use std::{
+    env, fs,
+    io::Write,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::{self, Command, Output, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use tokio::runtime::{self, Runtime};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+/// The host of the scripted forge.
+pub const HOST: &str = "forge.test";
+/// The owner of the repository cloned as `origin`.
+pub const UPSTREAM: &str = "upstream";
+/// The name of every repository on the scripted forge.
+pub const PROJECT: &str = "project";
+
+/// Distinguishes the sandboxes created by the tests in one process.
+static SANDBOX_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Stands in for `ssh` as Git runs it, i.e. as `<script> <host> <command>`, by running the command
+/// in the directory holding the forge's repositories.
+const FAKE_SSH: &str = "#!/bin/sh
+cd \"$(dirname \"$0\")/forge\" && exec sh -c \"$2\"
+";
+
+/// A temporary home directory holding a working repository, the bare repositories which stand in
+/// for the forge's, and the mock of the forge's API.  It's all removed when dropped.
+pub struct Sandbox {
+    root: PathBuf,
+    /// Runs the mock server.
+    runtime: Runtime,
+    server: MockServer,
+}
+
+impl Sandbox {
+    /// Creates a sandbox whose working repository has one commit and whose `origin` is
+    /// `upstream/project` with the branch `main`.
+    pub fn new() -> Self {
+        let root = env::temp_dir().join(format!(
+            "add-remote-e2e-{}-{}",
+            process::id(),
+            SANDBOX_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("home")).unwrap();
+        fs::create_dir_all(root.join("forge")).unwrap();
+        let fake_ssh = root.join("ssh");
+        fs::write(&fake_ssh, FAKE_SSH).unwrap();
+        fs::set_permissions(&fake_ssh, fs::Permissions::from_mode(0o755)).unwrap();
+        let runtime = runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let server = runtime.block_on(MockServer::start());
+        let sandbox = Sandbox {
+            root,
+            runtime,
+            server,
+        };
+
+        let repo_url = format!("{}/repos/{{owner}}/{{repo}}", sandbox.server.uri());
+        let forks_url = format!("{repo_url}/forks");
+        for (key, value) in [
+            ("user.name", "Tester"),
+            ("user.email", "tester@example.com"),
+            ("init.defaultBranch", "main"),
+            ("add-remote.forge.test.repoUrl", repo_url.as_str()),
+            ("add-remote.forge.test.forksUrl", forks_url.as_str()),
+            ("add-remote.forge.test.token", "unused"),
+            ("add-remote.offerMaintenance", "false"),
+        ] {
+            sandbox.git_global(&["config", "--global", key, value]);
+        }
+
+        sandbox.create_forge_repo(UPSTREAM, &["main"]);
+        let work = sandbox.work_dir();
+        sandbox.git_global(&["clone", "--quiet", &ssh_url(UPSTREAM), path_str(&work)]);
+        sandbox
+    }
+
+    /// Creates `owner`'s repository on the forge with the given branches, each having a commit of
+    /// its own, and makes the mock API describe it.  Unless `owner` is `UPSTREAM`, the repository
+    /// is a fork of `upstream/project`, though it's only listed among its forks once `serve_forks`
+    /// is called.
+    pub fn create_forge_repo(&self, owner: &str, branches: &[&str]) {
+        let bare = self
+            .root
+            .join("forge")
+            .join(owner)
+            .join(format!("{PROJECT}.git"));
+        let scratch = self.root.join("scratch").join(owner);
+        self.git_global(&["init", "--quiet", "--bare", path_str(&bare)]);
+        self.git_global(&["init", "--quiet", path_str(&scratch)]);
+        let in_scratch = |args: &[&str]| {
+            let mut all_args = vec!["-C", path_str(&scratch)];
+            all_args.extend(args);
+            self.git_global(&all_args);
+        };
+        in_scratch(&["commit", "--quiet", "--allow-empty", "-m", "initial"]);
+        for branch in branches {
+            in_scratch(&["checkout", "--quiet", "-B", branch, "main"]);
+            let message = format!("{owner}'s {branch}");
+            in_scratch(&["commit", "--quiet", "--allow-empty", "-m", &message]);
+            in_scratch(&["push", "--quiet", path_str(&bare), branch]);
+        }
+
+        let mut details = repo_json(owner);
+        if owner != UPSTREAM {
+            details["parent"] = repo_json(UPSTREAM);
+        }
+        let mock = Mock::given(method("GET"))
+            .and(path(format!("/repos/{owner}/{PROJECT}")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(details));
+        self.runtime.block_on(mock.mount(&self.server));
+    }
+
+    /// Makes the mock API list `owners`' forks of `upstream/project`, in that order.
+    pub fn serve_forks(&self, owners: &[&str]) {
+        let forks: Vec<Value> = owners.iter().map(|owner| repo_json(owner)).collect();
+        let mock = Mock::given(method("GET"))
+            .and(path(format!("/repos/{UPSTREAM}/{PROJECT}/forks")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(forks));
+        self.runtime.block_on(mock.mount(&self.server));
+    }
+
+    /// Makes the mock API answer requests for the forks of `upstream/project` with `status` and no
+    /// body.
+    pub fn fail_forks(&self, status: u16) {
+        let mock = Mock::given(method("GET"))
+            .and(path(format!("/repos/{UPSTREAM}/{PROJECT}/forks")))
+            .respond_with(ResponseTemplate::new(status));
+        self.runtime.block_on(mock.mount(&self.server));
+    }
+
+    /// Runs `add-remote` with `args` in the working repository, with `answers` piped to its stdin.
+    pub fn add_remote(&self, args: &[&str], answers: &str) -> Output {
+        let mut child = self
+            .command(env!("CARGO_BIN_EXE_add-remote"))
+            .current_dir(self.work_dir())
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(answers.as_bytes())
+            .unwrap();
+        child.wait_with_output().unwrap()
+    }
+
+    /// Runs `add-remote` as per `add_remote`, panicking unless it succeeds, and returns its stdout.
+    pub fn add_remote_ok(&self, args: &[&str], answers: &str) -> String {
+        let output = self.add_remote(args, answers);
+        assert!(output.status.success(), "{}", stdout(&output));
+        stdout(&output)
+    }
+
+    /// Runs `add-remote` as per `add_remote_ok` with `args`, which should include `--dry-run`, and
+    /// panics if the run changed the working repository's refs or local Git config.  Returns its
+    /// stdout, i.e. the Git commands it printed rather than ran.
+    pub fn add_remote_dry_run(&self, args: &[&str], answers: &str) -> String {
+        let state = || {
+            (
+                self.git(&["for-each-ref", "--format=%(refname) %(objectname)"]),
+                self.git(&["config", "--local", "--list"]),
+            )
+        };
+        let before = state();
+        let printed = self.add_remote_ok(args, answers);
+        assert_eq!(
+            state(),
+            before,
+            "'add-remote {}' changed the repository",
+            args.join(" ")
+        );
+        printed
+    }
+
+    /// Runs Git with `args` in the working repository, returning its stdout.
+    pub fn git(&self, args: &[&str]) -> String {
+        let work_dir = self.work_dir();
+        let mut all_args = vec!["-C", path_str(&work_dir)];
+        all_args.extend(args);
+        self.git_global(&all_args)
+    }
+
+    /// Returns the output of `git remote -v` in the working repository.
+    pub fn remotes(&self) -> String {
+        self.git(&["remote", "-v"])
+    }
+
+    /// Returns the path of the working repository.
+    pub fn work_dir(&self) -> PathBuf {
+        self.root.join("work")
+    }
+
+    /// Runs Git with `args` outside any repository, returning its stdout and panicking on failure.
+    fn git_global(&self, args: &[&str]) -> String {
+        let output = self.command("git").args(args).output().unwrap();
+        assert!(
+            output.status.success(),
+            "'git {}' failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    /// Returns a command running `program` isolated from the user's own config and cache.
+    fn command(&self, program: &str) -> Command {
+        let home = self.root.join("home");
+        let mut command = Command::new(program);
+        let _ = command
+            .current_dir(&self.root)
+            .env("HOME", &home)
+            .env("XDG_CONFIG_HOME", home.join(".config"))
+            .env("XDG_CACHE_HOME", home.join(".cache"))
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("GIT_SSH_COMMAND", self.root.join("ssh"))
+            .env("GIT_SSH_VARIANT", "simple")
+            .env("NO_COLOR", "1");
+        for var in [
+            "GIT_DIR",
+            "GIT_WORK_TREE",
+            "GIT_CONFIG_GLOBAL",
+            "GIT_CONFIG_PARAMETERS",
+            "GIT_CONFIG_COUNT",
+            "BROWSER",
+        ] {
+            let _ = command.env_remove(var);
+        }
+        command
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+/// Returns the SSH URL of `owner`'s repository on the forge.
+pub fn ssh_url(owner: &str) -> String {
+    format!("git@{HOST}:{owner}/{PROJECT}.git")
+}
+
+/// Returns `owner`'s repository as described by the mock API.
+fn repo_json(owner: &str) -> Value {
+    json!({
+        "owner": { "login": owner },
+        "name": PROJECT,
+        "ssh_url": ssh_url(owner),
+    })
+}
+
+/// Returns the text written to stdout by a run of `add-remote`.
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
@@ -0,0 +1,63 @@
+//! Checks the exit codes documented in the README, on which scripts wrapping `add-remote` rely, and
+//! that `--quiet` leaves nothing but the prompts and errors on stdout.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::{stdout, Sandbox};
+use std::process::Output;
+
+/// Returns the exit code of a run of `add-remote` as reported on Unix, i.e. modulo 256.
+fn exit_code(output: &Output) -> i32 {
+    output
+        .status
+        .code()
+        .expect("add-remote was killed by a signal")
+}
+
+#[test]
+fn not_a_repository() {
+    let sandbox = Sandbox::new();
+    std::fs::remove_dir_all(sandbox.work_dir().join(".git")).unwrap();
+    assert_eq!(exit_code(&sandbox.add_remote(&["list"], "")), 255);
+}
+
+#[test]
+fn no_forks_available() {
+    let sandbox = Sandbox::new();
+    sandbox.serve_forks(&[]);
+    assert_eq!(exit_code(&sandbox.add_remote(&["list"], "")), 242);
+
+    let output = sandbox.add_remote(&["--quiet"], "");
+    assert_eq!(exit_code(&output), 242);
+    assert_eq!(
+        stdout(&output),
+        "There are no forks available which aren't already a remote.\n"
+    );
+}
+
+#[test]
+fn auth_required() {
+    let sandbox = Sandbox::new();
+    sandbox.fail_forks(401);
+    assert_eq!(exit_code(&sandbox.add_remote(&["list"], "")), 241);
+}
+
+#[test]
+fn other_request_failure() {
+    let sandbox = Sandbox::new();
+    sandbox.fail_forks(404);
+    assert_eq!(exit_code(&sandbox.add_remote(&["list"], "")), 246);
+}
+
+#[test]
+fn quiet_add_prints_nothing() {
+    let sandbox = Sandbox::new();
+    sandbox.create_forge_repo("alice", &["main"]);
+    sandbox.serve_forks(&["alice"]);
+    let output = sandbox.add_remote(&["--quiet", "--fork", "alice", "--alias", "alice"], "");
+    assert_eq!(exit_code(&output), 0, "{}", stdout(&output));
+    assert_eq!(stdout(&output), "");
+    assert!(sandbox.remotes().contains("alice\t"));
+}
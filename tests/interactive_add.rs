@@ -0,0 +1,170 @@
+//! Runs the interactive flow of choosing a fork, choosing its alias and adding it as a remote, with
+//! the answers piped to `add-remote`'s stdin.  The harness stands in for `ssh` with a shell
+//! script, so these only run on Unix.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::{ssh_url, stdout, Sandbox, UPSTREAM};
+
+/// Returns the lines of `git remote -v` for `origin` and each of `remotes`, pairs of a remote added
+/// by `add-remote` and the owner of its repository.
+fn remote_listing(remotes: &[(&str, &str)]) -> String {
+    let mut remotes: Vec<(&str, String, String)> = remotes
+        .iter()
+        .map(|(alias, owner)| (*alias, ssh_url(owner), "disable_push".to_string()))
+        .collect();
+    remotes.push(("origin", ssh_url(UPSTREAM), ssh_url(UPSTREAM)));
+    remotes.sort();
+    remotes
+        .iter()
+        .map(|(alias, fetch_url, push_url)| {
+            format!("{alias}\t{fetch_url} (fetch)\n{alias}\t{push_url} (push)\n")
+        })
+        .collect()
+}
+
+/// Returns a sandbox whose forge has forks owned by Alice, with the branches `main` and
+/// `fix-parser`, and by Bob, with `main`.
+fn sandbox_with_forks() -> Sandbox {
+    let sandbox = Sandbox::new();
+    sandbox.create_forge_repo("alice", &["main", "fix-parser"]);
+    sandbox.create_forge_repo("bob", &["main"]);
+    sandbox.serve_forks(&["alice", "bob"]);
+    sandbox
+}
+
+#[test]
+fn add_chosen_fork_with_default_alias() {
+    let sandbox = sandbox_with_forks();
+    let output = sandbox.add_remote(&["--plain"], "0\n\n");
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(stdout(&output).contains("option 0: alice\noption 1: bob\n"));
+
+    assert_eq!(sandbox.remotes(), remote_listing(&[("alice", "alice")]));
+    assert_eq!(
+        sandbox.git(&["branch", "--remotes", "--list", "alice/*"]),
+        "  alice/fix-parser\n  alice/main\n"
+    );
+    assert_eq!(
+        sandbox.git(&["config", "remote.alice.addRemoteManaged"]),
+        "true\n"
+    );
+}
+
+#[test]
+fn custom_alias_is_stored_and_reused() {
+    let sandbox = sandbox_with_forks();
+    // Choose Bob's fork, name it "bobby", and accept the default of storing the alias.
+    let output = sandbox.add_remote(&["--plain"], "1\nbobby\n\n");
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert_eq!(sandbox.remotes(), remote_listing(&[("bobby", "bob")]));
+    assert_eq!(
+        sandbox.git(&["config", "--global", "add-remote.forkAlias.bob"]),
+        "bobby\n"
+    );
+
+    // Once the remote is removed, adding Bob's fork again offers the stored alias as the default.
+    let _ = sandbox.git(&["remote", "remove", "bobby"]);
+    let output = sandbox.add_remote(&["--plain"], "1\n\n");
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(stdout(&output).contains("The default is bobby."));
+    assert_eq!(sandbox.remotes(), remote_listing(&[("bobby", "bob")]));
+}
+
+#[test]
+fn go_back_to_choose_another_fork() {
+    let sandbox = sandbox_with_forks();
+    let output = sandbox.add_remote(&["--plain"], "0\n:b\n1\n\n");
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert_eq!(sandbox.remotes(), remote_listing(&[("bob", "bob")]));
+}
+
+#[test]
+fn remotes_can_be_named_q_or_b() {
+    let sandbox = sandbox_with_forks();
+    let output = sandbox.add_remote(&["--plain"], "0\nq\nn\n");
+    assert!(output.status.success(), "{}", stdout(&output));
+    let output = sandbox.add_remote(&["--plain"], "0\nb\nn\n");
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert_eq!(
+        sandbox.remotes(),
+        remote_listing(&[("b", "bob"), ("q", "alice")])
+    );
+}
+
+#[test]
+fn quitting_at_the_name_prompt_adds_nothing() {
+    let sandbox = sandbox_with_forks();
+    let output = sandbox.add_remote(&["--plain"], "0\n:q\n");
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert_eq!(sandbox.remotes(), remote_listing(&[]));
+}
+
+#[test]
+fn existing_remotes_are_not_listed() {
+    let sandbox = sandbox_with_forks();
+    assert!(sandbox.add_remote(&["--plain"], "0\n\n").status.success());
+
+    let output = sandbox.add_remote(&["--plain"], "q\n");
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(stdout(&output).contains("Available forks:\noption 0: bob\nEnter"));
+    assert_eq!(sandbox.remotes(), remote_listing(&[("alice", "alice")]));
+}
+
+#[test]
+fn quitting_adds_nothing() {
+    let sandbox = sandbox_with_forks();
+    let output = sandbox.add_remote(&["--plain"], "q\n");
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert_eq!(sandbox.remotes(), remote_listing(&[]));
+}
+
+#[test]
+fn input_ending_early_adds_nothing() {
+    let sandbox = sandbox_with_forks();
+    let output = sandbox.add_remote(&["--plain"], "0\n");
+    assert!(!output.status.success());
+    assert!(stdout(&output).contains("The input ended before this was answered."));
+    assert_eq!(sandbox.remotes(), remote_listing(&[]));
+}
+
+#[test]
+fn check_out_requested_branch() {
+    let sandbox = sandbox_with_forks();
+    let output = sandbox.add_remote(&["--plain", "--branch", "fix-parser"], "0\n\ny\n");
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert_eq!(sandbox.remotes(), remote_listing(&[("alice", "alice")]));
+    assert_eq!(
+        sandbox.git(&["rev-parse", "--abbrev-ref", "HEAD", "HEAD@{upstream}"]),
+        "fix-parser\nalice/fix-parser\n"
+    );
+}
+
+#[test]
+fn undo_hiding_a_fork() {
+    let sandbox = sandbox_with_forks();
+    sandbox.git(&["config", "--add", "add-remote.ignoreFork", "carol"]);
+    let output = sandbox.add_remote(&["--plain"], "h0\nq\n");
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(stdout(&output).contains(
+        "To undo this, run: git config --local --unset-all add-remote.ignoreFork '^alice$'\n"
+    ));
+    assert_eq!(
+        sandbox.git(&["config", "--get-all", "add-remote.ignoreFork"]),
+        "carol\nalice\n"
+    );
+
+    sandbox.git(&[
+        "config",
+        "--local",
+        "--unset-all",
+        "add-remote.ignoreFork",
+        "^alice$",
+    ]);
+    assert_eq!(
+        sandbox.git(&["config", "--get-all", "add-remote.ignoreFork"]),
+        "carol\n"
+    );
+}
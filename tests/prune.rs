@@ -0,0 +1,49 @@
+//! End-to-end tests of `add-remote prune`, which the forge's 404s for deleted forks drive: the
+//! remotes for those forks go, unless a local branch still tracks one.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::{ssh_url, Sandbox};
+
+/// The remotes the sandbox starts with, as listed by `git remote`.  Only `alice` has a fork on the
+/// forge, and the local branch `fix` tracks `tracked`.
+const ALL_REMOTES: &str = "alice\ngone\norigin\ntracked\n";
+
+fn sandbox_with_gone_forks() -> Sandbox {
+    let sandbox = Sandbox::new();
+    sandbox.create_forge_repo("alice", &["main"]);
+    for owner in ["alice", "gone", "tracked"] {
+        sandbox.git(&["remote", "add", owner, &ssh_url(owner)]);
+    }
+    sandbox.git(&["branch", "fix"]);
+    sandbox.git(&["config", "branch.fix.remote", "tracked"]);
+    sandbox.git(&["config", "branch.fix.merge", "refs/heads/fix"]);
+    sandbox
+}
+
+#[test]
+fn removes_gone_forks_but_keeps_tracked_ones() {
+    let sandbox = sandbox_with_gone_forks();
+    let printed = sandbox.add_remote_ok(&["prune"], "y\n");
+
+    assert!(printed.contains("Keeping 'tracked' since local branches track it"));
+    assert_eq!(sandbox.git(&["remote"]), "alice\norigin\ntracked\n");
+    assert_eq!(sandbox.git(&["config", "branch.fix.remote"]), "tracked\n");
+}
+
+#[test]
+fn keeps_gone_forks_when_declined() {
+    let sandbox = sandbox_with_gone_forks();
+    sandbox.add_remote_ok(&["prune"], "n\n");
+    assert_eq!(sandbox.git(&["remote"]), ALL_REMOTES);
+}
+
+#[test]
+fn dry_run_prints_the_removal() {
+    let sandbox = sandbox_with_gone_forks();
+    let printed = sandbox.add_remote_dry_run(&["prune", "--dry-run"], "y\n");
+    assert!(printed.ends_with("\ngit remote remove gone\n"));
+    assert_eq!(sandbox.git(&["remote"]), ALL_REMOTES);
+}
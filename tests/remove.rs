@@ -0,0 +1,72 @@
+//! `add-remote remove` run for real against a sandboxed repository holding two aliased forks, with
+//! and without `--forget-alias`.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::{ssh_url, Sandbox};
+
+/// Adds and fetches the remotes `ally` for `alice`'s fork and `bob` for `bob`'s, configuring each
+/// remote's name as its owner's alias.
+fn sandbox_with_aliased_forks() -> Sandbox {
+    let sandbox = Sandbox::new();
+    for (alias, owner) in [("ally", "alice"), ("bob", "bob")] {
+        sandbox.create_forge_repo(owner, &["main"]);
+        sandbox.git(&["remote", "add", alias, &ssh_url(owner)]);
+        sandbox.git(&["config", &format!("add-remote.forkAlias.{owner}"), alias]);
+    }
+    sandbox.git(&["fetch", "--quiet", "--all"]);
+    sandbox
+}
+
+/// Returns the local Git config entries whose keys match `pattern`.
+fn config_matching(sandbox: &Sandbox, pattern: &str) -> String {
+    sandbox.git(&["config", "--local", "--get-regexp", pattern])
+}
+
+#[test]
+fn removes_the_remote_and_its_owners_alias() {
+    let sandbox = sandbox_with_aliased_forks();
+    sandbox.add_remote_ok(&["remove", "ally", "--forget-alias"], "");
+
+    assert_eq!(sandbox.git(&["remote"]), "bob\norigin\n");
+    assert_eq!(
+        config_matching(&sandbox, "^remote\\."),
+        format!(
+            "remote.origin.url {}\n\
+             remote.origin.fetch +refs/heads/*:refs/remotes/origin/*\n\
+             remote.bob.url {}\n\
+             remote.bob.fetch +refs/heads/*:refs/remotes/bob/*\n",
+            ssh_url("upstream"),
+            ssh_url("bob")
+        )
+    );
+    assert_eq!(
+        config_matching(&sandbox, "^add-remote\\.forkAlias\\."),
+        "add-remote.forkAlias.bob bob\n"
+    );
+    assert!(!sandbox.git(&["branch", "--remotes"]).contains("ally/main"));
+}
+
+#[test]
+fn keeps_the_alias_when_declined() {
+    let sandbox = sandbox_with_aliased_forks();
+    sandbox.add_remote_ok(&["remove", "ally"], "n\n");
+
+    assert_eq!(sandbox.git(&["remote"]), "bob\norigin\n");
+    assert_eq!(
+        config_matching(&sandbox, "^add-remote\\.forkAlias\\."),
+        "add-remote.forkAlias.alice ally\nadd-remote.forkAlias.bob bob\n"
+    );
+}
+
+#[test]
+fn dry_run_prints_both_commands() {
+    let sandbox = sandbox_with_aliased_forks();
+    assert_eq!(
+        sandbox.add_remote_dry_run(&["remove", "ally", "--forget-alias", "--dry-run"], ""),
+        "git remote remove ally\n\
+         git config --local --unset-all add-remote.forkAlias.alice\n"
+    );
+}
@@ -0,0 +1,76 @@
+//! Runs `add-remote rename-all` over a remote named `alice-fork` whose owner's alias is `ally`,
+//! checking the rename carries the local branch tracking it along, and never clobbers a remote
+//! which already has the alias as its name.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::{ssh_url, Sandbox};
+
+fn sandbox_with_misnamed_remote() -> Sandbox {
+    let sandbox = Sandbox::new();
+    sandbox.create_forge_repo("alice", &["main"]);
+    sandbox.git(&["remote", "add", "alice-fork", &ssh_url("alice")]);
+    sandbox.git(&["fetch", "--quiet", "alice-fork"]);
+    // `fix` tracks the remote, so renaming it must rewrite the branch's upstream.
+    sandbox.git(&["branch", "--quiet", "--track", "fix", "alice-fork/main"]);
+    sandbox.git(&["config", "add-remote.forkAlias.alice", "ally"]);
+    sandbox
+}
+
+#[test]
+fn renames_and_rewrites_upstreams() {
+    let sandbox = sandbox_with_misnamed_remote();
+    sandbox.add_remote_ok(&["rename-all"], "y\n");
+
+    assert_eq!(sandbox.git(&["remote"]), "ally\norigin\n");
+    assert_eq!(sandbox.git(&["config", "branch.fix.remote"]), "ally\n");
+    assert_eq!(
+        sandbox.git(&["rev-parse", "--abbrev-ref", "fix@{upstream}"]),
+        "ally/main\n"
+    );
+}
+
+#[test]
+fn keeps_the_name_when_declined() {
+    let sandbox = sandbox_with_misnamed_remote();
+    sandbox.add_remote_ok(&["rename-all"], "n\n");
+
+    assert_eq!(sandbox.git(&["remote"]), "alice-fork\norigin\n");
+    assert_eq!(
+        sandbox.git(&["config", "branch.fix.remote"]),
+        "alice-fork\n"
+    );
+}
+
+#[test]
+fn skips_aliases_taken_by_other_remotes() {
+    let sandbox = sandbox_with_misnamed_remote();
+    sandbox.create_forge_repo("bob", &["main"]);
+    sandbox.git(&["remote", "add", "ally", &ssh_url("bob")]);
+    let printed = sandbox.add_remote_ok(&["rename-all"], "");
+
+    assert!(
+        printed.contains("Not renaming 'alice-fork' since a remote named 'ally' already exists.")
+    );
+    assert_eq!(sandbox.git(&["remote"]), "alice-fork\nally\norigin\n");
+    assert_eq!(
+        sandbox.git(&["remote", "get-url", "ally"]),
+        ssh_url("bob") + "\n"
+    );
+    assert_eq!(
+        sandbox.git(&["config", "branch.fix.remote"]),
+        "alice-fork\n"
+    );
+}
+
+#[test]
+fn renames_when_only_the_case_differs() {
+    let sandbox = sandbox_with_misnamed_remote();
+    sandbox.git(&["remote", "rename", "alice-fork", "Ally"]);
+    sandbox.add_remote_ok(&["rename-all"], "y\n");
+
+    assert_eq!(sandbox.git(&["remote"]), "ally\norigin\n");
+    assert_eq!(sandbox.git(&["config", "branch.fix.remote"]), "ally\n");
+}